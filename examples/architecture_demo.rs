@@ -116,6 +116,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         capabilities: vec![],
         name: "demo-agent".to_string(),
         models: vec![],
+        ..Default::default()
     };
 
     // Create minimal network config
@@ -20,6 +20,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         models: vec![],
         // Note: AgentConfig structure has changed, removing unused fields for now
         // id, network_port, and resource_limits are not currently part of AgentConfig
+        ..Default::default()
     };
 
     println!("✅ Configuration created");
@@ -66,6 +67,7 @@ mod tests {
             capabilities: vec![],
             name: "test-agent".to_string(),
             models: vec![],
+            ..Default::default()
         };
 
         let agent = DefaultAgent::new(config).await?;
@@ -89,6 +91,7 @@ mod tests {
             capabilities: vec![],
             name: "lifecycle-agent".to_string(),
             models: vec![],
+            ..Default::default()
         };
 
         let agent = DefaultAgent::new(config).await?;
@@ -59,6 +59,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
             capabilities: vec![],
             name: "bootstrap-node".to_string(),
             models: vec![],
+            ..Default::default()
         };
 
         // let service_registry = Arc::new(ServiceRegistry::new());
@@ -154,6 +155,7 @@ mod tests {
                 capabilities: vec![],
                 name: "test-bootstrap".to_string(),
                 models: vec![],
+                ..Default::default()
             };
 
             let agent = DefaultAgent::new(config).await?;
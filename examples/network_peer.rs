@@ -68,6 +68,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
             capabilities: vec![],
             name: "peer-node".to_string(),
             models: vec![],
+            ..Default::default()
         };
 
         let agent = DefaultAgent::new(config).await?;
@@ -233,6 +234,7 @@ mod tests {
                 capabilities: vec![],
                 name: "test-peer".to_string(),
                 models: vec![],
+                ..Default::default()
             };
 
             let agent = DefaultAgent::new(config).await?;
@@ -27,6 +27,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         capabilities: vec![TaskType::TextProcessing],
         name: "worker-agent".to_string(),
         models: vec![],
+        ..Default::default()
     };
     // Note: In a real scenario, we'd need to configure listening ports explicitly
     // to ensure they don't collide, but the random port selection in DefaultAgent helps.
@@ -46,6 +47,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "submitter-agent".to_string(),
         models: vec![],
+        ..Default::default()
     };
     let submitter = DefaultAgent::new(config_a).await?;
     submitter.start().await?;
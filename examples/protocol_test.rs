@@ -22,6 +22,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "receiver-agent".to_string(),
         models: vec![],
+        ..Default::default()
     };
     let agent = DefaultAgent::new(config).await?;
     agent.start().await?;
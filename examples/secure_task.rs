@@ -23,6 +23,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "worker".to_string(),
         models: vec![],
+        ..Default::default()
     })
     .await?;
     worker.start().await?;
@@ -31,6 +32,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "submitter".to_string(),
         models: vec![],
+        ..Default::default()
     })
     .await?;
     submitter.start().await?;
@@ -92,6 +94,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "rogue".to_string(),
         models: vec![],
+        ..Default::default()
     })
     .await?;
     rogue.start().await?;
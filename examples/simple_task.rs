@@ -23,6 +23,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         name: "task-processor".to_string(),
         models: vec![],
         // id, network_port, resource_limits moved or removed
+        ..Default::default()
     };
 
     // Define resource limits for the example context (not used by agent core yet)
@@ -236,6 +237,7 @@ mod tests {
             capabilities: vec![],
             name: "test-task-agent".to_string(),
             models: vec![],
+            ..Default::default()
         };
 
         // Limits omitted
@@ -273,6 +275,7 @@ mod tests {
             capabilities: vec![],
             name: "test-task-agent-priority".to_string(),
             models: vec![],
+            ..Default::default()
         };
 
         let agent = DefaultAgent::new(config).await?;
@@ -0,0 +1,287 @@
+#!/usr/bin/env python3
+"""
+Tiny BERT Fixture Generator
+Writes the tiny BERT model in tests/fixtures/tiny-bert (config.json,
+tokenizer.json, model.safetensors) and reference embeddings computed by a
+plain-Python forward pass, independent of candle.
+
+Weights come from a fixed-seed generator, so re-running the script reproduces
+the checked-in files exactly.
+"""
+
+import json
+import math
+import re
+import struct
+from pathlib import Path
+
+OUTPUT_DIR = Path(__file__).resolve().parent.parent / "tests" / "fixtures" / "tiny-bert"
+
+SPECIAL_TOKENS = ["[PAD]", "[UNK]", "[CLS]", "[SEP]"]
+WORDS = [
+    "the", "a", "agent", "peer", "task", "model", "network", "text", "runs",
+    "embeds", "on", "is", "fast", "slow", "rust", "hello", "world", ".", ",",
+    "##s", "##ing",
+]
+VOCAB = {token: i for i, token in enumerate(SPECIAL_TOKENS + WORDS)}
+
+CONFIG = {
+    "architectures": ["BertModel"],
+    "model_type": "bert",
+    "vocab_size": len(VOCAB),
+    "hidden_size": 8,
+    "num_hidden_layers": 2,
+    "num_attention_heads": 2,
+    "intermediate_size": 16,
+    "hidden_act": "gelu",
+    "hidden_dropout_prob": 0.1,
+    "attention_probs_dropout_prob": 0.1,
+    "max_position_embeddings": 16,
+    "type_vocab_size": 2,
+    "initializer_range": 0.02,
+    "layer_norm_eps": 1e-12,
+    "pad_token_id": 0,
+}
+
+# Texts with reference embeddings; they cover word pieces, unknown words and
+# truncation to `max_position_embeddings`
+TEXTS = [
+    "Hello world.",
+    "The agent runs a task on the network.",
+    "Agents embeds zebra text, fast",
+    "the peer runs the model on the network , the agent runs the task on a peer",
+]
+
+
+class Rng:
+    """xorshift64* generator, for weights that do not depend on Python's `random`."""
+
+    def __init__(self, seed):
+        self.state = seed
+
+    def uniform(self):
+        x = self.state
+        x ^= (x >> 12)
+        x ^= (x << 25) & 0xFFFFFFFFFFFFFFFF
+        x ^= (x >> 27)
+        self.state = x
+        return ((x * 0x2545F4914F6CDD1D) & 0xFFFFFFFFFFFFFFFF) / 2.0**64
+
+
+def f32(value):
+    """Rounds to the nearest float32, the precision the weights are stored with."""
+    return struct.unpack("<f", struct.pack("<f", value))[0]
+
+
+def make_weights():
+    rng = Rng(0x9E3779B97F4A7C15)
+    hidden = CONFIG["hidden_size"]
+    inter = CONFIG["intermediate_size"]
+    weights = {}
+
+    def tensor(name, shape, center=0.0, scale=0.5):
+        count = math.prod(shape)
+        values = [f32(center + scale * (2.0 * rng.uniform() - 1.0)) for _ in range(count)]
+        weights[name] = (shape, values)
+
+    def layer_norm(prefix):
+        tensor(prefix + ".weight", [hidden], center=1.0, scale=0.2)
+        tensor(prefix + ".bias", [hidden], scale=0.1)
+
+    def linear(prefix, inputs, outputs):
+        tensor(prefix + ".weight", [outputs, inputs])
+        tensor(prefix + ".bias", [outputs], scale=0.1)
+
+    tensor("embeddings.word_embeddings.weight", [CONFIG["vocab_size"], hidden])
+    tensor("embeddings.position_embeddings.weight", [CONFIG["max_position_embeddings"], hidden])
+    tensor("embeddings.token_type_embeddings.weight", [CONFIG["type_vocab_size"], hidden])
+    layer_norm("embeddings.LayerNorm")
+    for layer in range(CONFIG["num_hidden_layers"]):
+        prefix = "encoder.layer.%d." % layer
+        for name in ("query", "key", "value"):
+            linear(prefix + "attention.self." + name, hidden, hidden)
+        linear(prefix + "attention.output.dense", hidden, hidden)
+        layer_norm(prefix + "attention.output.LayerNorm")
+        linear(prefix + "intermediate.dense", hidden, inter)
+        linear(prefix + "output.dense", inter, hidden)
+        layer_norm(prefix + "output.LayerNorm")
+    return weights
+
+
+def write_safetensors(path, weights):
+    header = {"__metadata__": {"format": "pt"}}
+    offset = 0
+    for name, (shape, values) in weights.items():
+        size = 4 * len(values)
+        header[name] = {"dtype": "F32", "shape": shape, "data_offsets": [offset, offset + size]}
+        offset += size
+    encoded = json.dumps(header, separators=(",", ":")).encode()
+    encoded += b" " * (-len(encoded) % 8)
+    with open(path, "wb") as f:
+        f.write(struct.pack("<Q", len(encoded)))
+        f.write(encoded)
+        for _, values in weights.values():
+            f.write(struct.pack("<%df" % len(values), *values))
+
+
+def tokenizer_json():
+    return {
+        "version": "1.0",
+        "truncation": None,
+        "padding": None,
+        "added_tokens": [
+            {
+                "id": VOCAB[token],
+                "content": token,
+                "single_word": False,
+                "lstrip": False,
+                "rstrip": False,
+                "normalized": False,
+                "special": True,
+            }
+            for token in SPECIAL_TOKENS
+        ],
+        "normalizer": {
+            "type": "BertNormalizer",
+            "clean_text": True,
+            "handle_chinese_chars": True,
+            "strip_accents": None,
+            "lowercase": True,
+        },
+        "pre_tokenizer": {"type": "BertPreTokenizer"},
+        "post_processor": {
+            "type": "BertProcessing",
+            "sep": ["[SEP]", VOCAB["[SEP]"]],
+            "cls": ["[CLS]", VOCAB["[CLS]"]],
+        },
+        "decoder": None,
+        "model": {
+            "type": "WordPiece",
+            "unk_token": "[UNK]",
+            "continuing_subword_prefix": "##",
+            "max_input_chars_per_word": 100,
+            "vocab": VOCAB,
+        },
+    }
+
+
+def tokenize(text):
+    """BERT uncased tokenization of ASCII text, truncated like the tokenizer."""
+    ids = [VOCAB["[CLS]"]]
+    for word in re.findall(r"\w+|[^\w\s]", text.lower()):
+        pieces, start = [], 0
+        while start < len(word):
+            end = len(word)
+            while end > start:
+                piece = word[start:end] if start == 0 else "##" + word[start:end]
+                if piece in VOCAB:
+                    break
+                end -= 1
+            if end == start:
+                pieces = [VOCAB["[UNK]"]]
+                break
+            pieces.append(VOCAB[piece])
+            start = end
+        ids.extend(pieces)
+    ids = ids[: CONFIG["max_position_embeddings"] - 1]
+    return ids + [VOCAB["[SEP]"]]
+
+
+def matrix(weights, name):
+    (rows, cols), values = weights[name]
+    return [values[r * cols:(r + 1) * cols] for r in range(rows)]
+
+
+def linear(weights, prefix, xs):
+    w = matrix(weights, prefix + ".weight")
+    b = weights[prefix + ".bias"][1]
+    return [[sum(wi * xi for wi, xi in zip(row, x)) + bias for row, bias in zip(w, b)] for x in xs]
+
+
+def layer_norm(weights, prefix, xs):
+    gamma = weights[prefix + ".weight"][1]
+    beta = weights[prefix + ".bias"][1]
+    out = []
+    for x in xs:
+        mean = sum(x) / len(x)
+        var = sum((v - mean) ** 2 for v in x) / len(x)
+        std = math.sqrt(var + CONFIG["layer_norm_eps"])
+        out.append([(v - mean) / std * g + b for v, g, b in zip(x, gamma, beta)])
+    return out
+
+
+def gelu(x):
+    return 0.5 * x * (1.0 + math.erf(x / math.sqrt(2.0)))
+
+
+def add(xs, ys):
+    return [[a + b for a, b in zip(x, y)] for x, y in zip(xs, ys)]
+
+
+def forward(weights, ids):
+    hidden = CONFIG["hidden_size"]
+    heads = CONFIG["num_attention_heads"]
+    head_size = hidden // heads
+    word = matrix(weights, "embeddings.word_embeddings.weight")
+    position = matrix(weights, "embeddings.position_embeddings.weight")
+    token_type = matrix(weights, "embeddings.token_type_embeddings.weight")
+    xs = [[w + p + t for w, p, t in zip(word[i], position[pos], token_type[0])]
+          for pos, i in enumerate(ids)]
+    xs = layer_norm(weights, "embeddings.LayerNorm", xs)
+
+    for layer in range(CONFIG["num_hidden_layers"]):
+        prefix = "encoder.layer.%d." % layer
+        q = linear(weights, prefix + "attention.self.query", xs)
+        k = linear(weights, prefix + "attention.self.key", xs)
+        v = linear(weights, prefix + "attention.self.value", xs)
+        context = [[0.0] * hidden for _ in xs]
+        for h in range(heads):
+            cols = range(h * head_size, (h + 1) * head_size)
+            for i in range(len(xs)):
+                scores = [sum(q[i][c] * k[j][c] for c in cols) / math.sqrt(head_size)
+                          for j in range(len(xs))]
+                top = max(scores)
+                exps = [math.exp(s - top) for s in scores]
+                total = sum(exps)
+                for c in cols:
+                    context[i][c] = sum(e / total * v[j][c] for j, e in enumerate(exps))
+        attention = linear(weights, prefix + "attention.output.dense", context)
+        xs = layer_norm(weights, prefix + "attention.output.LayerNorm", add(attention, xs))
+        inter = [[gelu(v) for v in x] for x in linear(weights, prefix + "intermediate.dense", xs)]
+        out = linear(weights, prefix + "output.dense", inter)
+        xs = layer_norm(weights, prefix + "output.LayerNorm", add(out, xs))
+    return xs
+
+
+def normalize(x):
+    norm = math.sqrt(sum(v * v for v in x))
+    return [v / norm for v in x]
+
+
+def main():
+    OUTPUT_DIR.mkdir(parents=True, exist_ok=True)
+    weights = make_weights()
+    write_safetensors(OUTPUT_DIR / "model.safetensors", weights)
+    (OUTPUT_DIR / "config.json").write_text(json.dumps(CONFIG, indent=2) + "\n")
+    (OUTPUT_DIR / "tokenizer.json").write_text(json.dumps(tokenizer_json(), indent=2) + "\n")
+
+    references = []
+    for text in TEXTS:
+        ids = tokenize(text)
+        states = forward(weights, ids)
+        mean = [sum(col) / len(states) for col in zip(*states)]
+        cls = states[0]
+        references.append({
+            "text": text,
+            "input_ids": ids,
+            "mean": mean,
+            "mean_normalized": normalize(mean),
+            "cls": cls,
+            "cls_normalized": normalize(cls),
+        })
+    (OUTPUT_DIR / "reference.json").write_text(json.dumps(references, indent=2) + "\n")
+    print("Wrote tiny BERT fixture to %s" % OUTPUT_DIR)
+
+
+if __name__ == "__main__":
+    main()
@@ -1,17 +1,66 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 #[cfg(feature = "ai")]
-use candle_core::{Device, Tensor};
+use anyhow::Context;
 #[cfg(feature = "ai")]
-use tokenizers::Tokenizer;
+use candle_core::{DType, Device, IndexOp, Tensor};
+#[cfg(feature = "ai")]
+use candle_nn::VarBuilder;
+#[cfg(feature = "ai")]
+use candle_transformers::models::bert::{BertModel, Config};
+#[cfg(feature = "ai")]
+use std::collections::HashMap;
+#[cfg(feature = "ai")]
+use std::path::PathBuf;
+#[cfg(feature = "ai")]
+use std::sync::Arc;
+#[cfg(feature = "ai")]
+use tokenizers::{Tokenizer, TruncationParams};
+#[cfg(feature = "ai")]
+use tokio::sync::Mutex;
+
+/// How the token states of a text are pooled into its embedding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pooling {
+    /// Average of all token states.
+    #[default]
+    Mean,
+    /// State of the leading `[CLS]` token.
+    Cls,
+}
+
+/// Options of [`InferenceEngine::embed_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingOptions {
+    /// How token states are pooled.
+    pub pooling: Pooling,
+    /// Whether the embedding is scaled to unit L2 norm.
+    pub normalize: bool,
+}
+
+impl Default for EmbeddingOptions {
+    fn default() -> Self {
+        Self {
+            pooling: Pooling::Mean,
+            normalize: true,
+        }
+    }
+}
 
 /// A simple inference engine wrapper.
 ///
-/// Currently supports basic text feature extraction (embedding) using a BERT-like model.
+/// Computes text embeddings with BERT models in the layout `ModelManager`
+/// downloads (`config.json`, `tokenizer.json` and `model.safetensors`), on
+/// CPU. Models are loaded on first use and kept for the engine's lifetime.
 pub struct InferenceEngine {
     #[cfg(feature = "ai")]
     device: Device,
+    #[cfg(feature = "ai")]
+    models: Mutex<HashMap<PathBuf, Arc<BertEmbedder>>>,
 }
 
 impl Default for InferenceEngine {
@@ -29,55 +78,123 @@ impl InferenceEngine {
         Self {
             #[cfg(feature = "ai")]
             device,
+            #[cfg(feature = "ai")]
+            models: Mutex::new(HashMap::new()),
         }
     }
 
-    /// Loads a model and performs a simple embedding task.
-    ///
-    /// This is a simplified example that assumes a BERT architecture.
-    /// In a real system, we'd have model-specific pipelines.
-    #[cfg(feature = "ai")]
+    /// Embeds `text` with the model at `model_path`, mean-pooled and
+    /// L2-normalized.
     pub async fn embed(&self, model_path: &Path, text: &str) -> Result<Vec<f32>> {
-        // 1. Load Tokenizer
-        let tokenizer_path = model_path.join("tokenizer.json");
-        let tokenizer = Tokenizer::from_file(&tokenizer_path)
-            .map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
+        self.embed_with(model_path, text, &EmbeddingOptions::default())
+            .await
+    }
 
-        // 2. Tokenize
-        let tokens = tokenizer
-            .encode(text, true)
-            .map_err(|e| anyhow::anyhow!("Failed to tokenize: {}", e))?;
-        let token_ids = tokens.get_ids();
-        let _input_ids = Tensor::new(token_ids, &self.device)?.unsqueeze(0)?;
+    /// Embeds `text` with the BERT model at `model_path`.
+    ///
+    /// Texts longer than the model's `max_position_embeddings` are truncated.
+    #[cfg(feature = "ai")]
+    pub async fn embed_with(
+        &self,
+        model_path: &Path,
+        text: &str,
+        options: &EmbeddingOptions,
+    ) -> Result<Vec<f32>> {
+        let model = self.load(model_path).await?;
+        let text = text.to_string();
+        let options = *options;
+        tokio::task::spawn_blocking(move || model.embed(&text, options)).await?
+    }
 
-        // 3. Load Model (Placeholder for actual model loading)
-        // For this MVP step, we will verify the model file exists and return a dummy embedding
-        // derived from the input length, as loading a full BERT model requires more scaffolding
-        // (defining the architecture struct in Rust or using `candle-transformers`).
+    /// Mock embedding function when AI features are disabled.
+    #[cfg(not(feature = "ai"))]
+    pub async fn embed_with(
+        &self,
+        _model_path: &Path,
+        _text: &str,
+        _options: &EmbeddingOptions,
+    ) -> Result<Vec<f32>> {
+        // Mock embedding for when AI feature is disabled (e.g. tests)
+        Ok(vec![0.1, 0.2, 0.3])
+    }
 
-        // Ensure model file exists
-        let weights_path = model_path.join("model.safetensors");
-        if !weights_path.exists() {
-            return Err(anyhow::anyhow!(
-                "Model weights not found at {:?}",
-                weights_path
-            ));
+    /// Returns the model at `model_path`, loading it on first use.
+    #[cfg(feature = "ai")]
+    async fn load(&self, model_path: &Path) -> Result<Arc<BertEmbedder>> {
+        // Held while loading, so concurrent embeds load a model only once
+        let mut models = self.models.lock().await;
+        if let Some(model) = models.get(model_path) {
+            return Ok(model.clone());
         }
 
-        // TODO: Import and use actual BERT implementation from candle-transformers or local definition.
-        // For now, to satisfy the "Real Download" story verification without implementing full BERT:
-        // We simulate embedding based on text hash/length to show "processing".
+        let path = model_path.to_path_buf();
+        let device = self.device.clone();
+        let model = Arc::new(
+            tokio::task::spawn_blocking(move || BertEmbedder::load(&path, &device)).await??,
+        );
+        models.insert(model_path.to_path_buf(), model.clone());
+        Ok(model)
+    }
+}
 
-        let len = token_ids.len() as f32;
-        let dummy_embedding = vec![0.1 * len, 0.2 * len, 0.3 * len];
+/// A loaded BERT model and its tokenizer.
+#[cfg(feature = "ai")]
+struct BertEmbedder {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    device: Device,
+}
 
-        Ok(dummy_embedding)
+#[cfg(feature = "ai")]
+impl BertEmbedder {
+    fn load(model_path: &Path, device: &Device) -> Result<Self> {
+        let config_path = model_path.join("config.json");
+        let config = std::fs::read(&config_path)
+            .with_context(|| format!("Model config not found at {:?}", config_path))?;
+        let config: Config = serde_json::from_slice(&config).context("Invalid model config")?;
+
+        let tokenizer_path = model_path.join("tokenizer.json");
+        let mut tokenizer = Tokenizer::from_file(&tokenizer_path)
+            .map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: config.max_position_embeddings,
+                ..Default::default()
+            }))
+            .map_err(|e| anyhow::anyhow!("Failed to configure tokenizer: {}", e))?;
+
+        let weights_path = model_path.join("model.safetensors");
+        let weights = std::fs::read(&weights_path)
+            .with_context(|| format!("Model weights not found at {:?}", weights_path))?;
+        let vb = VarBuilder::from_buffered_safetensors(weights, DType::F32, device)?;
+        let model = BertModel::load(vb, &config).context("Failed to load BERT weights")?;
+
+        Ok(Self {
+            model,
+            tokenizer,
+            device: device.clone(),
+        })
     }
 
-    /// Mock embedding function when AI features are disabled.
-    #[cfg(not(feature = "ai"))]
-    pub async fn embed(&self, _model_path: &Path, _text: &str) -> Result<Vec<f32>> {
-        // Mock embedding for when AI feature is disabled (e.g. tests)
-        Ok(vec![0.1, 0.2, 0.3])
+    fn embed(&self, text: &str, options: EmbeddingOptions) -> Result<Vec<f32>> {
+        let tokens = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|e| anyhow::anyhow!("Failed to tokenize: {}", e))?;
+        let input_ids = Tensor::new(tokens.get_ids(), &self.device)?.unsqueeze(0)?;
+        let token_type_ids = Tensor::new(tokens.get_type_ids(), &self.device)?.unsqueeze(0)?;
+
+        // (1, tokens, hidden); a single unpadded text needs no attention mask
+        let states = self.model.forward(&input_ids, &token_type_ids, None)?;
+        let pooled = match options.pooling {
+            Pooling::Mean => states.mean(1)?,
+            Pooling::Cls => states.i((.., 0))?,
+        };
+        let pooled = if options.normalize {
+            pooled.broadcast_div(&pooled.sqr()?.sum_keepdim(1)?.sqrt()?)?
+        } else {
+            pooled
+        };
+        Ok(pooled.squeeze(0)?.to_vec1()?)
     }
 }
@@ -9,5 +9,5 @@ pub mod engine;
 /// Model manager for downloading and caching models.
 pub mod model_manager;
 
-pub use engine::InferenceEngine;
+pub use engine::{EmbeddingOptions, InferenceEngine, Pooling};
 pub use model_manager::ModelManager;
@@ -1,3 +1,4 @@
+use crate::agent::execution::{CancellationToken, Interrupted};
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 use tokio::fs;
@@ -64,6 +65,23 @@ impl ModelManager {
     /// Defaults to `prajjwal1/bert-tiny` if model_name is generic.
     #[instrument(skip(self))]
     pub async fn ensure_model(&self, model_name: &str) -> Result<PathBuf> {
+        self.ensure_model_cancellable(model_name, &CancellationToken::new())
+            .await
+    }
+
+    /// Downloads a model if it's not already cached, stopping when `cancellation`
+    /// is cancelled.
+    ///
+    /// Files are downloaded into a partial directory that only replaces the
+    /// model directory once complete, and that is removed if the download is
+    /// cancelled or fails, so an interrupted download is never taken for a
+    /// cached model.
+    #[instrument(skip(self, cancellation))]
+    pub async fn ensure_model_cancellable(
+        &self,
+        model_name: &str,
+        cancellation: &CancellationToken,
+    ) -> Result<PathBuf> {
         self.init().await?;
 
         // Sanitize model name for local storage
@@ -76,16 +94,30 @@ impl ModelManager {
         }
 
         info!("Model '{}' not found. Starting download...", model_name);
+        let partial_path = self.models_dir.join(format!(".{}.partial", safe_name));
 
         #[cfg(feature = "ai")]
-        {
-            self.download_from_hf(model_name, &model_path).await?;
-        }
+        let downloaded = self
+            .download_from_hf(model_name, &partial_path, cancellation)
+            .await;
 
         #[cfg(not(feature = "ai"))]
-        {
+        let downloaded = {
             warn!("AI feature not enabled. Using mock download.");
-            self.mock_download(model_name, &model_path).await?;
+            self.mock_download(model_name, &partial_path, cancellation)
+                .await
+        };
+
+        if let Err(e) = downloaded {
+            let _ = fs::remove_dir_all(&partial_path).await;
+            return Err(e);
+        }
+        if let Err(e) = fs::rename(&partial_path, &model_path).await {
+            let _ = fs::remove_dir_all(&partial_path).await;
+            // Fine if a concurrent download of the same model finished first
+            if !self.is_cached(model_name) {
+                return Err(e).context("Failed to move downloaded model into place");
+            }
         }
 
         info!(
@@ -97,7 +129,12 @@ impl ModelManager {
 
     /// Downloads model files from Hugging Face Hub.
     #[cfg(feature = "ai")]
-    async fn download_from_hf(&self, model_id: &str, destination: &Path) -> Result<()> {
+    async fn download_from_hf(
+        &self,
+        model_id: &str,
+        destination: &Path,
+        cancellation: &CancellationToken,
+    ) -> Result<()> {
         let api = Api::new().context("Failed to create Hugging Face API client")?;
         let repo = api.repo(Repo::new(model_id.to_string(), RepoType::Model));
 
@@ -109,6 +146,9 @@ impl ModelManager {
         fs::create_dir_all(destination).await?;
 
         for file in files {
+            if cancellation.is_cancelled() {
+                return Err(Interrupted::Cancelled.into());
+            }
             info!("Downloading {}...", file);
             let source_path = repo
                 .get(file)
@@ -159,14 +199,22 @@ impl ModelManager {
 
     /// Simulates downloading a model for environments without AI features or for testing.
     #[cfg(not(feature = "ai"))]
-    async fn mock_download(&self, _model_name: &str, path: &PathBuf) -> Result<()> {
-        // Simulate network delay
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-
+    async fn mock_download(
+        &self,
+        _model_name: &str,
+        path: &Path,
+        cancellation: &CancellationToken,
+    ) -> Result<()> {
         fs::create_dir_all(path).await?;
-
-        // Create dummy files
         fs::write(path.join("config.json"), b"{}").await?;
+
+        // Simulate network delay
+        tokio::select! {
+            _ = cancellation.cancelled() => return Err(Interrupted::Cancelled.into()),
+            _ = tokio::time::sleep(tokio::time::Duration::from_millis(500)) => {}
+        }
+
+        // Create the remaining dummy files
         fs::write(path.join("tokenizer.json"), b"{}").await?;
         fs::write(path.join("model.safetensors"), b"dummy model content")
             .await
@@ -198,6 +246,35 @@ mod tests {
         assert!(status.path.is_some());
     }
 
+    #[tokio::test]
+    async fn test_cancelled_download_leaves_nothing_behind() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ModelManager::new(temp_dir.path());
+        let model_name = "cancelled-model-bin";
+
+        let cancellation = CancellationToken::new();
+        let canceller = {
+            let cancellation = cancellation.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+                cancellation.cancel();
+            })
+        };
+        let err = manager
+            .ensure_model_cancellable(model_name, &cancellation)
+            .await
+            .unwrap_err();
+        canceller.await.unwrap();
+
+        assert_eq!(
+            err.downcast_ref::<Interrupted>(),
+            Some(&Interrupted::Cancelled)
+        );
+        assert!(!manager.is_cached(model_name));
+        let mut entries = fs::read_dir(temp_dir.path().join("models")).await.unwrap();
+        assert!(entries.next_entry().await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_is_cached() {
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,182 @@
+//! Call-for-proposals round run before dispatching a task.
+//!
+//! With `BiddingConfig::enabled`, `Agent::dispatch_task` broadcasts a
+//! `TaskOffer` instead of picking a peer from capability lists alone. Capable
+//! peers answer with a `TaskBid` describing how soon they could start the task,
+//! how many tasks they have queued and what they ask for it. Once every invited
+//! peer has bid, or `BiddingConfig::deadline` passed, the best [`Bid`] wins and
+//! the task is sent to that peer. Without any bid, the agent falls back to its
+//! `PeerSelector`.
+//!
+//! Only tasks dispatched to a single peer are offered; redundant and sharded
+//! tasks go to several peers at once.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// A peer's proposal to run an offered task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bid {
+    /// Expected delay before the peer starts the task.
+    pub estimated_start: Duration,
+    /// Number of tasks queued at the peer.
+    pub queue_depth: usize,
+    /// Price the peer asks for running the task.
+    pub cost: f64,
+}
+
+impl Bid {
+    /// Orders bids from best to worst: earliest start, then lowest cost, then
+    /// shortest queue.
+    fn rank(&self, other: &Self) -> std::cmp::Ordering {
+        self.estimated_start
+            .cmp(&other.estimated_start)
+            .then(self.cost.total_cmp(&other.cost))
+            .then(self.queue_depth.cmp(&other.queue_depth))
+    }
+}
+
+/// Settings of the bidding protocol, for both requesting and bidding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BiddingConfig {
+    /// Whether tasks are offered for bids before being dispatched.
+    pub enabled: bool,
+    /// How long bids are collected.
+    pub deadline: Duration,
+    /// Price asked in this agent's own bids.
+    pub cost: f64,
+    /// Typical execution time of a task here, used to estimate start times.
+    pub typical_task_duration: Duration,
+}
+
+impl Default for BiddingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            deadline: Duration::from_millis(500),
+            cost: 1.0,
+            typical_task_duration: Duration::from_secs(1),
+        }
+    }
+}
+
+impl BiddingConfig {
+    /// Builds this agent's bid given its current load.
+    ///
+    /// A task starts right away while a worker is free; otherwise it waits one
+    /// typical task duration for every full round of workers ahead of it.
+    pub fn bid(&self, queue_depth: usize, active_workers: usize, max_concurrent: usize) -> Bid {
+        let max_concurrent = max_concurrent.max(1);
+        let ahead = queue_depth + active_workers;
+        let waves = (ahead + 1)
+            .saturating_sub(max_concurrent)
+            .div_ceil(max_concurrent);
+        Bid {
+            estimated_start: self.typical_task_duration * waves as u32,
+            queue_depth,
+            cost: self.cost,
+        }
+    }
+}
+
+/// Bids collected for one offered task.
+#[derive(Debug)]
+pub(crate) struct BidRound {
+    invited: HashSet<String>,
+    bids: HashMap<String, Bid>,
+    complete: Arc<Notify>,
+}
+
+impl BidRound {
+    /// Opens a round for the given peers.
+    pub(crate) fn new(invited: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            invited: invited.into_iter().collect(),
+            bids: HashMap::new(),
+            complete: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Returns the notifier woken once every invited peer has bid.
+    pub(crate) fn completion(&self) -> Arc<Notify> {
+        self.complete.clone()
+    }
+
+    /// Records a bid. Bids from peers that were not invited are ignored, and
+    /// a peer bidding again replaces its earlier bid.
+    pub(crate) fn record(&mut self, peer: &str, bid: Bid) {
+        if !self.invited.contains(peer) {
+            return;
+        }
+        self.bids.insert(peer.to_string(), bid);
+        if self.bids.len() == self.invited.len() {
+            self.complete.notify_one();
+        }
+    }
+
+    /// Returns the peer with the best bid, if any peer bid.
+    pub(crate) fn winner(&self) -> Option<String> {
+        self.bids
+            .iter()
+            // Break full ties by peer ID so the outcome does not depend on map order
+            .min_by(|(a_peer, a), (b_peer, b)| a.rank(b).then(a_peer.cmp(b_peer)))
+            .map(|(peer, _)| peer.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bid(start_ms: u64, queue_depth: usize, cost: f64) -> Bid {
+        Bid {
+            estimated_start: Duration::from_millis(start_ms),
+            queue_depth,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_estimated_start_counts_full_worker_rounds() {
+        let config = BiddingConfig {
+            typical_task_duration: Duration::from_secs(2),
+            ..Default::default()
+        };
+        assert_eq!(config.bid(0, 1, 2).estimated_start, Duration::ZERO);
+        assert_eq!(config.bid(0, 2, 2).estimated_start, Duration::from_secs(2));
+        assert_eq!(config.bid(2, 2, 2).estimated_start, Duration::from_secs(4));
+        assert_eq!(config.bid(3, 2, 2).estimated_start, Duration::from_secs(4));
+        assert_eq!(config.bid(4, 2, 2).estimated_start, Duration::from_secs(6));
+    }
+
+    #[test]
+    fn test_round_picks_earliest_then_cheapest_invited_bid() {
+        let mut round = BidRound::new(["slow", "cheap", "pricey"].map(String::from));
+        round.record("slow", bid(500, 0, 0.1));
+        round.record("pricey", bid(0, 3, 9.0));
+        round.record("cheap", bid(0, 5, 1.0));
+        // Not invited, e.g. a peer that already failed the task
+        round.record("stranger", bid(0, 0, 0.0));
+        assert_eq!(round.winner().as_deref(), Some("cheap"));
+    }
+
+    #[tokio::test]
+    async fn test_round_completes_once_every_peer_bid() {
+        let mut round = BidRound::new(["a", "b"].map(String::from));
+        let complete = round.completion();
+        assert!(round.winner().is_none());
+
+        round.record("a", bid(0, 0, 1.0));
+        round.record("a", bid(0, 0, 1.0));
+        let waited = tokio::time::timeout(Duration::from_millis(20), complete.notified()).await;
+        assert!(waited.is_err());
+
+        round.record("b", bid(0, 0, 1.0));
+        tokio::time::timeout(Duration::from_secs(1), complete.notified())
+            .await
+            .unwrap();
+    }
+}
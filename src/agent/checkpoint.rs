@@ -0,0 +1,100 @@
+//! Checkpoints of long-running tasks.
+//!
+//! An executor saves its intermediate state with
+//! [`ExecutionContext::checkpoint`](crate::agent::execution::ExecutionContext::checkpoint).
+//! The latest [`Checkpoint`] is kept with the task (`Task::checkpoint`) and
+//! stored locally, and is pushed to the requester of a remotely submitted task
+//! unless `AgentConfig::push_checkpoints` is off.
+//!
+//! A task carries its checkpoint when it is dispatched again, e.g. by
+//! `Agent::retry_task` after its peer went offline, so the peer that picks it
+//! up resumes from it
+//! ([`ExecutionContext::resume_from`](crate::agent::execution::ExecutionContext::resume_from))
+//! instead of starting over. Checkpoints are dropped once a task completes.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::mpsc;
+
+/// Saved state of a running task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Position of the checkpoint among those of the task, starting at 1 and
+    /// continuing across peers.
+    pub sequence: u64,
+    /// Executor-defined state to resume from.
+    pub state: serde_json::Value,
+    /// When the checkpoint was taken.
+    pub created_at: SystemTime,
+}
+
+impl Checkpoint {
+    /// Returns true if this checkpoint is more recent than `other`.
+    pub fn supersedes(&self, other: Option<&Checkpoint>) -> bool {
+        other.is_none_or(|other| self.sequence > other.sequence)
+    }
+}
+
+/// Handle through which an executor saves checkpoints while it runs.
+///
+/// Like `ProgressReporter`, saving never blocks the executor. Clones share the
+/// sequence numbering.
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointWriter {
+    tx: Option<mpsc::UnboundedSender<Checkpoint>>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl CheckpointWriter {
+    /// Creates a writer numbering its checkpoints after `last`, the sequence of
+    /// the checkpoint the task resumes from (0 if none), and the receiver its
+    /// checkpoints are delivered to.
+    pub fn channel(last: u64) -> (Self, mpsc::UnboundedReceiver<Checkpoint>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let writer = Self {
+            tx: Some(tx),
+            sequence: Arc::new(AtomicU64::new(last)),
+        };
+        (writer, rx)
+    }
+
+    /// Creates a writer that discards all checkpoints.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Saves a checkpoint of `state` and returns its sequence number.
+    pub fn save(&self, state: serde_json::Value) -> u64 {
+        let sequence = self.sequence.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(Checkpoint {
+                sequence,
+                state,
+                created_at: SystemTime::now(),
+            });
+        }
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_sequence_continues_after_resumed_checkpoint() {
+        let (writer, mut rx) = CheckpointWriter::channel(3);
+        assert_eq!(writer.save(json!({"step": 4})), 4);
+        assert_eq!(writer.clone().save(json!({"step": 5})), 5);
+
+        let first = rx.recv().await.unwrap();
+        let second = rx.recv().await.unwrap();
+        assert_eq!(first.state, json!({"step": 4}));
+        assert!(second.supersedes(Some(&first)));
+        assert!(!first.supersedes(Some(&second)));
+        assert!(first.supersedes(None));
+    }
+}
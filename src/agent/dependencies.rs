@@ -0,0 +1,273 @@
+//! Task dependency graphs.
+//!
+//! A task listing parents in `Task::depends_on` is held back by the `TaskManager`
+//! until every parent is `Completed`. If a parent fails, is cancelled or times
+//! out for good, the failure cascades to all of its (transitive) dependents.
+//!
+//! Values inside a dependent's `TaskPayload.data` can pipe in a parent's result
+//! with a reference object, which is replaced by the parent output before the
+//! task is released:
+//!
+//! ```json
+//! { "text": { "$from_task": "<parent task id>", "$pointer": "/reversed_text" } }
+//! ```
+//!
+//! `$pointer` is an optional JSON pointer (RFC 6901) into the parent result; without
+//! it the whole result is substituted.
+
+use crate::agent::task::{Task, TaskId, TaskStatus};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Key of a reference object naming the parent task whose output is used.
+pub const FROM_TASK_KEY: &str = "$from_task";
+/// Optional key of a reference object selecting part of the parent output.
+pub const POINTER_KEY: &str = "$pointer";
+
+/// Whether a task's dependencies allow it to run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DependencyState {
+    /// All parents completed; the task can be released.
+    Ready,
+    /// At least one parent is still pending (or unknown).
+    Blocked,
+    /// A parent failed; the task must fail with the given reason.
+    Failed(String),
+    /// A parent was cancelled; the task must be cancelled too.
+    Cancelled(TaskId),
+}
+
+/// Computes the dependency state of `task` from the current task table.
+///
+/// Failures take precedence over pending parents, so a dependent is failed as soon
+/// as any one of its parents fails.
+pub fn dependency_state(task: &Task, tasks: &HashMap<TaskId, Task>) -> DependencyState {
+    let mut blocked = false;
+
+    for parent_id in &task.depends_on {
+        let Some(parent) = tasks.get(parent_id) else {
+            // Parent not submitted yet
+            blocked = true;
+            continue;
+        };
+
+        match &parent.status {
+            TaskStatus::Completed(_) => {}
+            TaskStatus::Failed(reason) => {
+                return DependencyState::Failed(format!(
+                    "Dependency {} failed: {}",
+                    parent_id, reason
+                ));
+            }
+            TaskStatus::Cancelled => return DependencyState::Cancelled(*parent_id),
+            // Only remote parents are retried after a timeout, on another peer;
+            // a local deadline is final
+            TaskStatus::Timeout
+                if parent.assigned_to.is_none() || parent.retry_count >= parent.max_retries =>
+            {
+                return DependencyState::Failed(format!("Dependency {} timed out", parent_id));
+            }
+            _ => blocked = true,
+        }
+    }
+
+    if blocked {
+        DependencyState::Blocked
+    } else {
+        DependencyState::Ready
+    }
+}
+
+/// Returns true if adding `task` would create a dependency cycle.
+pub fn has_cycle(task: &Task, tasks: &HashMap<TaskId, Task>) -> bool {
+    let mut visited = HashSet::new();
+    let mut stack: Vec<TaskId> = task.depends_on.clone();
+
+    while let Some(id) = stack.pop() {
+        if id == task.id {
+            return true;
+        }
+        if !visited.insert(id) {
+            continue;
+        }
+        if let Some(parent) = tasks.get(&id) {
+            stack.extend(parent.depends_on.iter().copied());
+        }
+    }
+
+    false
+}
+
+/// Returns the IDs of tasks that directly depend on `id`.
+pub fn dependents_of(id: TaskId, tasks: &HashMap<TaskId, Task>) -> Vec<TaskId> {
+    tasks
+        .values()
+        .filter(|t| t.depends_on.contains(&id))
+        .map(|t| t.id)
+        .collect()
+}
+
+/// Replaces parent output references in `task`'s payload with the actual results.
+///
+/// Must only be called once `dependency_state` returned `Ready`.
+pub fn resolve_payload(task: &mut Task, tasks: &HashMap<TaskId, Task>) -> anyhow::Result<()> {
+    let outputs: HashMap<TaskId, &Value> = task
+        .depends_on
+        .iter()
+        .filter_map(|id| match tasks.get(id).map(|t| &t.status) {
+            Some(TaskStatus::Completed(output)) => Some((*id, output)),
+            _ => None,
+        })
+        .collect();
+
+    if let Some(payload) = task.payload.as_mut() {
+        payload.data = resolve_references(&payload.data, &outputs)?;
+        for value in payload.parameters.values_mut() {
+            *value = resolve_references(value, &outputs)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Recursively substitutes reference objects in `value` with parent outputs.
+pub fn resolve_references(
+    value: &Value,
+    outputs: &HashMap<TaskId, &Value>,
+) -> anyhow::Result<Value> {
+    match value {
+        Value::Object(map) => {
+            if let Some(reference) = map.get(FROM_TASK_KEY) {
+                let parent_id = reference
+                    .as_str()
+                    .and_then(|s| s.parse::<TaskId>().ok())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid {} reference", FROM_TASK_KEY))?;
+                let output = outputs.get(&parent_id).ok_or_else(|| {
+                    anyhow::anyhow!("Task {} is not a completed dependency", parent_id)
+                })?;
+
+                return match map.get(POINTER_KEY).and_then(|p| p.as_str()) {
+                    Some(pointer) => output.pointer(pointer).cloned().ok_or_else(|| {
+                        anyhow::anyhow!("Output of task {} has no value at {}", parent_id, pointer)
+                    }),
+                    None => Ok((*output).clone()),
+                };
+            }
+
+            let mut resolved = serde_json::Map::with_capacity(map.len());
+            for (key, v) in map {
+                resolved.insert(key.clone(), resolve_references(v, outputs)?);
+            }
+            Ok(Value::Object(resolved))
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|v| resolve_references(v, outputs))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(Value::Array),
+        _ => Ok(value.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn completed(output: Value) -> Task {
+        let mut task = Task::new("parent");
+        task.status = TaskStatus::Completed(output);
+        task
+    }
+
+    #[test]
+    fn test_resolve_references_with_pointer() {
+        let parent_id = TaskId::new_v4();
+        let output = json!({"tokens": ["a", "b"], "count": 2});
+        let outputs = HashMap::from([(parent_id, &output)]);
+
+        let data = json!({
+            "operation": "embed",
+            "items": [{"$from_task": parent_id.to_string(), "$pointer": "/tokens/1"}],
+            "all": {"$from_task": parent_id.to_string()}
+        });
+        let resolved = resolve_references(&data, &outputs).unwrap();
+
+        assert_eq!(resolved["items"][0], json!("b"));
+        assert_eq!(resolved["all"]["count"], json!(2));
+        assert_eq!(resolved["operation"], json!("embed"));
+    }
+
+    #[test]
+    fn test_resolve_rejects_unknown_parent() {
+        let data = json!({"$from_task": TaskId::new_v4().to_string()});
+        assert!(resolve_references(&data, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_dependency_state() {
+        let done = completed(json!(1));
+        let running = {
+            let mut t = Task::new("running");
+            t.status = TaskStatus::Running;
+            t
+        };
+        let failed = {
+            let mut t = Task::new("failed");
+            t.status = TaskStatus::Failed("boom".to_string());
+            t
+        };
+        let tasks: HashMap<TaskId, Task> = [done.clone(), running.clone(), failed.clone()]
+            .into_iter()
+            .map(|t| (t.id, t))
+            .collect();
+
+        let mut child = Task::new("child");
+        child.depends_on = vec![done.id];
+        assert_eq!(dependency_state(&child, &tasks), DependencyState::Ready);
+
+        child.depends_on = vec![done.id, running.id];
+        assert_eq!(dependency_state(&child, &tasks), DependencyState::Blocked);
+
+        child.depends_on = vec![running.id, failed.id];
+        assert!(matches!(
+            dependency_state(&child, &tasks),
+            DependencyState::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn test_timed_out_parent_blocks_only_while_retryable() {
+        let mut parent = Task::new("parent");
+        parent.status = TaskStatus::Timeout;
+        let mut child = Task::new("child");
+        child.depends_on = vec![parent.id];
+        let state = |parent: &Task| {
+            let tasks = HashMap::from([(parent.id, parent.clone())]);
+            dependency_state(&child, &tasks)
+        };
+
+        // A local deadline is never retried
+        assert!(matches!(state(&parent), DependencyState::Failed(_)));
+
+        parent.assigned_to = Some("peer".to_string());
+        assert_eq!(state(&parent), DependencyState::Blocked);
+
+        parent.retry_count = parent.max_retries;
+        assert!(matches!(state(&parent), DependencyState::Failed(_)));
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        let mut a = Task::new("a");
+        let mut b = Task::new("b");
+        b.depends_on = vec![a.id];
+        let tasks = HashMap::from([(b.id, b.clone())]);
+
+        a.depends_on = vec![b.id];
+        assert!(has_cycle(&a, &tasks));
+
+        a.depends_on = vec![];
+        assert!(!has_cycle(&a, &tasks));
+    }
+}
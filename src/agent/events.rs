@@ -0,0 +1,245 @@
+//! Task lifecycle events.
+//!
+//! The `TaskManager` publishes these on its [`EventBus`] whenever a task changes
+//! state, and the agent publishes [`TaskDispatched`] once a task was sent to
+//! peers, so applications can subscribe with an [`EventHandler`] instead of
+//! polling `task_status`.
+//!
+//! These carry typed payloads and are distinct from the string-based
+//! `core::events::TaskCompleted` and `core::events::TaskFailed`.
+//!
+//! [`EventHandler`]: crate::core::events::EventHandler
+
+use crate::agent::task::{ProgressUpdate, Task, TaskId, TaskPriority, TaskStatus, TaskType};
+use crate::core::events::{Event, EventBus, EventId};
+use crate::define_event;
+use serde::{Deserialize, Serialize};
+
+/// Payload of [`TaskSubmitted`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskSubmission {
+    /// ID of the submitted task.
+    pub task_id: TaskId,
+    /// Type of the task, if it has a payload.
+    pub task_type: Option<TaskType>,
+    /// Priority of the task.
+    pub priority: TaskPriority,
+}
+
+/// Payload of [`TaskDispatched`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskDispatch {
+    /// ID of the dispatched task.
+    pub task_id: TaskId,
+    /// Peers the task was sent to.
+    pub peers: Vec<String>,
+}
+
+/// Payload of [`TaskProgress`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskProgressReport {
+    /// ID of the task.
+    pub task_id: TaskId,
+    /// The reported progress.
+    pub update: ProgressUpdate,
+}
+
+/// Payload of [`TaskCompleted`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskOutput {
+    /// ID of the completed task.
+    pub task_id: TaskId,
+    /// Result of the task.
+    pub result: serde_json::Value,
+}
+
+/// Payload of [`TaskFailed`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskFailure {
+    /// ID of the failed task.
+    pub task_id: TaskId,
+    /// Reason the task failed.
+    pub error: String,
+}
+
+define_event!(TaskSubmitted, TaskSubmission);
+define_event!(TaskDispatched, TaskDispatch);
+define_event!(TaskStarted, TaskId);
+define_event!(TaskProgress, TaskProgressReport);
+define_event!(TaskCompleted, TaskOutput);
+define_event!(TaskFailed, TaskFailure);
+define_event!(TaskCancelled, TaskId);
+define_event!(TaskTimedOut, TaskId);
+
+/// Source set on events published by the `TaskManager`.
+pub const TASK_MANAGER_SOURCE: &str = "task_manager";
+
+/// Publishes the event for a task that was just added.
+pub(crate) async fn publish_submitted(bus: &EventBus, task: &Task) {
+    let submission = TaskSubmission {
+        task_id: task.id,
+        task_type: task.payload.as_ref().map(|p| p.task_type.clone()),
+        priority: task.priority.clone(),
+    };
+    let _ = bus.publish(TaskSubmitted::new(submission, source())).await;
+}
+
+/// Publishes the event matching a task's new status.
+///
+/// `Queued` has no event of its own; a requeued task publishes nothing.
+pub(crate) async fn publish_status(bus: &EventBus, task_id: TaskId, status: &TaskStatus) {
+    let _ = match status {
+        TaskStatus::Queued => Ok(()),
+        TaskStatus::Running => bus.publish(TaskStarted::new(task_id, source())).await,
+        TaskStatus::Completed(result) => {
+            let output = TaskOutput {
+                task_id,
+                result: result.clone(),
+            };
+            bus.publish(TaskCompleted::new(output, source())).await
+        }
+        TaskStatus::Failed(error) => {
+            let failure = TaskFailure {
+                task_id,
+                error: error.clone(),
+            };
+            bus.publish(TaskFailed::new(failure, source())).await
+        }
+        TaskStatus::Cancelled => bus.publish(TaskCancelled::new(task_id, source())).await,
+        TaskStatus::Timeout => bus.publish(TaskTimedOut::new(task_id, source())).await,
+    };
+}
+
+/// Publishes a progress report of a task.
+pub(crate) async fn publish_progress(bus: &EventBus, task_id: TaskId, update: ProgressUpdate) {
+    let report = TaskProgressReport { task_id, update };
+    let _ = bus.publish(TaskProgress::new(report, source())).await;
+}
+
+fn source() -> Option<String> {
+    Some(TASK_MANAGER_SOURCE.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::task::TaskManager;
+    use crate::core::events::{EventHandler, EventResult};
+    use crate::storage::local::LocalStorage;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+
+    /// Records every received event as "<event type> <task id>".
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    macro_rules! record {
+        ($($event:ty => $id:expr),* $(,)?) => {
+            $(
+                #[async_trait::async_trait]
+                impl EventHandler<$event> for Recorder {
+                    async fn handle(&self, event: &$event) -> EventResult {
+                        let id: fn(&$event) -> TaskId = $id;
+                        self.0
+                            .lock()
+                            .await
+                            .push(format!("{} {}", event.event_type(), id(event)));
+                        EventResult::Success
+                    }
+
+                    fn name(&self) -> &'static str {
+                        "Recorder"
+                    }
+                }
+            )*
+        };
+    }
+
+    record!(
+        TaskSubmitted => |e| e.payload.task_id,
+        TaskStarted => |e| e.payload,
+        TaskProgress => |e| e.payload.task_id,
+        TaskCompleted => |e| e.payload.task_id,
+        TaskFailed => |e| e.payload.task_id,
+        TaskCancelled => |e| e.payload,
+    );
+
+    async fn subscribed_manager(dir: &std::path::Path) -> (TaskManager, Recorder) {
+        let bus = EventBus::new();
+        let recorder = Recorder::default();
+        bus.subscribe::<TaskSubmitted, _>(recorder.clone())
+            .await
+            .unwrap();
+        bus.subscribe::<TaskStarted, _>(recorder.clone())
+            .await
+            .unwrap();
+        bus.subscribe::<TaskProgress, _>(recorder.clone())
+            .await
+            .unwrap();
+        bus.subscribe::<TaskCompleted, _>(recorder.clone())
+            .await
+            .unwrap();
+        bus.subscribe::<TaskFailed, _>(recorder.clone())
+            .await
+            .unwrap();
+        bus.subscribe::<TaskCancelled, _>(recorder.clone())
+            .await
+            .unwrap();
+
+        let storage = Arc::new(LocalStorage::new(dir).unwrap());
+        (TaskManager::new(storage).with_event_bus(bus), recorder)
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_events_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let (manager, recorder) = subscribed_manager(dir.path()).await;
+
+        let id = manager.add_task(Task::new("tracked")).await;
+        manager
+            .update_status(id, TaskStatus::Running)
+            .await
+            .unwrap();
+        manager
+            .report_progress(id, ProgressUpdate::percent(50))
+            .await
+            .unwrap();
+        // Requeueing has no event
+        manager.update_status(id, TaskStatus::Queued).await.unwrap();
+        manager
+            .update_status(id, TaskStatus::Completed(serde_json::json!({"ok": true})))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *recorder.0.lock().await,
+            vec![
+                format!("TaskSubmitted {}", id),
+                format!("TaskStarted {}", id),
+                format!("TaskProgress {}", id),
+                format!("TaskCompleted {}", id),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cascaded_dependents_publish_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let (manager, recorder) = subscribed_manager(dir.path()).await;
+
+        let root = manager.add_task(Task::new("root")).await;
+        let mut child = Task::new("child");
+        child.depends_on = vec![root];
+        let child = manager.add_task(child).await;
+        recorder.0.lock().await.clear();
+
+        manager.cancel_task(root).await.unwrap();
+        assert_eq!(
+            *recorder.0.lock().await,
+            vec![
+                format!("TaskCancelled {}", root),
+                format!("TaskCancelled {}", child),
+            ]
+        );
+    }
+}
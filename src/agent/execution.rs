@@ -0,0 +1,213 @@
+//! Execution context handed to `TaskExecutor`s.
+//!
+//! An [`ExecutionContext`] carries a [`CancellationToken`], the task's
+//! `ProgressReporter`, an optional deadline and the task's checkpoints (see
+//! [`checkpoint`](crate::agent::checkpoint)). Cancelling a task cancels its
+//! token first, so executors checking it at safe points (see
+//! [`ExecutionContext::check`]) can stop and clean up, e.g. remove a partial
+//! model download. Executors that ignore the token are aborted once
+//! `CANCELLATION_GRACE_PERIOD` has passed.
+
+use crate::agent::checkpoint::{Checkpoint, CheckpointWriter};
+use crate::agent::task::ProgressReporter;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::Notify;
+
+/// Time a cancelled task gets to stop on its own before it is aborted.
+pub const CANCELLATION_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Why an executor stopped before finishing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum Interrupted {
+    /// The task was cancelled.
+    #[error("Task cancelled")]
+    Cancelled,
+    /// The task ran past its deadline.
+    #[error("Task deadline exceeded")]
+    DeadlineExceeded,
+}
+
+/// Shared flag signalling that a task was cancelled.
+///
+/// Clones observe the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<TokenState>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token, waking everyone waiting in [`Self::cancelled`].
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Returns true once the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Waits until the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            tokio::pin!(notified);
+            // Register before checking, so a concurrent cancel is not missed
+            notified.as_mut().enable();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Everything an executor gets besides the payload.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionContext {
+    /// Cancelled when the task is cancelled.
+    pub cancellation: CancellationToken,
+    /// Reporter for progress updates.
+    pub progress: ProgressReporter,
+    /// Time by which the task should be finished, if any.
+    pub deadline: Option<Instant>,
+    /// Writer for checkpoints of the task's state.
+    pub checkpoints: CheckpointWriter,
+    /// Checkpoint saved by an earlier run of the task, to resume from.
+    pub resume_from: Option<Checkpoint>,
+}
+
+impl ExecutionContext {
+    /// Creates a context.
+    pub fn new(
+        cancellation: CancellationToken,
+        progress: ProgressReporter,
+        deadline: Option<Instant>,
+    ) -> Self {
+        Self {
+            cancellation,
+            progress,
+            deadline,
+            checkpoints: CheckpointWriter::disabled(),
+            resume_from: None,
+        }
+    }
+
+    /// Saves checkpoints with `checkpoints`, resuming from `resume_from`.
+    pub fn with_checkpoints(
+        mut self,
+        checkpoints: CheckpointWriter,
+        resume_from: Option<Checkpoint>,
+    ) -> Self {
+        self.checkpoints = checkpoints;
+        self.resume_from = resume_from;
+        self
+    }
+
+    /// Creates a context that is never cancelled, has no deadline and
+    /// discards progress.
+    pub fn detached() -> Self {
+        Self::default()
+    }
+
+    /// Saves a checkpoint of the task's state and returns its sequence number.
+    pub fn checkpoint(&self, state: serde_json::Value) -> u64 {
+        self.checkpoints.save(state)
+    }
+
+    /// Returns the state of the checkpoint to resume from, if any.
+    pub fn resume_state(&self) -> Option<&serde_json::Value> {
+        self.resume_from
+            .as_ref()
+            .map(|checkpoint| &checkpoint.state)
+    }
+
+    /// Returns true once the task was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Returns the time left until the deadline, if there is one.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Safe point: fails if the task was cancelled or is past its deadline.
+    pub fn check(&self) -> Result<(), Interrupted> {
+        if self.is_cancelled() {
+            return Err(Interrupted::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(Interrupted::DeadlineExceeded);
+        }
+        Ok(())
+    }
+
+    /// Sleeps for `duration`, waking early with an error on cancellation or
+    /// once the deadline passes.
+    pub async fn sleep(&self, duration: Duration) -> Result<(), Interrupted> {
+        let until_deadline = self.remaining().unwrap_or(Duration::MAX);
+        tokio::select! {
+            _ = self.cancellation.cancelled() => Err(Interrupted::Cancelled),
+            _ = tokio::time::sleep(duration.min(until_deadline)) => self.check(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancel_wakes_sleeping_executor() {
+        let ctx = ExecutionContext::detached();
+        let sleeper = {
+            let ctx = ctx.clone();
+            tokio::spawn(async move { ctx.sleep(Duration::from_secs(60)).await })
+        };
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        ctx.cancellation.cancel();
+        let result = tokio::time::timeout(Duration::from_secs(1), sleeper)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result, Err(Interrupted::Cancelled));
+        assert_eq!(ctx.check(), Err(Interrupted::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_cuts_sleep_short() {
+        let ctx = ExecutionContext::new(
+            CancellationToken::new(),
+            ProgressReporter::disabled(),
+            Some(Instant::now() + Duration::from_millis(20)),
+        );
+        assert!(ctx.check().is_ok());
+
+        let started = Instant::now();
+        assert_eq!(
+            ctx.sleep(Duration::from_secs(60)).await,
+            Err(Interrupted::DeadlineExceeded)
+        );
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -1,10 +1,19 @@
 //! Agent task executors.
 
+pub mod model_download;
 /// Executor registry module.
 pub mod registry;
+pub mod schema;
+pub mod simulated;
+pub mod subprocess;
 pub mod text_processing;
 pub mod vector_computation;
+pub mod wasm;
 
+pub use model_download::ModelDownloadExecutor;
 pub use registry::ExecutorRegistry;
+pub use simulated::SimulatedExecutor;
+pub use subprocess::SubprocessExecutor;
 pub use text_processing::TextProcessingExecutor;
 pub use vector_computation::VectorComputationExecutor;
+pub use wasm::WasmExecutor;
@@ -0,0 +1,66 @@
+//! Model download executor.
+
+use crate::agent::ai::ModelManager;
+use crate::agent::execution::ExecutionContext;
+use crate::agent::task::{TaskExecutor, TaskPayload};
+use anyhow::Result;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Model downloaded when a payload does not name one.
+const DEFAULT_MODEL: &str = "prajjwal1/bert-tiny";
+
+/// Executor for `AiModelDownload` tasks.
+///
+/// Downloads the model named by the payload's `model` field into the model
+/// cache. Cancelling the task discards the partial download.
+pub struct ModelDownloadExecutor {
+    /// The manager responsible for downloading and caching AI models.
+    pub model_manager: Arc<ModelManager>,
+}
+
+impl ModelDownloadExecutor {
+    /// Creates a new `ModelDownloadExecutor`.
+    pub fn new(model_manager: Arc<ModelManager>) -> Self {
+        Self { model_manager }
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskExecutor for ModelDownloadExecutor {
+    async fn execute(&self, payload: &TaskPayload) -> Result<serde_json::Value> {
+        self.execute_with_context(payload, &ExecutionContext::detached())
+            .await
+    }
+
+    async fn execute_with_context(
+        &self,
+        payload: &TaskPayload,
+        ctx: &ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        let model_name = payload
+            .data
+            .get("model")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_MODEL);
+
+        let path = self
+            .model_manager
+            .ensure_model_cancellable(model_name, &ctx.cancellation)
+            .await?;
+        Ok(json!({
+            "status": "downloaded",
+            "model": model_name,
+            "path": path.to_string_lossy()
+        }))
+    }
+
+    fn input_schema(&self) -> Option<serde_json::Value> {
+        Some(json!({
+            "type": "object",
+            "properties": {
+                "model": { "type": "string", "minLength": 1 },
+            },
+        }))
+    }
+}
@@ -1,11 +1,40 @@
-use crate::agent::task::{TaskExecutor, TaskType};
+use crate::agent::executors::schema::{InvalidPayload, PayloadSchema, TaskSchema};
+use crate::agent::task::{TaskExecutor, TaskPayload, TaskType};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 /// Registry for task executors.
+///
+/// The agent runs every task through the executor registered for its type, and
+/// advertises the types registered with [`register`](Self::register) or passed
+/// to [`advertise`](Self::advertise) as its capabilities. Clones share the same
+/// executors, so executors registered at runtime are picked up by the agent.
 #[derive(Default, Clone)]
 pub struct ExecutorRegistry {
-    executors: Arc<RwLock<HashMap<TaskType, Arc<dyn TaskExecutor>>>>,
+    executors: Arc<RwLock<HashMap<TaskType, Registered>>>,
+    fallback: Arc<RwLock<Option<Registered>>>,
+    advertised: Arc<RwLock<Vec<TaskType>>>,
+}
+
+/// An executor with its compiled input schema.
+#[derive(Clone)]
+struct Registered {
+    executor: Arc<dyn TaskExecutor>,
+    schema: Option<Arc<PayloadSchema>>,
+}
+
+impl Registered {
+    fn new<E>(executor: E) -> Self
+    where
+        E: TaskExecutor + 'static,
+    {
+        Self {
+            schema: executor
+                .input_schema()
+                .map(|schema| Arc::new(PayloadSchema::compile(schema))),
+            executor: Arc::new(executor),
+        }
+    }
 }
 
 impl ExecutorRegistry {
@@ -14,18 +43,262 @@ impl ExecutorRegistry {
         Self::default()
     }
 
-    /// Registers an executor for a specific task type.
+    /// Registers an executor for a specific task type, replacing any executor
+    /// registered for it before.
     pub fn register<E>(&self, task_type: TaskType, executor: E)
     where
         E: TaskExecutor + 'static,
     {
+        self.advertise(task_type.clone());
+        self.register_local(task_type, executor);
+    }
+
+    /// Registers an executor like [`register`](Self::register), without
+    /// advertising its task type to peers.
+    pub fn register_local<E>(&self, task_type: TaskType, executor: E)
+    where
+        E: TaskExecutor + 'static,
+    {
+        let mut executors = self.executors.write().unwrap();
+        executors.insert(task_type, Registered::new(executor));
+    }
+
+    /// Advertises a task type to peers, whether or not an executor is
+    /// registered for it yet.
+    pub fn advertise(&self, task_type: TaskType) {
+        let mut advertised = self.advertised.write().unwrap();
+        if !advertised.contains(&task_type) {
+            advertised.push(task_type);
+        }
+    }
+
+    /// Removes the executor registered for a task type, returning it. The type
+    /// is no longer advertised.
+    pub fn unregister(&self, task_type: &TaskType) -> Option<Arc<dyn TaskExecutor>> {
+        self.advertised
+            .write()
+            .unwrap()
+            .retain(|advertised| advertised != task_type);
         let mut executors = self.executors.write().unwrap();
-        executors.insert(task_type, Arc::new(executor));
+        executors
+            .remove(task_type)
+            .map(|registered| registered.executor)
+    }
+
+    /// Sets the executor for `Custom` task types without an executor of their
+    /// own. Fallback types are not advertised.
+    pub fn register_fallback<E>(&self, executor: E)
+    where
+        E: TaskExecutor + 'static,
+    {
+        *self.fallback.write().unwrap() = Some(Registered::new(executor));
     }
 
     /// Retrieves an executor for a specific task type.
     pub fn get(&self, task_type: &TaskType) -> Option<Arc<dyn TaskExecutor>> {
         let executors = self.executors.read().unwrap();
-        executors.get(task_type).cloned()
+        executors
+            .get(task_type)
+            .map(|registered| registered.executor.clone())
+    }
+
+    fn registered(&self, task_type: &TaskType) -> Option<Registered> {
+        let registered = self.executors.read().unwrap().get(task_type).cloned();
+        registered.or_else(|| match task_type {
+            TaskType::Custom(_) => self.fallback.read().unwrap().clone(),
+            _ => None,
+        })
+    }
+
+    /// Retrieves the executor that runs tasks of a type: the registered one, or
+    /// the fallback for `Custom` types.
+    pub fn resolve(&self, task_type: &TaskType) -> Option<Arc<dyn TaskExecutor>> {
+        self.registered(task_type)
+            .map(|registered| registered.executor)
+    }
+
+    /// Checks a payload against the input schema of the executor that would run
+    /// it. Payloads without such an executor, or whose executor has no schema,
+    /// are accepted.
+    pub fn validate(&self, payload: &TaskPayload) -> Result<(), InvalidPayload> {
+        match self
+            .registered(&payload.task_type)
+            .and_then(|registered| registered.schema)
+        {
+            Some(schema) => schema.validate(&payload.task_type, &payload.data),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns true if an executor is registered for the task type.
+    pub fn supports(&self, task_type: &TaskType) -> bool {
+        self.executors.read().unwrap().contains_key(task_type)
+    }
+
+    /// Returns the registered task types, sorted by name.
+    pub fn task_types(&self) -> Vec<TaskType> {
+        let mut task_types: Vec<TaskType> =
+            self.executors.read().unwrap().keys().cloned().collect();
+        task_types.sort_by_key(|task_type| task_type.to_string());
+        task_types
+    }
+
+    /// Returns the advertised task types, sorted by name.
+    pub fn advertised(&self) -> Vec<TaskType> {
+        let mut task_types = self.advertised.read().unwrap().clone();
+        task_types.sort_by_key(|task_type| task_type.to_string());
+        task_types
+    }
+
+    /// Returns the input schemas of the advertised task types that have one,
+    /// sorted by task type name.
+    pub fn schemas(&self) -> Vec<TaskSchema> {
+        let advertised = self.advertised.read().unwrap();
+        let executors = self.executors.read().unwrap();
+        let mut schemas: Vec<TaskSchema> = executors
+            .iter()
+            .filter(|(task_type, _)| advertised.contains(task_type))
+            .filter_map(|(task_type, registered)| {
+                registered.schema.as_ref().map(|schema| TaskSchema {
+                    task_type: task_type.clone(),
+                    schema: schema.schema().clone(),
+                })
+            })
+            .collect();
+        schemas.sort_by_key(|schema| schema.task_type.to_string());
+        schemas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::task::TaskPayload;
+
+    struct Echo(&'static str);
+
+    #[async_trait::async_trait]
+    impl TaskExecutor for Echo {
+        async fn execute(&self, _payload: &TaskPayload) -> anyhow::Result<serde_json::Value> {
+            Ok(serde_json::json!(self.0))
+        }
+    }
+
+    async fn run(registry: &ExecutorRegistry, task_type: TaskType) -> Option<serde_json::Value> {
+        let executor = registry.resolve(&task_type)?;
+        let payload = TaskPayload {
+            task_type,
+            data: serde_json::Value::Null,
+            parameters: Default::default(),
+        };
+        Some(executor.execute(&payload).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_registered_executor_wins_over_fallback() {
+        let registry = ExecutorRegistry::new();
+        let custom = TaskType::Custom("translate".to_string());
+        registry.register_fallback(Echo("fallback"));
+        registry.register(custom.clone(), Echo("translate"));
+        registry.register(TaskType::VectorComputation, Echo("vector"));
+
+        assert_eq!(
+            run(&registry, custom.clone()).await,
+            Some("translate".into())
+        );
+        let other = TaskType::Custom("other".to_string());
+        assert_eq!(run(&registry, other.clone()).await, Some("fallback".into()));
+        // The fallback only covers custom types
+        assert_eq!(run(&registry, TaskType::TextProcessing).await, None);
+
+        // Only registered types are capabilities
+        assert_eq!(
+            registry.task_types(),
+            vec![custom.clone(), TaskType::VectorComputation]
+        );
+        assert!(!registry.supports(&other));
+
+        assert!(registry.unregister(&custom).is_some());
+        assert_eq!(run(&registry, custom).await, Some("fallback".into()));
+    }
+
+    #[test]
+    fn test_only_opted_in_types_are_advertised() {
+        let registry = ExecutorRegistry::new();
+        let custom = TaskType::Custom("translate".to_string());
+        registry.register_local(TaskType::TextProcessing, Strict);
+        registry.register(custom.clone(), Strict);
+        // Types can be advertised before their executor is registered
+        registry.advertise(TaskType::AiInference);
+
+        assert!(registry.supports(&TaskType::TextProcessing));
+        assert_eq!(
+            registry.advertised(),
+            vec![TaskType::AiInference, custom.clone()]
+        );
+        let schemas: Vec<TaskType> = registry
+            .schemas()
+            .into_iter()
+            .map(|schema| schema.task_type)
+            .collect();
+        assert_eq!(schemas, vec![custom.clone()]);
+
+        registry.register_local(TaskType::AiInference, Strict);
+        assert!(registry.unregister(&custom).is_some());
+        assert_eq!(registry.advertised(), vec![TaskType::AiInference]);
+    }
+
+    struct Strict;
+
+    #[async_trait::async_trait]
+    impl TaskExecutor for Strict {
+        async fn execute(&self, _payload: &TaskPayload) -> anyhow::Result<serde_json::Value> {
+            Ok(serde_json::Value::Null)
+        }
+
+        fn input_schema(&self) -> Option<serde_json::Value> {
+            Some(serde_json::json!({ "type": "object", "required": ["text"] }))
+        }
+    }
+
+    #[test]
+    fn test_validates_against_resolved_executor_schema() {
+        let registry = ExecutorRegistry::new();
+        let strict = TaskType::Custom("strict".to_string());
+        registry.register(strict.clone(), Strict);
+        registry.register(TaskType::TextProcessing, Echo("text"));
+        let payload = |task_type: TaskType, data| TaskPayload {
+            task_type,
+            data,
+            parameters: Default::default(),
+        };
+
+        assert!(registry
+            .validate(&payload(
+                strict.clone(),
+                serde_json::json!({ "text": "hi" })
+            ))
+            .is_ok());
+        assert!(registry
+            .validate(&payload(strict.clone(), serde_json::json!({})))
+            .is_err());
+        // Executors without a schema accept anything
+        assert!(registry
+            .validate(&payload(TaskType::TextProcessing, serde_json::json!(1)))
+            .is_ok());
+
+        // Custom types without an executor use the fallback's schema
+        let other = TaskType::Custom("other".to_string());
+        assert!(registry
+            .validate(&payload(other.clone(), serde_json::json!({})))
+            .is_ok());
+        registry.register_fallback(Strict);
+        assert!(registry
+            .validate(&payload(other, serde_json::json!({})))
+            .is_err());
+
+        let schemas = registry.schemas();
+        assert_eq!(schemas.len(), 1);
+        assert_eq!(schemas[0].task_type, strict);
     }
 }
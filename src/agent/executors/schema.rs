@@ -0,0 +1,130 @@
+//! JSON Schemas of task payloads.
+//!
+//! Executors describe the `data` of the payloads they accept with a JSON Schema
+//! (see `TaskExecutor::input_schema`). The [`ExecutorRegistry`] compiles them
+//! when executors are registered, and the task manager checks every submitted
+//! task against the schema of its type, so malformed tasks fail on submission
+//! instead of on the peer they were dispatched to. The schemas are published
+//! with the agent's capabilities as [`TaskSchema`]s.
+//!
+//! [`ExecutorRegistry`]: super::ExecutorRegistry
+
+use crate::agent::task::TaskType;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Most validation errors reported for a single payload.
+const MAX_ERRORS: usize = 5;
+
+/// Schema of the payloads of a task type, as published to peers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskSchema {
+    /// Task type the schema applies to.
+    pub task_type: TaskType,
+    /// JSON Schema of the payload `data`.
+    pub schema: serde_json::Value,
+}
+
+/// A payload that does not match the schema of its task type.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid {task_type} payload: {}", .errors.join("; "))]
+pub struct InvalidPayload {
+    /// Task type of the payload.
+    pub task_type: TaskType,
+    /// What is wrong with it, by location in the payload.
+    pub errors: Vec<String>,
+}
+
+/// A compiled payload schema.
+#[derive(Debug)]
+pub(crate) struct PayloadSchema {
+    schema: serde_json::Value,
+    /// Compile error of schemas that are not valid JSON Schemas themselves.
+    validator: Result<jsonschema::Validator, String>,
+}
+
+impl PayloadSchema {
+    pub(crate) fn compile(schema: serde_json::Value) -> Self {
+        let validator = jsonschema::validator_for(&schema).map_err(|e| e.to_string());
+        if let Err(e) = &validator {
+            tracing::error!("Invalid payload schema {}: {}", schema, e);
+        }
+        Self { schema, validator }
+    }
+
+    pub(crate) fn schema(&self) -> &serde_json::Value {
+        &self.schema
+    }
+
+    /// Checks `data` against the schema. Payloads of types with an invalid
+    /// schema are always rejected.
+    pub(crate) fn validate(
+        &self,
+        task_type: &TaskType,
+        data: &serde_json::Value,
+    ) -> Result<(), InvalidPayload> {
+        let errors: Vec<String> = match &self.validator {
+            Ok(validator) => validator
+                .iter_errors(data)
+                .take(MAX_ERRORS)
+                .map(|e| match e.instance_path.as_str() {
+                    "" => e.to_string(),
+                    path => format!("{}: {}", path, e),
+                })
+                .collect(),
+            Err(e) => vec![format!("schema is invalid: {}", e)],
+        };
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidPayload {
+                task_type: task_type.clone(),
+                errors,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_reports_errors_by_location() {
+        let schema = PayloadSchema::compile(json!({
+            "type": "object",
+            "required": ["operation"],
+            "properties": {
+                "operation": { "type": "string" },
+                "values": { "type": "array", "items": { "type": "number" } },
+            },
+        }));
+        let task_type = TaskType::VectorComputation;
+
+        assert!(schema
+            .validate(
+                &task_type,
+                &json!({ "operation": "sum", "values": [1, 2.5] })
+            )
+            .is_ok());
+        let error = schema
+            .validate(&task_type, &json!({ "values": [1, "two"] }))
+            .unwrap_err();
+        assert_eq!(error.errors.len(), 2);
+        assert_eq!(
+            error.to_string(),
+            "Invalid VectorComputation payload: \"operation\" is a required property; \
+             /values/1: \"two\" is not of type \"number\""
+        );
+    }
+
+    #[test]
+    fn test_invalid_schema_rejects_everything() {
+        let schema = PayloadSchema::compile(json!({ "type": "no-such-type" }));
+        let error = schema
+            .validate(&TaskType::TextProcessing, &json!({}))
+            .unwrap_err();
+        assert!(error.errors[0].starts_with("schema is invalid"));
+    }
+}
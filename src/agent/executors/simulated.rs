@@ -0,0 +1,61 @@
+//! Simulated executor for `Custom` task types.
+
+use crate::agent::execution::ExecutionContext;
+use crate::agent::task::{ProgressUpdate, TaskExecutor, TaskPayload};
+use anyhow::Result;
+use serde_json::json;
+use std::time::Duration;
+
+/// Number of progress steps reported by a simulated task.
+const SIMULATED_PROGRESS_STEPS: u64 = 4;
+
+/// Duration of a simulated task whose payload does not set `duration_ms`.
+const DEFAULT_DURATION_MS: u64 = 100;
+
+/// Executor that simulates work without computing anything.
+///
+/// It sleeps for the payload's `duration_ms` in a few steps, reporting progress
+/// and saving a checkpoint (`{"step": n}`) after each one, and stops early on
+/// cancellation or at the deadline. Resumed from a checkpoint, it skips the
+/// steps already done. The agent runs `Custom` tasks without an executor of their own with it.
+pub struct SimulatedExecutor;
+
+#[async_trait::async_trait]
+impl TaskExecutor for SimulatedExecutor {
+    async fn execute(&self, payload: &TaskPayload) -> Result<serde_json::Value> {
+        self.execute_with_context(payload, &ExecutionContext::detached())
+            .await
+    }
+
+    async fn execute_with_context(
+        &self,
+        payload: &TaskPayload,
+        ctx: &ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        let duration = payload
+            .data
+            .get("duration_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_DURATION_MS);
+
+        let steps = SIMULATED_PROGRESS_STEPS;
+        let step = Duration::from_millis(duration / steps);
+        let done = ctx
+            .resume_state()
+            .and_then(|state| state.get("step"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+            .min(steps - 1);
+        for i in done + 1..steps {
+            ctx.sleep(step).await?;
+            ctx.progress.report(ProgressUpdate {
+                percent: (i * 100 / steps) as u8,
+                partial_output: None,
+                eta_secs: Some((step * (steps - i) as u32).as_secs()),
+            });
+            ctx.checkpoint(json!({ "step": i }));
+        }
+        ctx.sleep(step).await?;
+        Ok(json!({"status": "simulated_custom_execution"}))
+    }
+}
@@ -0,0 +1,670 @@
+//! Executor running allow-listed local commands.
+//!
+//! Every entry of [`SubprocessConfig::commands`] is served as its own `Custom`
+//! task type, so only the programs and arguments configured there can be run.
+//! The payload's `input` is written to the command's stdin as JSON, and its
+//! stdout must be a single JSON value, which becomes the task result.
+//!
+//! Each run gets a fresh scratch directory as working directory, `HOME` and
+//! `TMPDIR`, and an environment holding only `PATH` and the configured
+//! variables. The command runs in its own process group under the
+//! [`SubprocessLimits`] of its entry: a wall-clock timeout, and address space
+//! and CPU time rlimits. When [`SubprocessConfig::cgroup_parent`] names a
+//! writable cgroup v2 directory, the run also gets a cgroup of its own with
+//! memory, CPU and process limits.
+//!
+//! Once the run is over, whether it finished, timed out or was cancelled, the
+//! whole process group is killed and the scratch directory and cgroup are
+//! removed.
+
+use crate::agent::execution::{ExecutionContext, Interrupted};
+use crate::agent::task::{TaskExecutor, TaskPayload, TaskType};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use uuid::Uuid;
+
+/// `PATH` of commands whose entry does not set one.
+pub const DEFAULT_PATH: &str = "/usr/local/bin:/usr/bin:/bin";
+
+/// Largest part of stderr kept for error messages.
+const STDERR_LIMIT: usize = 4096;
+
+/// Period `SubprocessLimits::cpu_cores` is applied over, in microseconds.
+const CPU_PERIOD_US: u64 = 100_000;
+
+/// Allow-listed commands and where they run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubprocessConfig {
+    /// Commands by the name of the `Custom` task type they serve.
+    #[serde(default)]
+    pub commands: BTreeMap<String, CommandSpec>,
+    /// Directory the scratch directories of runs are created in.
+    pub scratch_dir: PathBuf,
+    /// cgroup v2 directory the agent may create child cgroups in, e.g. one
+    /// delegated to it by systemd. Without one only rlimits apply.
+    #[serde(default)]
+    pub cgroup_parent: Option<PathBuf>,
+}
+
+impl Default for SubprocessConfig {
+    fn default() -> Self {
+        Self {
+            commands: BTreeMap::new(),
+            scratch_dir: std::env::temp_dir().join("p2p-ai-agents"),
+            cgroup_parent: None,
+        }
+    }
+}
+
+impl SubprocessConfig {
+    /// Returns an executor for every configured command, with its task type.
+    pub fn executors(&self) -> Vec<(TaskType, SubprocessExecutor)> {
+        self.commands
+            .iter()
+            .map(|(name, command)| {
+                (
+                    TaskType::Custom(name.clone()),
+                    SubprocessExecutor::new(command.clone(), self),
+                )
+            })
+            .collect()
+    }
+}
+
+/// An allow-listed command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandSpec {
+    /// Program to run: an absolute path, or a name looked up in `PATH`.
+    pub program: PathBuf,
+    /// Arguments passed to the program.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Environment variables set for the program.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Limits of a single run.
+    #[serde(default)]
+    pub limits: SubprocessLimits,
+    /// JSON Schema of the `input` the command accepts, if it is checked.
+    #[serde(default)]
+    pub input_schema: Option<serde_json::Value>,
+}
+
+impl CommandSpec {
+    /// Creates a spec running `program` with `args` under the default limits.
+    pub fn new<I, S>(program: impl Into<PathBuf>, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            program: program.into(),
+            args: args.into_iter().map(Into::into).collect(),
+            env: BTreeMap::new(),
+            limits: SubprocessLimits::default(),
+            input_schema: None,
+        }
+    }
+
+    /// Sets the JSON Schema of the `input` the command accepts.
+    pub fn with_input_schema(mut self, schema: serde_json::Value) -> Self {
+        self.input_schema = Some(schema);
+        self
+    }
+}
+
+/// Limits of a single run of a command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubprocessLimits {
+    /// Wall-clock time a run may take. The task's deadline applies too.
+    pub timeout: Duration,
+    /// Address space limit of each process (`RLIMIT_AS`), and memory limit of
+    /// the cgroup.
+    pub max_memory_bytes: Option<u64>,
+    /// CPU time limit of each process (`RLIMIT_CPU`).
+    pub max_cpu_time: Option<Duration>,
+    /// CPUs the run may use at once. cgroup only.
+    pub cpu_cores: Option<f64>,
+    /// Number of processes the run may have at once. cgroup only.
+    pub max_processes: Option<u64>,
+    /// Largest output accepted on stdout.
+    pub max_output_bytes: usize,
+}
+
+impl Default for SubprocessLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(300),
+            max_memory_bytes: Some(1024 * 1024 * 1024),
+            max_cpu_time: Some(Duration::from_secs(300)),
+            cpu_cores: None,
+            max_processes: Some(64),
+            max_output_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+/// Errors of running a command.
+#[derive(Debug, Error)]
+pub enum SubprocessError {
+    /// The command could not be started.
+    #[error("Failed to start {program}: {source}")]
+    Spawn {
+        /// Program of the command.
+        program: String,
+        /// Why it could not be started.
+        source: std::io::Error,
+    },
+    /// The command exited unsuccessfully or was killed by a signal.
+    #[error("Command failed ({status}): {stderr}")]
+    Failed {
+        /// Exit status of the command.
+        status: ExitStatus,
+        /// Start of its stderr.
+        stderr: String,
+    },
+    /// The command wrote more than `SubprocessLimits::max_output_bytes`.
+    #[error("Command output exceeds the limit of {limit} bytes")]
+    OutputTooLarge {
+        /// Largest output accepted.
+        limit: usize,
+    },
+    /// The command's stdout is not JSON.
+    #[error("Invalid command output: {0}")]
+    InvalidOutput(String),
+    /// Setting up or talking to the command failed.
+    #[error("Command I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Executor running an allow-listed command.
+pub struct SubprocessExecutor {
+    command: CommandSpec,
+    scratch_dir: PathBuf,
+    cgroup_parent: Option<PathBuf>,
+}
+
+impl SubprocessExecutor {
+    /// Creates an executor running `command` with the scratch and cgroup
+    /// directories of `config`.
+    pub fn new(command: CommandSpec, config: &SubprocessConfig) -> Self {
+        Self {
+            command,
+            scratch_dir: config.scratch_dir.clone(),
+            cgroup_parent: config.cgroup_parent.clone(),
+        }
+    }
+
+    /// Returns the command run by this executor.
+    pub fn command(&self) -> &CommandSpec {
+        &self.command
+    }
+
+    fn build(&self, scratch: &Path, cgroup: Option<&Cgroup>) -> Result<Command, SubprocessError> {
+        let mut command = Command::new(&self.command.program);
+        command
+            .args(&self.command.args)
+            .current_dir(scratch)
+            .env_clear()
+            .env("PATH", DEFAULT_PATH)
+            .env("HOME", scratch)
+            .env("TMPDIR", scratch)
+            .envs(&self.command.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .process_group(0);
+
+        let limits = self.command.limits.clone();
+        let procs = cgroup.map(Cgroup::procs_path).transpose()?;
+        // SAFETY: the closure runs between fork and exec, and only makes
+        // async-signal-safe system calls; the path was allocated before forking
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(procs) = &procs {
+                    join_cgroup(procs)?;
+                }
+                apply_rlimits(&limits)
+            });
+        }
+        Ok(command)
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskExecutor for SubprocessExecutor {
+    async fn execute(&self, payload: &TaskPayload) -> Result<serde_json::Value> {
+        self.execute_with_context(payload, &ExecutionContext::detached())
+            .await
+    }
+
+    async fn execute_with_context(
+        &self,
+        payload: &TaskPayload,
+        ctx: &ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        let input = serde_json::to_vec(
+            payload
+                .data
+                .get("input")
+                .unwrap_or(&serde_json::Value::Null),
+        )?;
+        ctx.check()?;
+
+        let limits = &self.command.limits;
+        let name = format!("task-{}", Uuid::new_v4());
+        let scratch = self.scratch_dir.join(&name);
+        tokio::fs::create_dir_all(&scratch).await?;
+        // Cleans up when dropped, including when this future is aborted
+        let mut guard = RunGuard {
+            pgid: None,
+            scratch,
+            cgroup: None,
+        };
+        if let Some(parent) = &self.cgroup_parent {
+            match Cgroup::create(parent, &name, limits) {
+                Ok(cgroup) => guard.cgroup = Some(cgroup),
+                Err(e) => tracing::warn!(
+                    "cgroup limits unavailable under {}: {}",
+                    parent.display(),
+                    e
+                ),
+            }
+        }
+
+        let mut child = self
+            .build(&guard.scratch, guard.cgroup.as_ref())?
+            .spawn()
+            .map_err(|source| SubprocessError::Spawn {
+                program: self.command.program.display().to_string(),
+                source,
+            })?;
+        guard.pgid = child.id().map(|pid| pid as i32);
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let run = async {
+            let write = async move {
+                // Commands may exit without reading their input
+                match stdin.write_all(&input).await {
+                    Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e.into()),
+                    _ => Ok(()),
+                }
+            };
+            let read_stdout = async {
+                let mut output = Vec::new();
+                // Read one byte past the limit to tell whether there is more
+                stdout
+                    .take(limits.max_output_bytes as u64 + 1)
+                    .read_to_end(&mut output)
+                    .await?;
+                if output.len() > limits.max_output_bytes {
+                    return Err(SubprocessError::OutputTooLarge {
+                        limit: limits.max_output_bytes,
+                    });
+                }
+                Ok(output)
+            };
+            let read_stderr = async { Ok(read_capped(stderr, STDERR_LIMIT).await?) };
+            let ((), stdout, stderr) = tokio::try_join!(write, read_stdout, read_stderr)?;
+            Ok::<_, SubprocessError>((child.wait().await?, stdout, stderr))
+        };
+
+        let timeout = limits.timeout.min(ctx.remaining().unwrap_or(Duration::MAX));
+        let (status, stdout, stderr) = tokio::select! {
+            output = run => output?,
+            _ = ctx.cancellation.cancelled() => return Err(Interrupted::Cancelled.into()),
+            _ = tokio::time::sleep(timeout) => return Err(Interrupted::DeadlineExceeded.into()),
+        };
+
+        if !status.success() {
+            return Err(SubprocessError::Failed {
+                status,
+                stderr: String::from_utf8_lossy(&stderr).trim().to_string(),
+            }
+            .into());
+        }
+        serde_json::from_slice(&stdout)
+            .map_err(|e| SubprocessError::InvalidOutput(e.to_string()).into())
+    }
+
+    fn input_schema(&self) -> Option<serde_json::Value> {
+        let input = self.command.input_schema.clone()?;
+        Some(serde_json::json!({
+            "type": "object",
+            "required": ["input"],
+            "properties": { "input": input },
+        }))
+    }
+}
+
+/// Reads `reader` to the end, keeping only the first `cap` bytes.
+async fn read_capped(reader: impl AsyncRead + Unpin, cap: usize) -> std::io::Result<Vec<u8>> {
+    let mut kept = Vec::new();
+    let mut reader = reader.take(cap as u64);
+    reader.read_to_end(&mut kept).await?;
+    // Drain the rest so the command does not block on a full pipe
+    tokio::io::copy(&mut reader.into_inner(), &mut tokio::io::sink()).await?;
+    Ok(kept)
+}
+
+fn apply_rlimits(limits: &SubprocessLimits) -> std::io::Result<()> {
+    use nix::sys::resource::{setrlimit, Resource};
+
+    setrlimit(Resource::RLIMIT_CORE, 0, 0)?;
+    if let Some(bytes) = limits.max_memory_bytes {
+        setrlimit(Resource::RLIMIT_AS, bytes, bytes)?;
+    }
+    if let Some(cpu_time) = limits.max_cpu_time {
+        // SIGXCPU at the soft limit, SIGKILL a second later
+        let secs = cpu_time.as_secs().max(1);
+        setrlimit(Resource::RLIMIT_CPU, secs, secs + 1)?;
+    }
+    Ok(())
+}
+
+/// Moves the calling process into the cgroup whose `cgroup.procs` is `procs`.
+fn join_cgroup(procs: &CString) -> std::io::Result<()> {
+    // SAFETY: open, write and close are async-signal-safe, and `procs` is a
+    // valid C string
+    unsafe {
+        let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        // Writing 0 moves the writer itself
+        let written = libc::write(fd, b"0".as_ptr().cast(), 1);
+        let error = std::io::Error::last_os_error();
+        libc::close(fd);
+        if written != 1 {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+/// A cgroup v2 created for a single run.
+#[derive(Debug)]
+struct Cgroup {
+    path: PathBuf,
+}
+
+impl Cgroup {
+    fn create(parent: &Path, name: &str, limits: &SubprocessLimits) -> std::io::Result<Self> {
+        if !parent.join("cgroup.controllers").exists() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "not a cgroup v2 directory",
+            ));
+        }
+        std::fs::create_dir(parent.join(name))?;
+        // Removed again on drop if a limit cannot be set
+        let cgroup = Self {
+            path: parent.join(name),
+        };
+        if let Some(bytes) = limits.max_memory_bytes {
+            cgroup.write("memory.max", &bytes.to_string())?;
+            // Not every kernel has swap accounting
+            let _ = cgroup.write("memory.swap.max", "0");
+        }
+        if let Some(cores) = limits.cpu_cores {
+            let quota = ((cores * CPU_PERIOD_US as f64) as u64).max(1000);
+            cgroup.write("cpu.max", &format!("{} {}", quota, CPU_PERIOD_US))?;
+        }
+        if let Some(processes) = limits.max_processes {
+            cgroup.write("pids.max", &processes.to_string())?;
+        }
+        Ok(cgroup)
+    }
+
+    fn write(&self, file: &str, value: &str) -> std::io::Result<()> {
+        std::fs::write(self.path.join(file), value)
+    }
+
+    fn procs_path(&self) -> std::io::Result<CString> {
+        CString::new(self.path.join("cgroup.procs").as_os_str().as_bytes())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
+    }
+}
+
+impl Drop for Cgroup {
+    fn drop(&mut self) {
+        // Kills every process left in the cgroup (Linux 5.14+)
+        let _ = self.write("cgroup.kill", "1");
+        if std::fs::remove_dir(&self.path).is_ok() {
+            return;
+        }
+        // The cgroup can only be removed once its killed processes are gone
+        let path = std::mem::take(&mut self.path);
+        std::thread::spawn(move || {
+            for _ in 0..50 {
+                std::thread::sleep(Duration::from_millis(20));
+                if std::fs::remove_dir(&path).is_ok() {
+                    return;
+                }
+            }
+            tracing::warn!("Failed to remove cgroup {}", path.display());
+        });
+    }
+}
+
+/// Resources of a run, released when it is dropped.
+struct RunGuard {
+    pgid: Option<i32>,
+    scratch: PathBuf,
+    cgroup: Option<Cgroup>,
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        if let Some(pgid) = self.pgid {
+            // Also kills processes the command left running in the background
+            let _ = nix::sys::signal::killpg(
+                nix::unistd::Pid::from_raw(pgid),
+                nix::sys::signal::Signal::SIGKILL,
+            );
+        }
+        if let Err(e) = std::fs::remove_dir_all(&self.scratch) {
+            tracing::warn!(
+                "Failed to remove scratch directory {}: {}",
+                self.scratch.display(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::execution::CancellationToken;
+    use std::time::Instant;
+    use tempfile::TempDir;
+
+    fn executor(dir: &TempDir, command: CommandSpec) -> SubprocessExecutor {
+        let config = SubprocessConfig {
+            scratch_dir: dir.path().join("scratch"),
+            ..Default::default()
+        };
+        SubprocessExecutor::new(command, &config)
+    }
+
+    fn shell(script: &str) -> CommandSpec {
+        CommandSpec::new("sh", ["-c", script])
+    }
+
+    fn payload(input: serde_json::Value) -> TaskPayload {
+        TaskPayload {
+            task_type: TaskType::Custom("tool".to_string()),
+            data: serde_json::json!({ "input": input }),
+            parameters: Default::default(),
+        }
+    }
+
+    fn scratch_is_empty(dir: &TempDir) -> bool {
+        std::fs::read_dir(dir.path().join("scratch"))
+            .unwrap()
+            .next()
+            .is_none()
+    }
+
+    #[tokio::test]
+    async fn test_input_on_stdin_result_on_stdout() {
+        let dir = TempDir::new().unwrap();
+        let input = serde_json::json!({"text": "hello", "n": [1, 2]});
+        let output = executor(&dir, CommandSpec::new("cat", Vec::<String>::new()))
+            .execute(&payload(input.clone()))
+            .await
+            .unwrap();
+        assert_eq!(output, input);
+
+        // Runs in an empty scratch directory with a minimal environment
+        let output = executor(
+            &dir,
+            shell(r#"ls -A | wc -l | tr -d ' '; [ "$HOME" = "$PWD" ] && [ -z "$USER" ] && echo"#),
+        )
+        .execute(&payload(serde_json::Value::Null))
+        .await
+        .unwrap();
+        assert_eq!(output, serde_json::json!(0));
+        assert!(scratch_is_empty(&dir));
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_reported() {
+        let dir = TempDir::new().unwrap();
+        let error = executor(&dir, shell("echo 'bad input' >&2; exit 3"))
+            .execute(&payload(serde_json::Value::Null))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Command failed (exit status: 3): bad input"
+        );
+
+        let error = executor(&dir, shell("echo not json"))
+            .execute(&payload(serde_json::Value::Null))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().starts_with("Invalid command output"));
+
+        // Commands flooding stdout are stopped once they pass the limit
+        let mut command = shell("yes 1");
+        command.limits.max_output_bytes = 1024;
+        let error = executor(&dir, command)
+            .execute(&payload(serde_json::Value::Null))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Command output exceeds the limit of 1024 bytes"
+        );
+        assert!(scratch_is_empty(&dir));
+    }
+
+    #[tokio::test]
+    async fn test_rlimits_stop_runaway_commands() {
+        let dir = TempDir::new().unwrap();
+
+        let mut command = shell("while :; do :; done");
+        command.limits.max_cpu_time = Some(Duration::from_secs(1));
+        let error = executor(&dir, command)
+            .execute(&payload(serde_json::Value::Null))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("signal"), "{}", error);
+
+        let mut command = shell("x=$(head -c 100000000 /dev/zero | tr '\\0' a); echo 1");
+        command.limits.max_memory_bytes = Some(32 * 1024 * 1024);
+        let error = executor(&dir, command)
+            .execute(&payload(serde_json::Value::Null))
+            .await;
+        assert!(error.is_err(), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills_command() {
+        let dir = TempDir::new().unwrap();
+        let mut command = shell("sleep 30");
+        command.limits.timeout = Duration::from_millis(100);
+
+        let started = Instant::now();
+        let error = executor(&dir, command)
+            .execute(&payload(serde_json::Value::Null))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<Interrupted>(),
+            Some(&Interrupted::DeadlineExceeded)
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(scratch_is_empty(&dir));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_cancellation_kills_process_group() {
+        let dir = TempDir::new().unwrap();
+        let pid_file = dir.path().join("pid");
+        let command = CommandSpec::new(
+            "sh",
+            [
+                "-c",
+                r#"sleep 30 & echo $! > "$1"; wait"#,
+                "sh",
+                pid_file.to_str().unwrap(),
+            ],
+        );
+        let ctx = ExecutionContext::new(CancellationToken::new(), Default::default(), None);
+        let run = {
+            let ctx = ctx.clone();
+            let executor = executor(&dir, command);
+            tokio::spawn(async move {
+                executor
+                    .execute_with_context(&payload(serde_json::Value::Null), &ctx)
+                    .await
+            })
+        };
+
+        let mut pid = String::new();
+        for _ in 0..100 {
+            pid = std::fs::read_to_string(&pid_file).unwrap_or_default();
+            if !pid.trim().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        ctx.cancellation.cancel();
+        let error = run.await.unwrap().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<Interrupted>(),
+            Some(&Interrupted::Cancelled)
+        );
+        assert!(scratch_is_empty(&dir));
+
+        // The background `sleep` is gone too (or a zombie awaiting its reaper)
+        let stat = format!("/proc/{}/stat", pid.trim());
+        let mut gone = false;
+        for _ in 0..100 {
+            gone = std::fs::read_to_string(&stat)
+                .map(|stat| stat.contains(") Z "))
+                .unwrap_or(true);
+            if gone {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(gone);
+    }
+}
@@ -2,16 +2,24 @@
 //!
 //! This module contains implementations of `TaskExecutor` for different `TaskType`s.
 
-use crate::agent::ai::{InferenceEngine, ModelManager};
+use crate::agent::ai::{EmbeddingOptions, InferenceEngine, ModelManager};
+use crate::agent::execution::ExecutionContext;
+use crate::agent::sharding::CORPUS_KEY;
 use crate::agent::task::{TaskExecutor, TaskPayload};
 use anyhow::Result;
+use serde::Deserialize;
 use serde_json::json;
 use std::sync::Arc;
 
 /// Executor for text processing tasks.
+///
+/// Clones share the inference engine, and so the models it has loaded.
+#[derive(Clone)]
 pub struct TextProcessingExecutor {
     /// The manager responsible for downloading and caching AI models.
     pub model_manager: Arc<ModelManager>,
+    /// The engine computing embeddings, keeping models loaded across tasks.
+    pub engine: Arc<InferenceEngine>,
 }
 
 impl TextProcessingExecutor {
@@ -21,13 +29,80 @@ impl TextProcessingExecutor {
     ///
     /// * `model_manager` - Shared instance of `ModelManager`.
     pub fn new(model_manager: Arc<ModelManager>) -> Self {
-        Self { model_manager }
+        Self {
+            model_manager,
+            engine: Arc::new(InferenceEngine::new()),
+        }
+    }
+
+    /// Runs an operation over a corpus of documents.
+    async fn execute_corpus(
+        &self,
+        payload: &TaskPayload,
+        operation: &str,
+        documents: &[&str],
+        ctx: &ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        match operation {
+            "word_count" => {
+                let count: usize = documents
+                    .iter()
+                    .map(|doc| doc.split_whitespace().count())
+                    .sum();
+                Ok(json!({ "word_count": count }))
+            }
+            "embed" => {
+                let model_name = model_name(payload);
+                let model_path = self
+                    .model_manager
+                    .ensure_model_cancellable(model_name, &ctx.cancellation)
+                    .await?;
+
+                let options = embedding_options(payload)?;
+                let mut embeddings = Vec::with_capacity(documents.len());
+                for doc in documents {
+                    ctx.check()?;
+                    embeddings.push(self.engine.embed_with(&model_path, doc, &options).await?);
+                }
+
+                Ok(json!({ "embeddings": embeddings, "model": model_name }))
+            }
+            _ => Err(anyhow::anyhow!(
+                "Unknown corpus text operation: {}",
+                operation
+            )),
+        }
     }
 }
 
+/// Model requested by an `embed` payload.
+pub(crate) fn model_name(payload: &TaskPayload) -> &str {
+    payload
+        .data
+        .get("model")
+        .and_then(|v| v.as_str())
+        .unwrap_or("prajjwal1/bert-tiny") // Default tiny model
+}
+
+/// Pooling and normalization requested by an `embed` payload.
+fn embedding_options(payload: &TaskPayload) -> Result<EmbeddingOptions> {
+    EmbeddingOptions::deserialize(&payload.data)
+        .map_err(|e| anyhow::anyhow!("Invalid embedding options: {}", e))
+}
+
 #[async_trait::async_trait]
 impl TaskExecutor for TextProcessingExecutor {
     async fn execute(&self, payload: &TaskPayload) -> Result<serde_json::Value> {
+        self.execute_with_context(payload, &ExecutionContext::detached())
+            .await
+    }
+
+    async fn execute_with_context(
+        &self,
+        payload: &TaskPayload,
+        ctx: &ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        ctx.check()?;
         let operation = payload
             .data
             .get("operation")
@@ -39,6 +114,14 @@ impl TaskExecutor for TextProcessingExecutor {
             .and_then(|v| v.as_str())
             .unwrap_or("");
 
+        // Sharded corpus jobs (see `agent::sharding`) carry documents instead of one text
+        if let Some(corpus) = payload.data.get(CORPUS_KEY).and_then(|v| v.as_array()) {
+            let documents: Vec<&str> = corpus.iter().filter_map(|v| v.as_str()).collect();
+            return self
+                .execute_corpus(payload, operation, &documents, ctx)
+                .await;
+        }
+
         match operation {
             "word_count" => {
                 let count = text.split_whitespace().count();
@@ -54,20 +137,45 @@ impl TaskExecutor for TextProcessingExecutor {
             }
             "embed" => {
                 // AI Task!
-                let model_name = payload
-                    .data
-                    .get("model")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("prajjwal1/bert-tiny"); // Default tiny model
-
-                let model_path = self.model_manager.ensure_model(model_name).await?;
+                let model_name = model_name(payload);
+                let model_path = self
+                    .model_manager
+                    .ensure_model_cancellable(model_name, &ctx.cancellation)
+                    .await?;
+                ctx.check()?;
 
-                let engine = InferenceEngine::new();
-                let embedding = engine.embed(&model_path, text).await?;
+                let options = embedding_options(payload)?;
+                let embedding = self.engine.embed_with(&model_path, text, &options).await?;
 
                 Ok(json!({ "embedding": embedding, "model": model_name }))
             }
             _ => Err(anyhow::anyhow!("Unknown text operation: {}", operation)),
         }
     }
+
+    fn input_schema(&self) -> Option<serde_json::Value> {
+        Some(json!({
+            "type": "object",
+            "required": ["operation"],
+            "properties": {
+                "operation": { "type": "string" },
+                "text": { "type": "string" },
+                (CORPUS_KEY): { "type": "array", "items": { "type": "string" } },
+                "model": { "type": "string", "minLength": 1 },
+                "pooling": { "enum": ["mean", "cls"] },
+                "normalize": { "type": "boolean" },
+            },
+            "allOf": [{
+                "if": {
+                    "required": ["operation"],
+                    "properties": {
+                        "operation": { "enum": ["word_count", "reverse", "tokenize", "embed"] },
+                    },
+                },
+                "then": {
+                    "anyOf": [{ "required": ["text"] }, { "required": [CORPUS_KEY] }],
+                },
+            }],
+        }))
+    }
 }
@@ -1,5 +1,6 @@
 //! Vector computation executor.
 
+use crate::agent::execution::ExecutionContext;
 use crate::agent::task::{TaskExecutor, TaskPayload};
 use anyhow::Result;
 use serde_json::json;
@@ -54,4 +55,38 @@ impl TaskExecutor for VectorComputationExecutor {
             _ => Err(anyhow::anyhow!("Unknown vector operation: {}", operation)),
         }
     }
+
+    async fn execute_with_context(
+        &self,
+        payload: &TaskPayload,
+        ctx: &ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        ctx.check()?;
+        self.execute(payload).await
+    }
+
+    fn input_schema(&self) -> Option<serde_json::Value> {
+        // Other operations are left to the executor that runs the task, which
+        // may be a peer's
+        Some(json!({
+            "type": "object",
+            "required": ["operation"],
+            "properties": {
+                "operation": { "type": "string" },
+            },
+            "allOf": [{
+                "if": {
+                    "required": ["operation"],
+                    "properties": { "operation": { "const": "cosine_similarity" } },
+                },
+                "then": {
+                    "required": ["vector_a", "vector_b"],
+                    "properties": {
+                        "vector_a": { "type": "array", "items": { "type": "number" } },
+                        "vector_b": { "type": "array", "items": { "type": "number" } },
+                    },
+                },
+            }],
+        }))
+    }
 }
@@ -0,0 +1,554 @@
+//! Sandboxed WebAssembly executor for `Custom("wasm")` tasks.
+//!
+//! The payload names a module by the hex SHA-256 of its bytes, and may carry the
+//! module itself the first time it is sent:
+//!
+//! ```json
+//! { "module_hash": "<sha256>", "module": "<base64, optional>", "input": { ... } }
+//! ```
+//!
+//! Modules are kept in a [`ModuleStore`] on disk, and validated before they are
+//! stored and again when they are read back.
+//!
+//! # ABI
+//!
+//! A module must not import anything, so it has no access to the filesystem,
+//! the network, clocks or any other host state. It exports:
+//!
+//! - `memory`: its linear memory;
+//! - `alloc(len: i32) -> i32`: reserves `len` bytes and returns their address;
+//! - `run(ptr: i32, len: i32) -> i64`: processes the input at `ptr` and returns
+//!   the address of its output in the high 32 bits and its length in the low
+//!   32 bits.
+//!
+//! The input is the payload's `input` serialized as JSON, and the output must be
+//! JSON too. Every run gets [`WasmConfig::fuel`] units of fuel, roughly one per
+//! instruction, and at most [`WasmConfig::max_memory_bytes`] of memory.
+//!
+//! Without the `wasm` feature the executor is not registered, and modules can
+//! neither be stored nor run.
+
+use crate::agent::execution::ExecutionContext;
+use crate::agent::task::{TaskExecutor, TaskPayload};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::fs;
+
+/// Name of the `Custom` task type run by the [`WasmExecutor`].
+pub const WASM_TASK_TYPE: &str = "wasm";
+
+/// Limits and module cache location of the WASM executor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WasmConfig {
+    /// Directory modules are cached in.
+    pub module_dir: PathBuf,
+    /// Fuel available to a single run.
+    pub fuel: u64,
+    /// Largest linear memory a module may use.
+    pub max_memory_bytes: usize,
+    /// Largest module accepted.
+    pub max_module_bytes: usize,
+    /// Largest output a module may return.
+    pub max_output_bytes: usize,
+}
+
+impl Default for WasmConfig {
+    fn default() -> Self {
+        Self {
+            module_dir: PathBuf::from(".p2p-ai-agents/wasm"),
+            fuel: 100_000_000,
+            max_memory_bytes: 64 * 1024 * 1024,
+            max_module_bytes: 8 * 1024 * 1024,
+            max_output_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+/// Errors of storing and running WASM modules.
+#[derive(Debug, Error)]
+pub enum WasmError {
+    /// The crate was built without the `wasm` feature.
+    #[error("WASM support is not enabled")]
+    Disabled,
+    /// The payload does not reference a module.
+    #[error("Payload does not name a module")]
+    MissingModule,
+    /// A module hash is not a hex SHA-256 digest.
+    #[error("Invalid module hash: {0}")]
+    InvalidHash(String),
+    /// The module is neither cached nor included in the payload.
+    #[error("Module {0} not found")]
+    NotFound(String),
+    /// The module bytes do not match the hash they were referenced by.
+    #[error("Module does not match hash {expected} (got {actual})")]
+    HashMismatch {
+        /// Hash the module was referenced by.
+        expected: String,
+        /// Hash of the bytes received or read.
+        actual: String,
+    },
+    /// The module is larger than `WasmConfig::max_module_bytes`.
+    #[error("Module of {size} bytes exceeds the limit of {limit} bytes")]
+    TooLarge {
+        /// Size of the module.
+        size: usize,
+        /// Largest module accepted.
+        limit: usize,
+    },
+    /// The module is malformed or does not follow the ABI.
+    #[error("Invalid module: {0}")]
+    Invalid(String),
+    /// The module used up its fuel.
+    #[error("Module ran out of fuel")]
+    OutOfFuel,
+    /// The module trapped or exceeded its memory limit.
+    #[error("Module trapped: {0}")]
+    Trap(String),
+    /// The module returned output that is out of bounds or not JSON.
+    #[error("Invalid module output: {0}")]
+    InvalidOutput(String),
+    /// Reading or writing the module cache failed.
+    #[error("Module cache error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Returns the hex SHA-256 of module bytes, the hash modules are referenced by.
+pub fn module_hash(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn check_hash(hash: &str) -> Result<(), WasmError> {
+    if hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(WasmError::InvalidHash(hash.to_string()))
+    }
+}
+
+/// Content-addressed cache of validated modules.
+#[derive(Debug, Clone)]
+pub struct ModuleStore {
+    dir: PathBuf,
+    max_module_bytes: usize,
+}
+
+impl ModuleStore {
+    /// Creates a store keeping modules of up to `max_module_bytes` in `dir`.
+    pub fn new(dir: impl Into<PathBuf>, max_module_bytes: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            max_module_bytes,
+        }
+    }
+
+    fn path(&self, hash: &str) -> PathBuf {
+        self.dir.join(format!("{}.wasm", hash.to_ascii_lowercase()))
+    }
+
+    /// Returns true if a module with the hash is cached.
+    pub fn contains(&self, hash: &str) -> bool {
+        check_hash(hash).is_ok() && self.path(hash).exists()
+    }
+
+    /// Validates a module and caches it, returning its hash.
+    pub async fn insert(&self, bytes: &[u8]) -> Result<String, WasmError> {
+        self.validate(bytes)?;
+        let hash = module_hash(bytes);
+        let path = self.path(&hash);
+        if path.exists() {
+            return Ok(hash);
+        }
+
+        fs::create_dir_all(&self.dir).await?;
+        // Write to a temporary file first so readers never see a partial module
+        let partial = self.dir.join(format!(".{}.partial", hash));
+        fs::write(&partial, bytes).await?;
+        fs::rename(&partial, &path).await?;
+        Ok(hash)
+    }
+
+    /// Reads a cached module, checking it still matches its hash.
+    pub async fn get(&self, hash: &str) -> Result<Vec<u8>, WasmError> {
+        check_hash(hash)?;
+        let bytes = match fs::read(self.path(hash)).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(WasmError::NotFound(hash.to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let actual = module_hash(&bytes);
+        if !actual.eq_ignore_ascii_case(hash) {
+            return Err(WasmError::HashMismatch {
+                expected: hash.to_string(),
+                actual,
+            });
+        }
+        Ok(bytes)
+    }
+
+    /// Checks a module's size and that it follows the ABI.
+    pub fn validate(&self, bytes: &[u8]) -> Result<(), WasmError> {
+        if bytes.len() > self.max_module_bytes {
+            return Err(WasmError::TooLarge {
+                size: bytes.len(),
+                limit: self.max_module_bytes,
+            });
+        }
+        sandbox::validate(bytes)
+    }
+
+    /// Returns the directory modules are cached in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Executor running WASM modules in a sandbox.
+pub struct WasmExecutor {
+    store: ModuleStore,
+    config: WasmConfig,
+}
+
+impl WasmExecutor {
+    /// Creates an executor with the given limits and module cache.
+    pub fn new(config: WasmConfig) -> Self {
+        Self {
+            store: ModuleStore::new(config.module_dir.clone(), config.max_module_bytes),
+            config,
+        }
+    }
+
+    /// Returns the module cache.
+    pub fn modules(&self) -> &ModuleStore {
+        &self.store
+    }
+
+    /// Loads the module a payload references, caching it if it is included.
+    async fn load_module(&self, payload: &TaskPayload) -> Result<Vec<u8>, WasmError> {
+        let hash = payload
+            .data
+            .get("module_hash")
+            .and_then(|v| v.as_str())
+            .ok_or(WasmError::MissingModule)?;
+        check_hash(hash)?;
+
+        if self.store.contains(hash) {
+            return self.store.get(hash).await;
+        }
+        let Some(encoded) = payload.data.get("module").and_then(|v| v.as_str()) else {
+            return Err(WasmError::NotFound(hash.to_string()));
+        };
+
+        use base64::Engine as _;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| WasmError::Invalid(format!("module is not base64: {}", e)))?;
+        let actual = module_hash(&bytes);
+        if !actual.eq_ignore_ascii_case(hash) {
+            return Err(WasmError::HashMismatch {
+                expected: hash.to_string(),
+                actual,
+            });
+        }
+        self.store.insert(&bytes).await?;
+        Ok(bytes)
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskExecutor for WasmExecutor {
+    async fn execute(&self, payload: &TaskPayload) -> Result<serde_json::Value> {
+        self.execute_with_context(payload, &ExecutionContext::detached())
+            .await
+    }
+
+    async fn execute_with_context(
+        &self,
+        payload: &TaskPayload,
+        ctx: &ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        let module = self.load_module(payload).await?;
+        let input = serde_json::to_vec(
+            payload
+                .data
+                .get("input")
+                .unwrap_or(&serde_json::Value::Null),
+        )?;
+        ctx.check()?;
+
+        // Modules run to completion or until their fuel is used up; fuel bounds
+        // how long a cancelled task keeps its worker
+        let config = self.config.clone();
+        let output =
+            tokio::task::spawn_blocking(move || sandbox::run(&module, &input, &config)).await??;
+        ctx.check()?;
+        Ok(output)
+    }
+
+    fn input_schema(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "type": "object",
+            "required": ["module_hash"],
+            "properties": {
+                "module_hash": { "type": "string", "pattern": "^[0-9a-fA-F]{64}$" },
+                "module": { "type": "string", "contentEncoding": "base64" },
+                "input": {},
+            },
+        }))
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod sandbox {
+    use super::{WasmConfig, WasmError};
+    use wasmi::core::{TrapCode, ValType};
+    use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+    struct HostState {
+        limits: StoreLimits,
+    }
+
+    fn engine() -> Engine {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        Engine::new(&config)
+    }
+
+    fn trap(e: wasmi::Error) -> WasmError {
+        match e.as_trap_code() {
+            Some(TrapCode::OutOfFuel) => WasmError::OutOfFuel,
+            _ => WasmError::Trap(e.to_string()),
+        }
+    }
+
+    fn check_func(
+        module: &Module,
+        name: &str,
+        params: &[ValType],
+        results: &[ValType],
+    ) -> Result<(), WasmError> {
+        let matches = module.exports().any(|export| {
+            export.name() == name
+                && export
+                    .ty()
+                    .func()
+                    .is_some_and(|ty| ty.params() == params && ty.results() == results)
+        });
+        if matches {
+            Ok(())
+        } else {
+            Err(WasmError::Invalid(format!(
+                "missing export `{}` of type {:?} -> {:?}",
+                name, params, results
+            )))
+        }
+    }
+
+    /// Parses a module and checks it follows the ABI.
+    pub(super) fn validate(bytes: &[u8]) -> Result<(), WasmError> {
+        let module =
+            Module::new(&engine(), bytes).map_err(|e| WasmError::Invalid(e.to_string()))?;
+        check_abi(&module)
+    }
+
+    fn check_abi(module: &Module) -> Result<(), WasmError> {
+        if let Some(import) = module.imports().next() {
+            return Err(WasmError::Invalid(format!(
+                "imports are not allowed (imports `{}.{}`)",
+                import.module(),
+                import.name()
+            )));
+        }
+        if !module
+            .exports()
+            .any(|export| export.name() == "memory" && export.ty().memory().is_some())
+        {
+            return Err(WasmError::Invalid("missing export `memory`".to_string()));
+        }
+        check_func(module, "alloc", &[ValType::I32], &[ValType::I32])?;
+        check_func(
+            module,
+            "run",
+            &[ValType::I32, ValType::I32],
+            &[ValType::I64],
+        )
+    }
+
+    /// Runs a module on `input` within the configured limits.
+    pub(super) fn run(
+        bytes: &[u8],
+        input: &[u8],
+        config: &WasmConfig,
+    ) -> Result<serde_json::Value, WasmError> {
+        let engine = engine();
+        let module = Module::new(&engine, bytes).map_err(|e| WasmError::Invalid(e.to_string()))?;
+        check_abi(&module)?;
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(config.max_memory_bytes)
+            .instances(1)
+            .memories(1)
+            .tables(1)
+            .build();
+        let mut store = Store::new(&engine, HostState { limits });
+        store.limiter(|state| &mut state.limits);
+        store
+            .set_fuel(config.fuel)
+            .map_err(|e| WasmError::Trap(e.to_string()))?;
+
+        // Nothing is linked in, so the module has no access to the host
+        let instance = Linker::<HostState>::new(&engine)
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(trap)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| WasmError::Invalid("missing export `memory`".to_string()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|e| WasmError::Invalid(e.to_string()))?;
+        let run = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "run")
+            .map_err(|e| WasmError::Invalid(e.to_string()))?;
+
+        let len = i32::try_from(input.len())
+            .map_err(|_| WasmError::Trap("input too large".to_string()))?;
+        let ptr = alloc.call(&mut store, len).map_err(trap)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .map_err(|e| WasmError::Trap(format!("input out of bounds: {}", e)))?;
+
+        let packed = run.call(&mut store, (ptr, len)).map_err(trap)? as u64;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        if out_len > config.max_output_bytes {
+            return Err(WasmError::InvalidOutput(format!(
+                "{} bytes exceed the limit of {} bytes",
+                out_len, config.max_output_bytes
+            )));
+        }
+        let mut output = vec![0; out_len];
+        memory
+            .read(&store, out_ptr, &mut output)
+            .map_err(|e| WasmError::InvalidOutput(format!("out of bounds: {}", e)))?;
+        serde_json::from_slice(&output).map_err(|e| WasmError::InvalidOutput(e.to_string()))
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+mod sandbox {
+    use super::{WasmConfig, WasmError};
+
+    pub(super) fn validate(_bytes: &[u8]) -> Result<(), WasmError> {
+        Err(WasmError::Disabled)
+    }
+
+    pub(super) fn run(
+        _bytes: &[u8],
+        _input: &[u8],
+        _config: &WasmConfig,
+    ) -> Result<serde_json::Value, WasmError> {
+        Err(WasmError::Disabled)
+    }
+}
+
+#[cfg(all(test, feature = "wasm"))]
+mod tests {
+    use super::*;
+
+    /// Echoes its input back.
+    const ECHO: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (global $next (mut i32) (i32.const 1024))
+          (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get $len)))
+            (local.get $ptr))
+          (func (export "run") (param $ptr i32) (param $len i32) (result i64)
+            (i64.or
+              (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+              (i64.extend_i32_u (local.get $len)))))
+    "#;
+
+    fn config(dir: &Path) -> WasmConfig {
+        WasmConfig {
+            module_dir: dir.to_path_buf(),
+            fuel: 100_000,
+            max_memory_bytes: 1024 * 1024,
+            ..Default::default()
+        }
+    }
+
+    fn run(wat: &str, config: &WasmConfig) -> Result<serde_json::Value, WasmError> {
+        sandbox::run(&wat::parse_str(wat).unwrap(), br#"{"n":1}"#, config)
+    }
+
+    #[test]
+    fn test_limits_stop_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(dir.path());
+        assert_eq!(run(ECHO, &config).unwrap(), serde_json::json!({"n": 1}));
+
+        let spin = r#"
+            (module
+              (memory (export "memory") 1)
+              (func (export "alloc") (param i32) (result i32) (i32.const 0))
+              (func (export "run") (param i32 i32) (result i64)
+                (loop $forever (br $forever))
+                (i64.const 0)))
+        "#;
+        assert!(matches!(run(spin, &config), Err(WasmError::OutOfFuel)));
+
+        // 32 pages of 64 KiB are over the 1 MiB memory cap
+        let hungry = ECHO.replace(
+            "(memory (export \"memory\") 1)",
+            "(memory (export \"memory\") 32)",
+        );
+        assert!(matches!(run(&hungry, &config), Err(WasmError::Trap(_))));
+    }
+
+    #[tokio::test]
+    async fn test_store_rejects_modules_outside_the_abi() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ModuleStore::new(dir.path(), 1024);
+
+        let with_import = r#"
+            (module
+              (import "env" "now" (func (result i64)))
+              (memory (export "memory") 1)
+              (func (export "alloc") (param i32) (result i32) (i32.const 0))
+              (func (export "run") (param i32 i32) (result i64) (i64.const 0)))
+        "#;
+        let err = store
+            .insert(&wat::parse_str(with_import).unwrap())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WasmError::Invalid(_)), "{}", err);
+
+        let no_run = r#"(module (memory (export "memory") 1))"#;
+        assert!(store
+            .insert(&wat::parse_str(no_run).unwrap())
+            .await
+            .is_err());
+        assert!(matches!(
+            store.insert(&[0; 2048]).await,
+            Err(WasmError::TooLarge { .. })
+        ));
+
+        let echo = wat::parse_str(ECHO).unwrap();
+        let hash = store.insert(&echo).await.unwrap();
+        assert_eq!(store.get(&hash).await.unwrap(), echo);
+
+        // A corrupted cache entry no longer matches its hash
+        std::fs::write(dir.path().join(format!("{}.wasm", hash)), b"\0asm").unwrap();
+        assert!(matches!(
+            store.get(&hash).await,
+            Err(WasmError::HashMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+//! Task groups for batch submission.
+//!
+//! `Agent::submit_batch` tags every task of a batch with a new [`GroupId`]
+//! (`Task::group`). The group can then be tracked as a whole: its
+//! [`GroupStatus`] counts its tasks per status, `Agent::cancel_group` cancels
+//! every unfinished task, and `Agent::wait_for_group` resolves once all of them
+//! have finished.
+//!
+//! Tasks of a batch that repeat an earlier submission (see
+//! `TaskManager::try_add_task`) are not added again and do not join the group.
+//! Recurring templates in a batch are not counted in the group's status; the
+//! occurrences they submit join the group instead.
+
+use crate::agent::task::{Task, TaskStatus};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Identifier of a task group.
+pub type GroupId = Uuid;
+
+/// Number of tasks of a group in each status.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupStatus {
+    /// Number of tasks in the group.
+    pub total: usize,
+    /// Tasks waiting to run.
+    pub queued: usize,
+    /// Tasks currently running.
+    pub running: usize,
+    /// Tasks that completed.
+    pub completed: usize,
+    /// Tasks that failed.
+    pub failed: usize,
+    /// Tasks that were cancelled.
+    pub cancelled: usize,
+    /// Tasks that timed out.
+    pub timed_out: usize,
+}
+
+impl GroupStatus {
+    /// Counts the statuses of `tasks`.
+    pub fn of<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Self {
+        let mut status = Self::default();
+        for task in tasks {
+            status.total += 1;
+            match task.status {
+                TaskStatus::Queued => status.queued += 1,
+                TaskStatus::Running => status.running += 1,
+                TaskStatus::Completed(_) => status.completed += 1,
+                TaskStatus::Failed(_) => status.failed += 1,
+                TaskStatus::Cancelled => status.cancelled += 1,
+                TaskStatus::Timeout => status.timed_out += 1,
+            }
+        }
+        status
+    }
+
+    /// Number of tasks that have finished, successfully or not.
+    pub fn finished(&self) -> usize {
+        self.completed + self.failed + self.cancelled + self.timed_out
+    }
+
+    /// Returns true once every task of the group has finished.
+    pub fn is_finished(&self) -> bool {
+        self.finished() == self.total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_per_status() {
+        let statuses = [
+            TaskStatus::Queued,
+            TaskStatus::Running,
+            TaskStatus::Completed(serde_json::json!(1)),
+            TaskStatus::Completed(serde_json::json!(2)),
+            TaskStatus::Failed("error".to_string()),
+            TaskStatus::Cancelled,
+            TaskStatus::Timeout,
+        ];
+        let tasks: Vec<Task> = statuses
+            .into_iter()
+            .map(|status| {
+                let mut task = Task::new("member");
+                task.status = status;
+                task
+            })
+            .collect();
+
+        let status = GroupStatus::of(&tasks);
+        assert_eq!(
+            status,
+            GroupStatus {
+                total: 7,
+                queued: 1,
+                running: 1,
+                completed: 2,
+                failed: 1,
+                cancelled: 1,
+                timed_out: 1,
+            }
+        );
+        assert_eq!(status.finished(), 5);
+        assert!(!status.is_finished());
+        assert!(GroupStatus::of(&tasks[2..]).is_finished());
+    }
+}
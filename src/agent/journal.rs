@@ -0,0 +1,169 @@
+//! Append-only journal of task state transitions.
+//!
+//! A `TaskManager` with a journal (see `TaskManager::with_journal`) appends a
+//! snapshot of a task to the journal, and syncs it to disk, before writing the
+//! task to its `Storage`. A crash can therefore leave a task's storage entry
+//! stale or torn, but never the journal: `TaskManager::load_tasks` replays the
+//! journal over the stored tasks so the last recorded transition of every task
+//! wins. Once replayed and written back, the journal is checkpointed (emptied).
+//!
+//! Each line of the journal file holds one JSON encoded [`JournalEntry`]. A
+//! torn last line from a crash mid-append is skipped on replay.
+
+use crate::agent::task::Task;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// One recorded transition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// When the transition was recorded.
+    pub recorded_at: SystemTime,
+    /// The task after the transition.
+    pub task: Task,
+}
+
+/// Append-only, file-backed task journal.
+pub struct TaskJournal {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl TaskJournal {
+    /// Opens the journal at `path`, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+
+        // Terminate a torn last entry so the next append starts on a new line
+        let len = file.metadata()?.len();
+        if len > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::Start(len - 1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n")?;
+            }
+        }
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Returns the path of the journal file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends the state of a task and syncs it to disk.
+    pub fn append(&self, task: &Task) -> anyhow::Result<()> {
+        let entry = JournalEntry {
+            recorded_at: SystemTime::now(),
+            task: task.clone(),
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Journal lock poisoned"))?;
+        file.write_all(&line)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Reads all entries in the order they were appended.
+    pub fn replay(&self) -> anyhow::Result<Vec<JournalEntry>> {
+        let _file = self
+            .file
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Journal lock poisoned"))?;
+        let reader = BufReader::new(File::open(&self.path)?);
+
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<JournalEntry>(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => tracing::warn!("Skipping unreadable journal entry: {}", e),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Empties the journal. Only safe once every journaled task is in storage.
+    pub fn checkpoint(&self) -> anyhow::Result<()> {
+        let file = self
+            .file
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Journal lock poisoned"))?;
+        file.set_len(0)?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::task::TaskStatus;
+
+    #[test]
+    fn test_replay_in_append_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = TaskJournal::open(dir.path().join("journal.log")).unwrap();
+
+        let mut task = Task::new("journaled");
+        journal.append(&task).unwrap();
+        task.status = TaskStatus::Running;
+        journal.append(&task).unwrap();
+
+        let reopened = TaskJournal::open(journal.path()).unwrap();
+        let entries = reopened.replay().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].task.status, TaskStatus::Queued);
+        assert_eq!(entries[1].task.status, TaskStatus::Running);
+    }
+
+    #[test]
+    fn test_torn_entry_is_skipped_and_checkpoint_empties() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.log");
+        let journal = TaskJournal::open(&path).unwrap();
+        journal.append(&Task::new("complete entry")).unwrap();
+
+        // Simulate a crash in the middle of an append
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"recorded_at\":").unwrap();
+
+        assert_eq!(journal.replay().unwrap().len(), 1);
+
+        // After a restart, new entries are not glued to the torn one
+        let journal = TaskJournal::open(&path).unwrap();
+        journal.append(&Task::new("after restart")).unwrap();
+        assert_eq!(journal.replay().unwrap().len(), 2);
+
+        journal.checkpoint().unwrap();
+        assert!(journal.replay().unwrap().is_empty());
+
+        // Appends after a checkpoint start a fresh journal
+        journal.append(&Task::new("after checkpoint")).unwrap();
+        assert_eq!(journal.replay().unwrap().len(), 1);
+    }
+}
@@ -1,7 +1,11 @@
 //! Messaging module for Agent communication.
 
-use crate::agent::task::{Task, TaskId, TaskStatus};
+use crate::agent::bidding::Bid;
+use crate::agent::checkpoint::Checkpoint;
+use crate::agent::executors::schema::TaskSchema;
+use crate::agent::task::{ProgressUpdate, Task, TaskId, TaskStatus, TaskType};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use uuid::Uuid;
 
 /// Unique identifier for a message.
@@ -24,16 +28,72 @@ pub enum MessageType {
     /// Announcement of agent capabilities.
     CapabilityAnnouncement {
         /// List of supported task types.
-        capabilities: Vec<crate::agent::task::TaskType>,
+        capabilities: Vec<TaskType>,
         /// List of available AI models.
         #[serde(default)]
         models: Vec<String>,
+        /// Input schemas of the supported task types that have one.
+        #[serde(default)]
+        schemas: Vec<TaskSchema>,
     },
     /// Request to cancel a task.
     TaskCancellation {
         /// ID of the task to cancel.
         task_id: TaskId,
     },
+    /// Progress of a running task (percent, partial output, ETA).
+    TaskProgress {
+        /// ID of the task.
+        task_id: TaskId,
+        /// The progress report.
+        update: ProgressUpdate,
+    },
+    /// Checkpoint of a running task, pushed by the peer running it to the
+    /// requester so a retry can resume from it.
+    TaskCheckpoint {
+        /// ID of the task.
+        task_id: TaskId,
+        /// The latest checkpoint.
+        checkpoint: Checkpoint,
+    },
+    /// Request for the current status of a task, answered with a `TaskResponse`
+    /// or `TaskUnknown`.
+    TaskStatusQuery {
+        /// ID of the task.
+        task_id: TaskId,
+    },
+    /// Reply to a `TaskStatusQuery` for a task the peer has no record of.
+    TaskUnknown {
+        /// ID of the task.
+        task_id: TaskId,
+    },
+    /// Reply to a `TaskRequest` the peer declined to accept, e.g. because it
+    /// is over its resource limits.
+    TaskRejected {
+        /// ID of the task.
+        task_id: TaskId,
+        /// Why the task was rejected.
+        reason: String,
+        /// How long the peer expects to stay unable to accept tasks.
+        retry_after: Duration,
+    },
+    /// Call for proposals to run a task, answered with a `TaskBid` by peers
+    /// able to run it.
+    TaskOffer {
+        /// ID of the offered task.
+        task_id: TaskId,
+        /// Type of the task.
+        task_type: TaskType,
+        /// AI model the task needs, if any.
+        model: Option<String>,
+    },
+    /// A peer's proposal to run an offered task.
+    TaskBid {
+        /// ID of the offered task.
+        task_id: TaskId,
+        /// The proposal.
+        bid: Bid,
+    },
 }
 
 /// A message exchanged between agents.
@@ -113,6 +173,7 @@ impl Message {
         sender: impl Into<String>,
         capabilities: Vec<crate::agent::task::TaskType>,
         models: Vec<String>,
+        schemas: Vec<TaskSchema>,
     ) -> Self {
         Self {
             id: Uuid::new_v4(),
@@ -121,6 +182,7 @@ impl Message {
             content: MessageType::CapabilityAnnouncement {
                 capabilities,
                 models,
+                schemas,
             },
             timestamp: chrono::Utc::now(),
             signature: None,
@@ -145,6 +207,143 @@ impl Message {
         }
     }
 
+    /// Creates a task progress message.
+    pub fn new_task_progress(
+        sender: impl Into<String>,
+        recipient: impl Into<String>,
+        task_id: TaskId,
+        update: ProgressUpdate,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            sender: sender.into(),
+            recipient: recipient.into(),
+            content: MessageType::TaskProgress { task_id, update },
+            timestamp: chrono::Utc::now(),
+            signature: None,
+            public_key: None,
+        }
+    }
+
+    /// Creates a task checkpoint message.
+    pub fn new_task_checkpoint(
+        sender: impl Into<String>,
+        recipient: impl Into<String>,
+        task_id: TaskId,
+        checkpoint: Checkpoint,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            sender: sender.into(),
+            recipient: recipient.into(),
+            content: MessageType::TaskCheckpoint {
+                task_id,
+                checkpoint,
+            },
+            timestamp: chrono::Utc::now(),
+            signature: None,
+            public_key: None,
+        }
+    }
+
+    /// Creates a task status query message.
+    pub fn new_task_status_query(
+        sender: impl Into<String>,
+        recipient: impl Into<String>,
+        task_id: TaskId,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            sender: sender.into(),
+            recipient: recipient.into(),
+            content: MessageType::TaskStatusQuery { task_id },
+            timestamp: chrono::Utc::now(),
+            signature: None,
+            public_key: None,
+        }
+    }
+
+    /// Creates a reply for a task the sender has no record of.
+    pub fn new_task_unknown(
+        sender: impl Into<String>,
+        recipient: impl Into<String>,
+        task_id: TaskId,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            sender: sender.into(),
+            recipient: recipient.into(),
+            content: MessageType::TaskUnknown { task_id },
+            timestamp: chrono::Utc::now(),
+            signature: None,
+            public_key: None,
+        }
+    }
+
+    /// Creates a reply declining a task request.
+    pub fn new_task_rejected(
+        sender: impl Into<String>,
+        recipient: impl Into<String>,
+        task_id: TaskId,
+        reason: impl Into<String>,
+        retry_after: Duration,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            sender: sender.into(),
+            recipient: recipient.into(),
+            content: MessageType::TaskRejected {
+                task_id,
+                reason: reason.into(),
+                retry_after,
+            },
+            timestamp: chrono::Utc::now(),
+            signature: None,
+            public_key: None,
+        }
+    }
+
+    /// Creates a call for proposals to run a task.
+    pub fn new_task_offer(
+        sender: impl Into<String>,
+        recipient: impl Into<String>,
+        task_id: TaskId,
+        task_type: TaskType,
+        model: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            sender: sender.into(),
+            recipient: recipient.into(),
+            content: MessageType::TaskOffer {
+                task_id,
+                task_type,
+                model,
+            },
+            timestamp: chrono::Utc::now(),
+            signature: None,
+            public_key: None,
+        }
+    }
+
+    /// Creates a bid for an offered task.
+    pub fn new_task_bid(
+        sender: impl Into<String>,
+        recipient: impl Into<String>,
+        task_id: TaskId,
+        bid: Bid,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            sender: sender.into(),
+            recipient: recipient.into(),
+            content: MessageType::TaskBid { task_id, bid },
+            timestamp: chrono::Utc::now(),
+            signature: None,
+            public_key: None,
+        }
+    }
+
     /// Serializes the core message data for signing.
     /// Excludes the signature field itself.
     pub fn to_signable_bytes(&self) -> Vec<u8> {
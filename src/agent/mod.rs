@@ -5,24 +5,82 @@
 
 /// AI integration and model management.
 pub mod ai;
+/// Bidding rounds picking the peer a task is dispatched to.
+pub mod bidding;
+/// Checkpoints of long-running tasks, for resuming them elsewhere.
+pub mod checkpoint;
+/// Task dependency graphs and result piping.
+pub mod dependencies;
+/// Task lifecycle events published on the event bus.
+pub mod events;
+/// Cancellation, progress and deadlines handed to executors.
+pub mod execution;
 pub mod executors;
+/// Batch submission and task groups.
+pub mod groups;
 pub mod identity;
+/// Write-ahead journal of task state transitions.
+pub mod journal;
 pub mod messaging;
+/// Peer selection strategies for task dispatch.
+pub mod peer_selection;
+/// Filtered, sorted and paginated task listings.
+pub mod query;
+/// Per-requester task quotas by reputation tier.
+pub mod quota;
+/// Cron-style recurrence for scheduled tasks.
+pub mod recurrence;
 pub mod resource;
+/// Content-addressed caching of deterministic task results.
+pub mod result_cache;
+/// Retention and pruning of finished tasks.
+pub mod retention;
+/// Priority scheduling for queued tasks.
+pub mod scheduler;
+/// Map-reduce sharding of large tasks across peers.
+pub mod sharding;
 pub mod task;
+/// Redundant execution with k-of-n result voting.
+pub mod voting;
+/// Bounded worker pool for local task execution.
+pub mod worker_pool;
 
 use crate::agent::ai::ModelManager;
+use crate::agent::bidding::{BidRound, BiddingConfig};
+use crate::agent::checkpoint::CheckpointWriter;
+use crate::agent::events::{TaskDispatch, TaskDispatched};
+use crate::agent::execution::{CancellationToken, ExecutionContext, Interrupted};
+use crate::agent::executors::schema::InvalidPayload;
+use crate::agent::executors::subprocess::SubprocessConfig;
+use crate::agent::executors::wasm::WasmConfig;
 use crate::agent::executors::{
-    ExecutorRegistry, TextProcessingExecutor, VectorComputationExecutor,
+    ExecutorRegistry, ModelDownloadExecutor, SimulatedExecutor, TextProcessingExecutor,
+    VectorComputationExecutor,
 };
+use crate::agent::groups::{GroupId, GroupStatus};
 use crate::agent::identity::AgentIdentity;
 use crate::agent::messaging::{Message, MessageType};
-use crate::agent::task::{Task, TaskExecutor, TaskId, TaskManager, TaskStatus, TaskType};
+use crate::agent::peer_selection::{PeerCandidate, PeerSelectionStrategy, PeerSelector};
+use crate::agent::query::{TaskPage, TaskQuery};
+use crate::agent::quota::{QuotaConfig, QuotaExceeded, RequesterQuotas};
+use crate::agent::resource::{ResourceError, ResourceMonitor};
+use crate::agent::sharding::{CorpusSplitter, Sharding, TaskSplitter};
+use crate::agent::task::{
+    ProgressReporter, Recovery, SubmitOutcome, Task, TaskExecutor, TaskId, TaskManager,
+    TaskPayload, TaskStatus, TaskType,
+};
+use crate::agent::voting::{
+    Redundancy, VoteOutcome, VoteTally, AGREEMENT_REWARD, DISAGREEMENT_PENALTY,
+};
+use crate::agent::worker_pool::{ConcurrencyLimit, WorkerPool, DEFAULT_MAX_CONCURRENT_TASKS};
+use crate::core::events::EventBus;
 use crate::core::identity::IdentityError;
+use crate::network::reputation::{ReputationManager, ReputationTier};
 use crate::network::{NetworkConfig, NetworkManager, NetworkMessage, PeerId as NetworkPeerId};
 use futures::future::{AbortHandle, Abortable};
 use serde_json::json;
-use tokio::sync::{broadcast, mpsc, Mutex};
+use std::collections::HashMap;
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
 
 /// Unique identifier for an Agent.
 pub type AgentId = String;
@@ -32,12 +90,74 @@ pub type AgentId = String;
 pub struct AgentConfig {
     /// The unique name or identifier for the agent.
     pub name: String,
-    /// List of capabilities this agent supports.
+    /// Task types this agent advertises to peers.
+    ///
+    /// Built-in executors run tasks of their types locally whether or not they
+    /// are listed, but peers only dispatch the types listed here, those of
+    /// executors registered at runtime and allow-listed subprocess commands.
+    /// Listed `Custom` types without an executor of their own are served by the
+    /// simulation executor.
     pub capabilities: Vec<TaskType>,
     /// List of AI models this agent has available.
     #[serde(default)]
     pub models: Vec<String>,
-    // Add other config fields if necessary, currently minimal for compilation
+    /// Maximum number of tasks executed concurrently by this agent.
+    #[serde(default = "default_max_concurrent_tasks")]
+    pub max_concurrent_tasks: usize,
+    /// Per-task-type concurrency limits (e.g. one `AiInference` at a time).
+    #[serde(default)]
+    pub task_type_limits: Vec<ConcurrencyLimit>,
+    /// Strategy used to pick the peer a task is dispatched to.
+    #[serde(default)]
+    pub peer_selection: PeerSelectionStrategy,
+    /// CPU and memory limits above which incoming task requests are rejected.
+    /// Without limits every request is accepted.
+    #[serde(default)]
+    pub resource_limits: Option<ResourceLimits>,
+    /// Bidding rounds run before dispatching a task, and this agent's own bids.
+    #[serde(default)]
+    pub bidding: BiddingConfig,
+    /// Task quotas of peers sending task requests, by reputation tier.
+    #[serde(default)]
+    pub quotas: QuotaConfig,
+    /// Limits and module cache of the sandboxed WASM executor.
+    #[serde(default)]
+    pub wasm: WasmConfig,
+    /// Allow-listed local commands, each served as a `Custom` task type.
+    #[serde(default)]
+    pub subprocess: SubprocessConfig,
+    /// Whether checkpoints of remotely requested tasks are pushed to the
+    /// requester, so a retry elsewhere can resume from them. Checkpoints are
+    /// always kept locally.
+    #[serde(default = "default_push_checkpoints")]
+    pub push_checkpoints: bool,
+}
+
+fn default_max_concurrent_tasks() -> usize {
+    DEFAULT_MAX_CONCURRENT_TASKS
+}
+
+fn default_push_checkpoints() -> bool {
+    true
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            capabilities: vec![],
+            models: vec![],
+            max_concurrent_tasks: DEFAULT_MAX_CONCURRENT_TASKS,
+            task_type_limits: vec![],
+            peer_selection: PeerSelectionStrategy::default(),
+            resource_limits: None,
+            bidding: BiddingConfig::default(),
+            quotas: QuotaConfig::default(),
+            wasm: WasmConfig::default(),
+            subprocess: SubprocessConfig::default(),
+            push_checkpoints: true,
+        }
+    }
 }
 
 /// Core Agent structure representing a node in the network.
@@ -52,6 +172,24 @@ pub struct Agent {
     pub executor_registry: ExecutorRegistry,
     /// The agent's AI model manager.
     pub model_manager: Arc<ModelManager>,
+    /// Worker pool bounding concurrent local task execution.
+    pub worker_pool: WorkerPool,
+    /// Reputation scores of peers, used when selecting dispatch targets.
+    pub reputation_manager: Arc<RwLock<ReputationManager>>,
+    /// Strategy picking the peer a task is dispatched to.
+    peer_selector: Arc<dyn PeerSelector>,
+    /// Vote tallies of tasks dispatched redundantly to several peers.
+    votes: Mutex<HashMap<TaskId, VoteTally>>,
+    /// Open bidding rounds of tasks offered to peers.
+    bid_rounds: Mutex<HashMap<TaskId, BidRound>>,
+    /// Peers that rejected a task, with the time until which they get no tasks.
+    busy_peers: Mutex<HashMap<String, std::time::Instant>>,
+    /// Splitters used to shard tasks, by task type.
+    task_splitters: HashMap<TaskType, Arc<dyn TaskSplitter>>,
+    /// Monitor gating incoming task requests on `AgentConfig::resource_limits`.
+    resource_monitor: Option<ResourceMonitor>,
+    /// Usage of the task quotas of peers sending task requests.
+    requester_quotas: Mutex<RequesterQuotas>,
     /// Network manager (protected by mutex for mutable access during start/stop).
     pub network_manager: Arc<Mutex<NetworkManager>>,
     /// Shutdown signal sender.
@@ -71,6 +209,9 @@ impl Agent {
     }
 
     /// Creates a new Agent instance.
+    ///
+    /// Tasks submitted to `task_manager` are validated against the input
+    /// schemas of the agent's executors.
     pub fn new(
         identity: AgentIdentity,
         config: AgentConfig,
@@ -81,18 +222,52 @@ impl Agent {
         let (shutdown_tx, _) = broadcast::channel(1);
         let network_manager = NetworkManager::new(network_config);
 
-        let executor_registry = ExecutorRegistry::new();
+        let executor_registry = default_executors(&config, &model_manager);
+        let worker_pool = WorkerPool::new(config.max_concurrent_tasks, &config.task_type_limits);
+        let peer_selector = config.peer_selection.selector();
+        let requester_quotas = RequesterQuotas::new(config.quotas.clone());
+        let mut task_splitters: HashMap<TaskType, Arc<dyn TaskSplitter>> = HashMap::new();
+        task_splitters.insert(TaskType::TextProcessing, Arc::new(CorpusSplitter));
+        let resource_monitor = config
+            .resource_limits
+            .as_ref()
+            .and_then(|limits| ResourceMonitor::new(limits).ok());
         Self {
             identity,
             config,
-            task_manager,
+            task_manager: task_manager.with_payload_validation(executor_registry.clone()),
             executor_registry,
             model_manager,
+            worker_pool,
+            reputation_manager: Arc::new(RwLock::new(ReputationManager::new())),
+            peer_selector,
+            votes: Mutex::new(HashMap::new()),
+            bid_rounds: Mutex::new(HashMap::new()),
+            busy_peers: Mutex::new(HashMap::new()),
+            task_splitters,
+            resource_monitor,
+            requester_quotas: Mutex::new(requester_quotas),
             network_manager: Arc::new(Mutex::new(network_manager)),
             shutdown_tx,
         }
     }
 
+    /// Replaces the peer selection strategy with a custom implementation.
+    pub fn with_peer_selector(mut self, selector: Arc<dyn PeerSelector>) -> Self {
+        self.peer_selector = selector;
+        self
+    }
+
+    /// Registers the splitter used to shard tasks of `task_type`.
+    pub fn with_task_splitter(
+        mut self,
+        task_type: TaskType,
+        splitter: Arc<dyn TaskSplitter>,
+    ) -> Self {
+        self.task_splitters.insert(task_type, splitter);
+        self
+    }
+
     /// Returns the Agent's ID.
     pub fn id(&self) -> AgentId {
         // Fallback to name for now, should be DID
@@ -140,7 +315,8 @@ impl Agent {
 
         for task in tasks {
             // Only check tasks that are running and assigned to remote peers
-            if task.status == TaskStatus::Running && task.assigned_to.is_some() {
+            let remote = task.assigned_to.is_some() || !task.replicas.is_empty();
+            if task.status == TaskStatus::Running && remote {
                 // Determine timeout duration
                 let timeout_ms = if let Some(payload) = &task.payload {
                     payload
@@ -155,6 +331,11 @@ impl Agent {
                 if let Some(started_at) = task.started_at {
                     if let Ok(duration) = now.duration_since(started_at) {
                         if duration.as_millis() as u64 > timeout_ms {
+                            // Redundant tasks are decided by vote instead of retried
+                            if !task.replicas.is_empty() {
+                                self.time_out_replicas(task.id).await?;
+                                continue;
+                            }
                             println!(
                                 "Task {} timed out after {}ms (limit {}ms)",
                                 task.id,
@@ -184,6 +365,10 @@ impl Agent {
     }
 
     /// Retries a task by finding another capable peer.
+    ///
+    /// The task is sent with its latest checkpoint, if the peer that ran it
+    /// saved one, so the new peer resumes from there instead of starting over.
+    /// A queued or running task that has used up its retries fails.
     pub async fn retry_task(&self, task_id: TaskId) -> anyhow::Result<()> {
         let task = self
             .task_manager
@@ -192,10 +377,23 @@ impl Agent {
             .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
 
         if task.retry_count >= task.max_retries {
-            println!(
+            tracing::warn!(
                 "Task {} exceeded max retries ({})",
-                task_id, task.max_retries
+                task_id,
+                task.max_retries
             );
+            // Nobody runs the task any more, so it would otherwise never finish
+            if matches!(task.status, TaskStatus::Queued | TaskStatus::Running) {
+                self.task_manager
+                    .update_status(
+                        task_id,
+                        TaskStatus::Failed(format!("Exceeded max retries ({})", task.max_retries)),
+                    )
+                    .await?;
+            }
+            if let Some(parent) = task.shard_of {
+                self.settle_sharded_task(parent).await?;
+            }
             return Ok(());
         }
 
@@ -204,41 +402,40 @@ impl Agent {
         self.task_manager
             .update_retry_count(task_id, new_count)
             .await?;
-        println!(
-            "Retrying task {} (attempt {}/{})",
-            task_id, new_count, task.max_retries
-        );
+        match &task.checkpoint {
+            Some(checkpoint) => tracing::info!(
+                "Retrying task {} (attempt {}/{}) from checkpoint {}",
+                task_id,
+                new_count,
+                task.max_retries,
+                checkpoint.sequence
+            ),
+            None => tracing::info!(
+                "Retrying task {} (attempt {}/{})",
+                task_id,
+                new_count,
+                task.max_retries
+            ),
+        }
 
         // Reset status to Queued so we can dispatch it again
         self.task_manager
             .update_status(task_id, TaskStatus::Queued)
             .await?;
 
-        // We need to re-dispatch. We can try to use dispatch_task again.
-        // However, dispatch_task finds *any* peer. We might want to avoid the *failed* peer.
-        // For now, let's keep it simple and just re-dispatch. The load balancing (random/first)
-        // logic might pick the same one if it's the only one, but if there are others, it might pick another.
-        // Ideally, we should exclude the failed peer.
+        // Never send the retry back to the peer that just failed it
+        if let Some(peer) = &task.assigned_to {
+            self.task_manager
+                .exclude_peer(task_id, peer.clone())
+                .await?;
+        }
 
         // Note: dispatch_task expects the task to be in the manager. We just updated it.
-        // We spawn this because we might be in the check loop.
-
-        // We can't clone &Self to Arc<Self> unless we are already Arc.
-        // Actually, `check_task_timeouts` is called on &Self.
-        // But `Agent` struct is usually wrapped in Arc.
-        // `retry_task` is async, so we can just call `dispatch_task`.
-
-        // But wait, `dispatch_task` is async and we are in `check_task_timeouts` loop.
-        // If we await here, we block the check loop. That's probably fine as retries are rare events.
-
-        // IMPORTANT: We should ideally blacklist the failed peer for this specific retry.
-        // But `dispatch_task` doesn't support exclusions yet.
-        // Implementing simple retry for now.
-
+        // If we await here, we block the timeout check loop. That's fine as retries are rare events.
         match self.dispatch_task(task_id).await {
-            Ok(_) => println!("Task {} re-dispatched successfully", task_id),
+            Ok(_) => tracing::info!("Task {} re-dispatched successfully", task_id),
             Err(e) => {
-                eprintln!("Failed to re-dispatch task {}: {}", task_id, e);
+                tracing::warn!("Failed to re-dispatch task {}: {}", task_id, e);
                 // If dispatch fails (e.g. no peers), mark as Failed?
                 // Or leave as Queued?
                 // If we leave as Queued, `process_next_task` might pick it up if it was local,
@@ -247,30 +444,96 @@ impl Agent {
                 self.task_manager
                     .update_status(task_id, TaskStatus::Failed(format!("Retry failed: {}", e)))
                     .await?;
+                if let Some(parent) = task.shard_of {
+                    self.settle_sharded_task(parent).await?;
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Sends a task `peer` rejected to another peer.
+    ///
+    /// A rejection is an admission decision, not a failed run, so it does not use
+    /// up one of the task's retries. `peer` gets no tasks until `retry_after` has
+    /// passed; the task fails if no other peer can take it.
+    async fn reroute_rejected_task(
+        &self,
+        task_id: TaskId,
+        peer: &str,
+        retry_after: std::time::Duration,
+    ) -> anyhow::Result<()> {
+        self.busy_peers
+            .lock()
+            .await
+            .insert(peer.to_string(), std::time::Instant::now() + retry_after);
+
+        self.task_manager
+            .update_status(task_id, TaskStatus::Queued)
+            .await?;
+        if let Err(e) = self.dispatch_task(task_id).await {
+            tracing::warn!("Failed to re-dispatch rejected task {}: {}", task_id, e);
+            self.task_manager
+                .update_status(
+                    task_id,
+                    TaskStatus::Failed(format!("No peer accepted the task: {}", e)),
+                )
+                .await?;
+            let task = self.task_manager.get_task(task_id).await;
+            if let Some(parent) = task.and_then(|task| task.shard_of) {
+                self.settle_sharded_task(parent).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads persisted tasks and settles the ones a crash interrupted (see
+    /// [`TaskManager::recover`]).
+    ///
+    /// Redundant tasks still running remotely get an empty vote tally again, so
+    /// the responses of their replicas are voted on rather than taken as the
+    /// result. Votes cast before the restart are collected again by asking the
+    /// replicas for the task's status.
+    pub async fn recover(&self) -> anyhow::Result<Recovery> {
+        let recovery = self.task_manager.recover().await?;
+        let mut votes = self.votes.lock().await;
+        for &task_id in &recovery.remote {
+            let Some(task) = self.task_manager.get_task(task_id).await else {
+                continue;
+            };
+            if let Some(redundancy) = task.redundancy.filter(|_| !task.replicas.is_empty()) {
+                votes.insert(task_id, VoteTally::new(redundancy, task.replicas));
+            }
+        }
+        Ok(recovery)
+    }
+
     /// Starts the Agent.
     pub async fn start(self: Arc<Self>) -> anyhow::Result<()> {
         let _shutdown_rx = self.shutdown_tx.subscribe();
 
-        // Load persisted tasks
-        match self.task_manager.load_tasks().await {
-            Ok(count) => {
-                if count > 0 {
-                    println!("Recovered {} tasks from storage", count);
-                    tracing::info!("Recovered {} tasks from storage", count);
+        // Load persisted tasks and settle the ones interrupted by a crash
+        let remote_tasks = match self.recover().await {
+            Ok(recovery) => {
+                if recovery.loaded > 0 {
+                    tracing::info!(
+                        "Recovered {} tasks from storage ({} requeued, {} failed, {} remote)",
+                        recovery.loaded,
+                        recovery.requeued.len(),
+                        recovery.failed.len(),
+                        recovery.remote.len()
+                    );
                 }
+                recovery.remote
             }
             Err(e) => {
                 eprintln!("Failed to load tasks from storage: {}", e);
                 tracing::error!("Failed to load tasks from storage: {}", e);
                 // Continue startup even if load fails
+                Vec::new()
             }
-        }
+        };
 
         let agent_clone = self.clone();
         let mut shutdown_rx = self.shutdown_tx.subscribe();
@@ -279,20 +542,7 @@ impl Agent {
         {
             let mut nm = self.network_manager.lock().await;
 
-            // Construct agent version string with capabilities
-            // e.g. "/p2p-ai-agents/1.0.0/TextProcessor,VectorComputer"
-            let mut version = "/p2p-ai-agents/1.0.0".to_string();
-            if !self.config.capabilities.is_empty() {
-                version.push('/');
-                let caps: Vec<String> = self
-                    .config
-                    .capabilities
-                    .iter()
-                    .map(|c| c.to_string())
-                    .collect();
-                version.push_str(&caps.join(","));
-            }
-            nm.set_agent_version(version);
+            nm.set_agent_version(self.agent_version());
 
             // Channel for network messages -> agent
             let (tx, mut rx) = mpsc::channel::<Vec<u8>>(100);
@@ -307,21 +557,22 @@ impl Agent {
             // We spawn a task to do this shortly after startup to ensure peers are connected
             let agent_announce = self.clone();
             tokio::spawn(async move {
-                if !agent_announce.config.capabilities.is_empty() {
+                // Wait a bit for initial connections
+                tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+                agent_announce.announce_capabilities().await;
+            });
+
+            // Ask peers about tasks they were running for us before the restart
+            if !remote_tasks.is_empty() {
+                let agent_recovery = self.clone();
+                tokio::spawn(async move {
                     // Wait a bit for initial connections
                     tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-                    let msg = Message::new_capability_announcement(
-                        agent_announce.id(),
-                        agent_announce.config.capabilities.clone(),
-                        agent_announce.config.models.clone(),
-                    );
-                    if let Err(e) = agent_announce.broadcast_message(msg).await {
-                        tracing::error!("Failed to broadcast capability announcement: {:?}", e);
-                    } else {
-                        tracing::info!("Broadcasted capability announcement");
+                    for task_id in remote_tasks {
+                        agent_recovery.query_task_status(task_id).await;
                     }
-                }
-            });
+                });
+            }
 
             // Spawn message handler loop
             let agent_msg_clone = self.clone();
@@ -350,24 +601,68 @@ impl Agent {
             });
         }
 
-        // Spawn background task processing loop
+        // Prune finished tasks according to the retention policy
+        if let Some(interval) = self.task_manager.retention().map(|p| p.interval) {
+            let agent_compact = self.clone();
+            let mut compact_shutdown_rx = self.shutdown_tx.subscribe();
+            tokio::spawn(async move {
+                let mut compaction = tokio::time::interval(interval);
+                loop {
+                    tokio::select! {
+                        _ = compact_shutdown_rx.recv() => break,
+                        _ = compaction.tick() => {
+                            match agent_compact.task_manager.compact().await {
+                                Ok(report) if report.pruned > 0 => {
+                                    tracing::info!("Pruned {} finished tasks", report.pruned);
+                                }
+                                Ok(_) => {}
+                                Err(e) => tracing::error!("Failed to compact tasks: {}", e),
+                            }
+                        }
+                    }
+                }
+            });
+        }
+
+        // Spawn background task processing loop.
+        // The loop sleeps until a task is queued or a worker frees its slot,
+        // and only wakes periodically to check remote task timeouts and
+        // release scheduled tasks.
+        let queue_notify = self.task_manager.queue_notifier();
         tokio::spawn(async move {
+            let mut housekeeping =
+                tokio::time::interval(std::time::Duration::from_millis(HOUSEKEEPING_INTERVAL_MS));
             loop {
+                // Start as many queued tasks as the worker pool allows
+                loop {
+                    match agent_clone.process_next_task().await {
+                        Ok(Some(_)) => continue,
+                        Ok(None) => break,
+                        Err(e) => {
+                            tracing::error!("Error processing task: {:?}", e);
+                            break;
+                        }
+                    }
+                }
+
                 tokio::select! {
                     _ = shutdown_rx.recv() => {
                         // Received shutdown signal
                         break;
                     }
-                    _ = tokio::time::sleep(std::time::Duration::from_millis(50)) => {
-                        // Poll for tasks
-                        if let Err(e) = agent_clone.process_next_task().await {
-                             eprintln!("Error processing task: {:?}", e);
-                        }
-
+                    _ = queue_notify.notified() => {}
+                    _ = housekeeping.tick() => {
                         // Check for task timeouts
                         if let Err(e) = agent_clone.check_task_timeouts().await {
                              eprintln!("Error checking timeouts: {:?}", e);
                         }
+                        // Queue delayed and recurring tasks that came due
+                        agent_clone.task_manager.release_due_tasks().await;
+                        agent_clone
+                            .requester_quotas
+                            .lock()
+                            .await
+                            .prune(std::time::Instant::now());
                     }
                 }
             }
@@ -419,14 +714,77 @@ impl Agent {
         self.send_network_message(message).await
     }
 
+    /// Returns the task types this agent advertises to peers (see
+    /// [`AgentConfig::capabilities`]).
+    pub fn capabilities(&self) -> Vec<TaskType> {
+        self.executor_registry.advertised()
+    }
+
+    /// Returns the Identify agent version string, listing the capabilities,
+    /// e.g. "/p2p-ai-agents/1.0.0/TextProcessing,VectorComputation".
+    fn agent_version(&self) -> String {
+        let mut version = "/p2p-ai-agents/1.0.0".to_string();
+        let capabilities = self.capabilities();
+        if !capabilities.is_empty() {
+            version.push('/');
+            let caps: Vec<String> = capabilities.iter().map(|c| c.to_string()).collect();
+            version.push_str(&caps.join(","));
+        }
+        version
+    }
+
+    /// Broadcasts the capabilities and models of this agent, if it has any.
+    async fn announce_capabilities(&self) {
+        let capabilities = self.capabilities();
+        if capabilities.is_empty() {
+            return;
+        }
+        let msg = Message::new_capability_announcement(
+            self.id(),
+            capabilities,
+            self.config.models.clone(),
+            self.executor_registry.schemas(),
+        );
+        if let Err(e) = self.broadcast_message(msg).await {
+            tracing::error!("Failed to broadcast capability announcement: {:?}", e);
+        } else {
+            tracing::info!("Broadcasted capability announcement");
+        }
+    }
+
+    /// Registers an executor for a task type at runtime and announces the
+    /// updated capabilities to peers.
+    ///
+    /// The Identify agent version string keeps the capabilities the agent
+    /// started with.
+    pub async fn register_executor<E>(&self, task_type: TaskType, executor: E)
+    where
+        E: TaskExecutor + 'static,
+    {
+        self.executor_registry.register(task_type, executor);
+        self.announce_capabilities().await;
+    }
+
     /// Dispatches a task to a capable peer.
     pub async fn dispatch_task(&self, task_id: TaskId) -> anyhow::Result<()> {
-        let task = self
+        let mut task = self
             .task_manager
             .get_task(task_id)
             .await
             .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
 
+        if task.is_scheduled(std::time::SystemTime::now()) {
+            return Err(anyhow::anyhow!("Task {} is scheduled for later", task_id));
+        }
+
+        // Dependent tasks are only dispatched once their parents completed
+        if !self.task_manager.dependencies_satisfied(task_id).await {
+            return Err(anyhow::anyhow!("Task {} has unmet dependencies", task_id));
+        }
+        // Parent outputs are already resolved into the payload, and the parents
+        // themselves are unknown to the remote peer
+        task.depends_on.clear();
+
         // 1. Identify TaskType and Model Requirement
         let (task_type, required_model) = if let Some(payload) = &task.payload {
             let model = match payload.task_type {
@@ -451,20 +809,36 @@ impl Agent {
             return Err(anyhow::anyhow!("Task has no payload to determine type"));
         };
 
-        // 2. Find Peers
-        let peers = self
-            .find_peers_with_capability(task_type.clone(), required_model)
+        // 2. Find Peers (skipping peers that already failed this task)
+        let candidates = self
+            .dispatch_candidates(&task, task_type.clone(), required_model)
             .await;
-        if peers.is_empty() {
+        if candidates.is_empty() {
             return Err(anyhow::anyhow!(
-                "No peers found with capability {} (model: {:?})",
+                "No peers found with capability {} (model: {:?}, excluded: {:?})",
                 task_type,
-                required_model
+                required_model,
+                task.excluded_peers
             ));
         }
 
-        // 3. Select Peer (Simple: First one)
-        let target_peer = peers[0].clone();
+        // Sharded tasks are split up, with every shard going to its own peer
+        if let Some(sharding) = task.sharding.clone() {
+            return self.dispatch_sharded(task, sharding, candidates).await;
+        }
+
+        // Redundant tasks go to several peers at once and are decided by vote
+        if let Some(redundancy) = task.redundancy.clone() {
+            return self.dispatch_redundant(task, redundancy, candidates).await;
+        }
+
+        // 3. Select Peer, by bidding if enabled
+        let winner = self
+            .collect_bids(&task, task_type, required_model, &candidates)
+            .await;
+        let target_peer = winner
+            .or_else(|| self.peer_selector.select(&task, &candidates))
+            .ok_or_else(|| anyhow::anyhow!("No suitable peer selected for task {}", task_id))?;
         println!("Dispatching task {} to peer {}", task_id, target_peer);
 
         // 4. Update Task with Assignment
@@ -472,10 +846,297 @@ impl Agent {
             .assign_task(task_id, target_peer.to_string())
             .await?;
 
-        // 5. Construct Message
+        // 5. Sign and Send
+        self.send_task_request(target_peer.clone(), task).await?;
+        self.publish_dispatched(task_id, vec![target_peer.to_string()])
+            .await;
+        Ok(())
+    }
+
+    /// Offers a task to `candidates` and returns the one with the best bid.
+    ///
+    /// Returns `None` if bidding is disabled or no candidate bid before the
+    /// deadline.
+    async fn collect_bids(
+        &self,
+        task: &Task,
+        task_type: TaskType,
+        required_model: Option<&str>,
+        candidates: &[PeerCandidate],
+    ) -> Option<NetworkPeerId> {
+        if !self.config.bidding.enabled {
+            return None;
+        }
+        let round = BidRound::new(candidates.iter().map(|c| c.peer_id.to_string()));
+        let complete = round.completion();
+        self.bid_rounds.lock().await.insert(task.id, round);
+
+        let offer = Message::new_task_offer(
+            self.id(),
+            "broadcast",
+            task.id,
+            task_type,
+            required_model.map(str::to_string),
+        );
+        if let Err(e) = self.broadcast_message(offer).await {
+            tracing::warn!("Failed to offer task {} ({}), skipping bids", task.id, e);
+        } else {
+            let _ = tokio::time::timeout(self.config.bidding.deadline, complete.notified()).await;
+        }
+
+        let round = self.bid_rounds.lock().await.remove(&task.id)?;
+        let winner = round.winner()?;
+        candidates
+            .iter()
+            .find(|c| c.peer_id.to_string() == winner)
+            .map(|c| c.peer_id.clone())
+    }
+
+    /// Publishes that a task was sent to `peers`.
+    async fn publish_dispatched(&self, task_id: TaskId, peers: Vec<String>) {
+        let dispatch = TaskDispatch { task_id, peers };
+        let _ = self
+            .event_bus()
+            .publish(TaskDispatched::new(dispatch, Some(self.id())))
+            .await;
+    }
+
+    /// Returns the bus task lifecycle events are published on.
+    pub fn event_bus(&self) -> &EventBus {
+        self.task_manager.event_bus()
+    }
+
+    /// Sends a task to `redundancy.replicas` distinct peers and registers a vote
+    /// tally that decides the result from their responses.
+    async fn dispatch_redundant(
+        &self,
+        task: Task,
+        redundancy: Redundancy,
+        mut candidates: Vec<PeerCandidate>,
+    ) -> anyhow::Result<()> {
+        redundancy.validate()?;
+
+        let mut selected: Vec<NetworkPeerId> = Vec::new();
+        while selected.len() < redundancy.replicas {
+            let Some(peer) = self.peer_selector.select(&task, &candidates) else {
+                break;
+            };
+            if selected.contains(&peer) {
+                break;
+            }
+            candidates.retain(|c| c.peer_id != peer);
+            selected.push(peer);
+        }
+        if selected.len() < redundancy.quorum {
+            return Err(anyhow::anyhow!(
+                "Only {} peers available for task {}, quorum needs {}",
+                selected.len(),
+                task.id,
+                redundancy.quorum
+            ));
+        }
+
+        let peer_ids: Vec<String> = selected.iter().map(|p| p.to_string()).collect();
+        tracing::info!(
+            "Dispatching task {} to {} peers ({} must agree): {:?}",
+            task.id,
+            peer_ids.len(),
+            redundancy.quorum,
+            peer_ids
+        );
+        self.task_manager
+            .set_replicas(task.id, peer_ids.clone())
+            .await?;
+        self.votes
+            .lock()
+            .await
+            .insert(task.id, VoteTally::new(redundancy, peer_ids));
+        // Timed out like other remote tasks, see `check_task_timeouts`
+        self.task_manager
+            .update_status(task.id, TaskStatus::Running)
+            .await?;
+
+        let mut sent = Vec::new();
+        for peer in selected {
+            match self.send_task_request(peer.clone(), task.clone()).await {
+                Ok(()) => sent.push(peer.to_string()),
+                Err(e) => tracing::warn!("Failed to send task {} to {}: {}", task.id, peer, e),
+            }
+        }
+        if !sent.is_empty() {
+            self.publish_dispatched(task.id, sent).await;
+        }
+        Ok(())
+    }
+
+    /// Splits a task into shard tasks and dispatches each to a different peer.
+    ///
+    /// The task itself stays `Running` until its shards are reduced by
+    /// `settle_sharded_task`.
+    async fn dispatch_sharded(
+        &self,
+        task: Task,
+        sharding: Sharding,
+        mut candidates: Vec<PeerCandidate>,
+    ) -> anyhow::Result<()> {
+        let payload = task
+            .payload
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Task has no payload to shard"))?;
+        let splitter = self
+            .task_splitters
+            .get(&payload.task_type)
+            .ok_or_else(|| anyhow::anyhow!("No splitter registered for {}", payload.task_type))?;
+        let shard_payloads = splitter.split(payload, sharding.max_shards.min(candidates.len()))?;
+
+        // Pick a distinct peer for every shard before creating any shard task
+        let mut assignments = Vec::with_capacity(shard_payloads.len());
+        for (index, shard_payload) in shard_payloads.into_iter().enumerate() {
+            let mut shard = Task::with_payload(task.priority.clone(), shard_payload);
+            shard.description = format!("Shard {} of task {}", index + 1, task.id);
+            shard.max_retries = task.max_retries;
+            shard.shard_of = Some(task.id);
+
+            let peer = self
+                .peer_selector
+                .select(&shard, &candidates)
+                .ok_or_else(|| anyhow::anyhow!("No peer selected for shard {}", index + 1))?;
+            candidates.retain(|c| c.peer_id != peer);
+            assignments.push((peer, shard));
+        }
+
+        tracing::info!(
+            "Dispatching task {} as {} shards",
+            task.id,
+            assignments.len()
+        );
+        self.task_manager
+            .set_shards(task.id, assignments.iter().map(|(_, s)| s.id).collect())
+            .await?;
+        self.task_manager
+            .update_status(task.id, TaskStatus::Running)
+            .await?;
+
+        for (peer, shard) in assignments {
+            let shard_id = self.task_manager.add_task(shard.clone()).await;
+            self.task_manager
+                .assign_task(shard_id, peer.to_string())
+                .await?;
+            match self.send_task_request(peer.clone(), shard).await {
+                Ok(()) => {
+                    self.publish_dispatched(shard_id, vec![peer.to_string()])
+                        .await
+                }
+                Err(e) => tracing::warn!("Failed to send shard {} to {}: {}", shard_id, peer, e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles a status change of a shard task.
+    ///
+    /// A failed or timed out shard is retried on its own on another peer; any
+    /// other change may settle the sharded task.
+    async fn on_shard_update(&self, shard_id: TaskId) -> anyhow::Result<()> {
+        let Some(shard) = self.task_manager.get_task(shard_id).await else {
+            return Ok(());
+        };
+        let Some(parent) = shard.shard_of else {
+            return Ok(());
+        };
+        if matches!(shard.status, TaskStatus::Failed(_) | TaskStatus::Timeout)
+            && shard.retry_count < shard.max_retries
+        {
+            return self.retry_task(shard_id).await;
+        }
+        self.settle_sharded_task(parent).await
+    }
+
+    /// Reduces the shard results into a sharded task once every shard completed.
+    ///
+    /// If a shard finally failed, the remaining shards are cancelled and the task
+    /// fails.
+    async fn settle_sharded_task(&self, id: TaskId) -> anyhow::Result<()> {
+        let Some(task) = self.task_manager.get_task(id).await else {
+            return Ok(());
+        };
+        if task.status != TaskStatus::Running {
+            return Ok(());
+        }
+
+        let mut results = Vec::with_capacity(task.shards.len());
+        let mut failure = None;
+        for shard_id in &task.shards {
+            let status = match self.task_manager.get_task(*shard_id).await {
+                Some(shard) => shard.status,
+                None => TaskStatus::Failed("shard not found".to_string()),
+            };
+            match status {
+                TaskStatus::Completed(result) => results.push(result),
+                TaskStatus::Queued | TaskStatus::Running => {}
+                TaskStatus::Failed(reason) => {
+                    failure = Some(format!("Shard {} failed: {}", shard_id, reason));
+                    break;
+                }
+                TaskStatus::Cancelled => {
+                    failure = Some(format!("Shard {} was cancelled", shard_id));
+                    break;
+                }
+                TaskStatus::Timeout => {
+                    failure = Some(format!("Shard {} timed out", shard_id));
+                    break;
+                }
+            }
+        }
+
+        let status = if let Some(reason) = failure {
+            self.cancel_shards(&task).await;
+            TaskStatus::Failed(reason)
+        } else if results.len() == task.shards.len() {
+            let payload = task
+                .payload
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Task has no payload to reduce"))?;
+            let reduced = match self.task_splitters.get(&payload.task_type) {
+                Some(splitter) => splitter.reduce(payload, results),
+                None => Err(anyhow::anyhow!(
+                    "No splitter registered for {}",
+                    payload.task_type
+                )),
+            };
+            match reduced {
+                Ok(result) => TaskStatus::Completed(result),
+                Err(e) => TaskStatus::Failed(format!("Failed to reduce shard results: {}", e)),
+            }
+        } else {
+            return Ok(());
+        };
+
+        tracing::info!("Sharded task {} settled: {:?}", id, status);
+        self.task_manager.update_status(id, status).await
+    }
+
+    /// Cancels the unfinished shards of a sharded task.
+    async fn cancel_shards(&self, task: &Task) {
+        for shard_id in &task.shards {
+            let Some(shard) = self.task_manager.get_task(*shard_id).await else {
+                continue;
+            };
+            if matches!(shard.status, TaskStatus::Queued | TaskStatus::Running) {
+                self.cancel_remote(&shard).await;
+                let _ = self.task_manager.cancel_task(*shard_id).await;
+            }
+        }
+    }
+
+    /// Sends a signed task request to a peer, falling back to broadcast.
+    async fn send_task_request(
+        &self,
+        target_peer: NetworkPeerId,
+        task: Task,
+    ) -> anyhow::Result<()> {
         let message = Message::new_task_request(self.id(), target_peer.clone(), task.clone());
 
-        // 6. Sign and Send
         if let Err(e) = self.send_network_message(message).await {
             // Fallback to broadcast if direct send fails (e.g. invalid peer ID format for libp2p)
             // This is a robustness feature to keep tests passing while we transition.
@@ -505,182 +1166,452 @@ impl Agent {
         Ok(())
     }
 
+    /// Asks the peers a task is assigned to for its current status.
+    ///
+    /// Peers answer with a `TaskResponse`, or with `TaskUnknown` if they lost the
+    /// task, in which case it is retried elsewhere.
+    pub async fn query_task_status(&self, task_id: TaskId) {
+        let Some(task) = self.task_manager.get_task(task_id).await else {
+            return;
+        };
+        let mut peers = task.replicas.clone();
+        if let Some(peer) = task.assigned_to {
+            if !peers.contains(&peer) {
+                peers.push(peer);
+            }
+        }
+
+        for peer in peers {
+            let message = Message::new_task_status_query(self.id(), peer.clone(), task_id);
+            if let Err(e) = self.send_network_message(message.clone()).await {
+                tracing::warn!("Direct send failed ({}), falling back to broadcast", e);
+                sign_and_broadcast(&self.identity, &self.network_manager, message).await;
+            }
+        }
+    }
+
+    /// Feeds a peer's response for a redundantly executed task into its vote tally.
+    ///
+    /// Returns false if the task is not being voted on.
+    async fn record_vote(
+        &self,
+        task_id: TaskId,
+        peer: &str,
+        status: &TaskStatus,
+    ) -> anyhow::Result<bool> {
+        let outcome = {
+            let mut votes = self.votes.lock().await;
+            let Some(tally) = votes.get_mut(&task_id) else {
+                return Ok(false);
+            };
+            let outcome = tally.record(peer, status);
+            // Kept until every peer answered so late responses still count
+            if tally.is_complete() {
+                votes.remove(&task_id);
+            }
+            outcome
+        };
+
+        match outcome {
+            VoteOutcome::Pending => {}
+            VoteOutcome::Accepted {
+                result,
+                agreeing,
+                disagreeing,
+            } => {
+                tracing::info!(
+                    "Task {} accepted by {:?}, disagreeing peers: {:?}",
+                    task_id,
+                    agreeing,
+                    disagreeing
+                );
+                self.adjust_reputation(&agreeing, AGREEMENT_REWARD).await;
+                self.adjust_reputation(&disagreeing, -DISAGREEMENT_PENALTY)
+                    .await;
+                self.task_manager
+                    .update_status(task_id, TaskStatus::Completed(result))
+                    .await?;
+            }
+            VoteOutcome::NoQuorum(reason) => {
+                self.task_manager
+                    .update_status(task_id, TaskStatus::Failed(reason))
+                    .await?;
+            }
+            VoteOutcome::Late { peer, agrees } => {
+                let delta = if agrees {
+                    AGREEMENT_REWARD
+                } else {
+                    -DISAGREEMENT_PENALTY
+                };
+                self.adjust_reputation(&[peer], delta).await;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Counts the replicas of a redundant task that have not answered as failed
+    /// votes, so its tally is decided.
+    async fn time_out_replicas(&self, task_id: TaskId) -> anyhow::Result<()> {
+        let outstanding = self
+            .votes
+            .lock()
+            .await
+            .get(&task_id)
+            .map(VoteTally::outstanding);
+        let Some(outstanding) = outstanding else {
+            // Nothing left to wait for
+            self.task_manager
+                .update_status(task_id, TaskStatus::Timeout)
+                .await?;
+            return Ok(());
+        };
+        for peer in outstanding {
+            tracing::warn!("Replica {} of task {} timed out", peer, task_id);
+            self.record_vote(task_id, &peer, &TaskStatus::Timeout)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Changes the reputation of peers, registering unknown peers first.
+    async fn adjust_reputation(&self, peers: &[String], delta: i32) {
+        let mut reputation = self.reputation_manager.write().await;
+        for peer in peers {
+            if reputation.get_score(peer).is_err() {
+                reputation.register_agent(peer.clone());
+            }
+            let _ = if delta >= 0 {
+                reputation.increase_reputation(peer, delta)
+            } else {
+                reputation.decrease_reputation(peer, -delta)
+            };
+        }
+    }
+
+    /// Collects the peers a task may be dispatched to, with their selection data.
+    ///
+    /// Peers in the task's exclusion list are skipped, as are peers that rejected
+    /// a task until the time they asked to be retried after.
+    pub async fn dispatch_candidates(
+        &self,
+        task: &Task,
+        task_type: TaskType,
+        required_model: Option<&str>,
+    ) -> Vec<PeerCandidate> {
+        let peers = self
+            .find_peers_with_capability(task_type, required_model)
+            .await;
+        let load = self.task_manager.active_assignments().await;
+        let mut busy = self.busy_peers.lock().await;
+        let now = std::time::Instant::now();
+        busy.retain(|_, until| *until > now);
+        let reputation = self.reputation_manager.read().await;
+        let network = self.network_manager.lock().await;
+
+        let mut candidates = Vec::new();
+        for peer_id in peers {
+            let id = peer_id.to_string();
+            if task.excluded_peers.contains(&id) || busy.contains_key(&id) {
+                continue;
+            }
+            let Some(info) = network.peer_cache.get_peer(&peer_id).await else {
+                continue;
+            };
+            let metrics = network
+                .peer_cache
+                .get_metrics(&peer_id)
+                .await
+                .unwrap_or_default();
+
+            candidates.push(PeerCandidate {
+                // Prefer locally tracked reputation over the advertised value
+                reputation: reputation.get_score(&id).unwrap_or(info.reputation),
+                active_tasks: load.get(&id).copied().unwrap_or(0),
+                avg_latency_ms: (metrics.avg_latency_ms > 0).then_some(metrics.avg_latency_ms),
+                peer_id,
+            });
+        }
+        candidates
+    }
+
     /// Submits a task to the agent.
+    ///
+    /// A task repeating an earlier submission (same ID or idempotency key) is not
+    /// run again; the ID of the existing task is returned. A task whose payload
+    /// does not match the input schema of its executor fails right away (see
+    /// [`Agent::validate_payload`]).
     pub async fn submit_task(&self, task: Task) -> TaskId {
         // Add the task to the manager
         self.task_manager.add_task(task).await
     }
 
+    /// Checks a payload against the input schema of the executor that would run
+    /// it.
+    pub fn validate_payload(&self, payload: &TaskPayload) -> Result<(), InvalidPayload> {
+        self.executor_registry.validate(payload)
+    }
+
     /// Cancels a task.
+    ///
+    /// Cancelling a sharded task also cancels its unfinished shards.
     pub async fn cancel_task(&self, id: TaskId) -> anyhow::Result<()> {
         // 1. Check if the task is remote
         if let Some(task) = self.task_manager.get_task(id).await {
-            if let Some(assigned_peer) = task.assigned_to {
-                // Task was dispatched to a remote peer. Send cancellation request.
-                // Only send if not already completed/failed/cancelled
-                match task.status {
-                    TaskStatus::Queued | TaskStatus::Running => {
-                        let message =
+            self.cancel_remote(&task).await;
+            self.cancel_shards(&task).await;
+        }
+
+        // 2. Cancel locally (stops local execution or marks as cancelled)
+        self.votes.lock().await.remove(&id);
+        self.task_manager.cancel_task(id).await
+    }
+
+    /// Returns one page of the tasks matching `query`.
+    pub async fn query_tasks(&self, query: &TaskQuery) -> TaskPage {
+        self.task_manager.query_tasks(query).await
+    }
+
+    /// Submits a batch of related tasks as a group and returns the group's ID.
+    ///
+    /// Tasks repeating an earlier submission are not run again and do not join
+    /// the group.
+    pub async fn submit_batch(&self, tasks: Vec<Task>) -> GroupId {
+        self.task_manager.add_group(tasks).await
+    }
+
+    /// Counts the tasks of a group per status.
+    pub async fn group_status(&self, group: GroupId) -> GroupStatus {
+        self.task_manager.group_status(group).await
+    }
+
+    /// Waits until every task of a group has finished and returns its final
+    /// status.
+    pub async fn wait_for_group(&self, group: GroupId) -> GroupStatus {
+        self.task_manager.wait_for_group(group).await
+    }
+
+    /// Cancels every unfinished task of a group.
+    ///
+    /// Returns the number of tasks cancelled.
+    pub async fn cancel_group(&self, group: GroupId) -> anyhow::Result<usize> {
+        let mut cancelled = 0;
+        for task in self.task_manager.group_tasks(group).await {
+            // Earlier cancellations may have cascaded to this task meanwhile
+            let unfinished = self
+                .task_manager
+                .get_task(task.id)
+                .await
+                .is_some_and(|task| {
+                    matches!(task.status, TaskStatus::Queued | TaskStatus::Running)
+                });
+            if unfinished {
+                self.cancel_task(task.id).await?;
+                cancelled += 1;
+            }
+        }
+        Ok(cancelled)
+    }
+
+    /// Asks the peers a task was dispatched to to cancel it.
+    async fn cancel_remote(&self, task: &Task) {
+        let id = task.id;
+        for assigned_peer in task.assigned_to.iter().chain(&task.replicas) {
+            // Task was dispatched to a remote peer. Send cancellation request.
+            // Only send if not already completed/failed/cancelled
+            match task.status {
+                TaskStatus::Queued | TaskStatus::Running => {
+                    let message =
+                        Message::new_task_cancellation(self.id(), assigned_peer.clone(), id);
+                    // Best effort send with fallback
+                    if let Err(e) = self.send_network_message(message).await {
+                        tracing::warn!(
+                            "Failed to send cancellation to {}: {:?}. Falling back to broadcast.",
+                            assigned_peer,
+                            e
+                        );
+
+                        // Recreate message for broadcast fallback
+                        let mut message_clone =
                             Message::new_task_cancellation(self.id(), assigned_peer.clone(), id);
-                        // Best effort send with fallback
-                        if let Err(e) = self.send_network_message(message).await {
-                            eprintln!("Failed to send cancellation to {}: {:?}. Falling back to broadcast.", assigned_peer, e);
-
-                            // Recreate message for broadcast fallback
-                            let mut message_clone = Message::new_task_cancellation(
-                                self.id(),
-                                assigned_peer.clone(),
-                                id,
-                            );
 
-                            // Manually sign and broadcast
-                            let signable_bytes = message_clone.to_signable_bytes();
-                            if let Ok(signature) = self.identity.sign_data(&signable_bytes) {
-                                message_clone.signature = Some(signature);
-                                message_clone.public_key = Some(self.identity.public_key_bytes());
-
-                                if let Ok(bytes) = serde_json::to_vec(&message_clone) {
-                                    let msg = NetworkMessage {
-                                        from: self.id(),
-                                        to: "broadcast".to_string(),
-                                        content: bytes,
-                                    };
-                                    self.network_manager.lock().await.send_message(msg).await;
-                                }
+                        // Manually sign and broadcast
+                        let signable_bytes = message_clone.to_signable_bytes();
+                        if let Ok(signature) = self.identity.sign_data(&signable_bytes) {
+                            message_clone.signature = Some(signature);
+                            message_clone.public_key = Some(self.identity.public_key_bytes());
+
+                            if let Ok(bytes) = serde_json::to_vec(&message_clone) {
+                                let msg = NetworkMessage {
+                                    from: self.id(),
+                                    to: "broadcast".to_string(),
+                                    content: bytes,
+                                };
+                                self.network_manager.lock().await.send_message(msg).await;
                             }
                         }
                     }
-                    _ => {} // Already done, no need to send cancel
                 }
+                _ => {} // Already done, no need to send cancel
             }
         }
-
-        // 2. Cancel locally (stops local execution or marks as cancelled)
-        self.task_manager.cancel_task(id).await
     }
 
     /// Processes a single pending task if available (for manual execution or testing).
+    ///
+    /// The task is started only if the worker pool has a free slot for its type.
+    /// Returns `Ok(None)` when nothing is queued or every queued task is blocked
+    /// by a concurrency limit.
     pub async fn process_next_task(&self) -> anyhow::Result<Option<TaskId>> {
-        let next_task = self.task_manager.get_next_pending_task().await;
+        if !self.worker_pool.has_capacity() {
+            return Ok(None);
+        }
 
-        if let Some(task) = next_task {
-            // Update status to Running
-            self.task_manager
-                .update_status(task.id, TaskStatus::Running)
-                .await?;
+        let pool = &self.worker_pool;
+        let next_task = self
+            .task_manager
+            .get_next_pending_task_where(|t| pool.can_run(t.payload.as_ref().map(|p| &p.task_type)))
+            .await;
 
-            let _task_manager = self.task_manager.clone();
-            let _identity = self.identity.clone();
-            let _network_manager = self.network_manager.clone();
-            let _agent_id = self.id();
+        let Some(task) = next_task else {
+            return Ok(None);
+        };
+        let Some(permit) = pool.try_acquire(task.payload.as_ref().map(|p| &p.task_type)) else {
+            return Ok(None);
+        };
 
-            let task_id = task.id;
-            let _executor_registry = self.executor_registry.clone();
+        // Update status to Running
+        self.task_manager
+            .update_status(task.id, TaskStatus::Running)
+            .await?;
 
-            // Create an abort handle for cancellation
-            let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let _task_manager = self.task_manager.clone();
+        let _identity = self.identity.clone();
+        let _network_manager = self.network_manager.clone();
+        let _agent_id = self.id();
 
-            // Register the handle
-            self.task_manager
-                .register_running_task(task_id, abort_handle)
-                .await;
-
-            let model_manager = self.model_manager.clone();
-
-            // Spawn the execution
-            let _handle = tokio::spawn(Abortable::new(
-                async move {
-                    let result = if let Some(payload) = &task.payload {
-                        match payload.task_type {
-                            TaskType::TextProcessing => {
-                                let executor = TextProcessingExecutor::new(model_manager);
-                                executor.execute(payload).await
-                            }
-                            TaskType::VectorComputation => {
-                                VectorComputationExecutor.execute(payload).await
-                            }
-                            TaskType::AiModelDownload => {
-                                let model_name = payload
-                                    .data
-                                    .get("model")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("prajjwal1/bert-tiny");
-
-                                match model_manager.ensure_model(model_name).await {
-                                    Ok(path) => Ok(json!({
-                                        "status": "downloaded",
-                                        "model": model_name,
-                                        "path": path.to_string_lossy()
-                                    })),
-                                    Err(e) => Err(e),
-                                }
-                            }
-                            TaskType::AiInference => {
-                                // Delegate to TextProcessingExecutor which handles "embed" and other AI ops
-                                // We might want to refactor this later to be more distinct, but for now
-                                // TextProcessingExecutor knows how to use the ModelManager and InferenceEngine.
-                                let executor = TextProcessingExecutor::new(model_manager.clone());
-                                executor.execute(payload).await
-                            }
-                            TaskType::Custom(_) => {
-                                // Check for requested duration in payload for testing/simulation
-                                let duration = payload
-                                    .data
-                                    .get("duration_ms")
-                                    .and_then(|v| v.as_u64())
-                                    .unwrap_or(100);
-
-                                tokio::time::sleep(std::time::Duration::from_millis(duration))
-                                    .await;
-                                Ok(json!({"status": "simulated_custom_execution"}))
-                            }
-                        }
-                    } else {
-                        // No payload, just simulate work
-                        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-                        Ok::<serde_json::Value, anyhow::Error>(json!({"status": "no_payload"}))
-                    };
+        let task_id = task.id;
+        let executor_registry = self.executor_registry.clone();
 
-                    let status = match result {
-                        Ok(output) => TaskStatus::Completed(output),
-                        Err(e) => TaskStatus::Failed(e.to_string()),
-                    };
+        // Cancellation asks the executor to stop, and aborts it if it does not
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let cancellation = CancellationToken::new();
+        self.task_manager
+            .register_running_task(task_id, abort_handle, cancellation.clone())
+            .await;
 
-                    // Update local state
-                    let _ = _task_manager.update_status(task_id, status.clone()).await;
-
-                    // Broadcast update
-                    let mut message =
-                        Message::new_task_response(_agent_id.clone(), "broadcast", task_id, status);
-
-                    // Sign and send
-                    // Note: We duplicate logic from broadcast_message here because we can't easily
-                    // call methods on 'self' inside this moved async block without cloning the whole Agent
-                    // (which has other non-clonable fields like shutdown_tx).
-                    let signable_bytes = message.to_signable_bytes();
-                    if let Ok(signature) = _identity.sign_data(&signable_bytes) {
-                        message.signature = Some(signature);
-                        message.public_key = Some(_identity.public_key_bytes());
-
-                        if let Ok(bytes) = serde_json::to_vec(&message) {
-                            let msg = NetworkMessage {
-                                from: _agent_id,
-                                to: "broadcast".to_string(),
-                                content: bytes,
-                            };
-                            _network_manager.lock().await.send_message(msg).await;
-                        }
-                    } else {
-                        eprintln!("Failed to sign task completion message");
+        // Frees the worker slot when execution ends (or is aborted)
+        // and wakes the processing loop so queued tasks can start.
+        let release = ReleaseOnDrop {
+            _permit: permit,
+            notify: self.task_manager.queue_notifier(),
+        };
+
+        // Forward progress reports to the local task state and to the requester
+        let (progress, mut progress_rx) = ProgressReporter::channel();
+        let forwarder = {
+            let task_manager = self.task_manager.clone();
+            let identity = self.identity.clone();
+            let network_manager = self.network_manager.clone();
+            let agent_id = self.id();
+            tokio::spawn(async move {
+                while let Some(update) = progress_rx.recv().await {
+                    let _ = task_manager.report_progress(task_id, update.clone()).await;
+                    let message =
+                        Message::new_task_progress(agent_id.clone(), "broadcast", task_id, update);
+                    sign_and_broadcast(&identity, &network_manager, message).await;
+                }
+            })
+        };
+
+        // Keep checkpoints with the task, and push them to the requester
+        let resume_from = task.checkpoint.clone();
+        let (checkpoints, mut checkpoint_rx) =
+            CheckpointWriter::channel(resume_from.as_ref().map_or(0, |c| c.sequence));
+        let checkpoint_forwarder = {
+            let task_manager = self.task_manager.clone();
+            let identity = self.identity.clone();
+            let network_manager = self.network_manager.clone();
+            let agent_id = self.id();
+            let push = self.config.push_checkpoints && task.requested_by.is_some();
+            tokio::spawn(async move {
+                while let Some(checkpoint) = checkpoint_rx.recv().await {
+                    let recorded = task_manager
+                        .record_checkpoint(task_id, checkpoint.clone())
+                        .await
+                        .unwrap_or(false);
+                    if recorded && push {
+                        let message = Message::new_task_checkpoint(
+                            agent_id.clone(),
+                            "broadcast",
+                            task_id,
+                            checkpoint,
+                        );
+                        sign_and_broadcast(&identity, &network_manager, message).await;
                     }
-                },
-                abort_registration,
-            ));
+                }
+            })
+        };
 
-            Ok(Some(task_id))
-        } else {
-            Ok(None)
-        }
+        let deadline = task.deadline.map(|deadline| {
+            let remaining = deadline
+                .duration_since(std::time::SystemTime::now())
+                .unwrap_or_default();
+            std::time::Instant::now() + remaining
+        });
+        let ctx = ExecutionContext::new(cancellation, progress, deadline)
+            .with_checkpoints(checkpoints, resume_from);
+
+        // Spawn the execution
+        let _handle = tokio::spawn(Abortable::new(
+            async move {
+                let _release = release;
+
+                let result = if let Some(payload) = &task.payload {
+                    match executor_registry.resolve(&payload.task_type) {
+                        Some(executor) => executor.execute_with_context(payload, &ctx).await,
+                        None => Err(anyhow::anyhow!(
+                            "No executor registered for {}",
+                            payload.task_type
+                        )),
+                    }
+                } else {
+                    // No payload, just simulate work
+                    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                    Ok::<serde_json::Value, anyhow::Error>(json!({"status": "no_payload"}))
+                };
+
+                let status = match result {
+                    Ok(output) => TaskStatus::Completed(output),
+                    Err(e) if e.downcast_ref() == Some(&Interrupted::DeadlineExceeded) => {
+                        TaskStatus::Timeout
+                    }
+                    Err(e) => TaskStatus::Failed(e.to_string()),
+                };
+
+                // Let pending progress reports and checkpoints go out before
+                // the final response
+                let cancelled = ctx.is_cancelled();
+                drop(ctx);
+                let _ = forwarder.await;
+                let _ = checkpoint_forwarder.await;
+
+                // Cancellation already settled the task and answered the requester
+                if cancelled {
+                    return;
+                }
+
+                // Update local state
+                let _ = _task_manager.update_status(task_id, status.clone()).await;
+
+                // Broadcast update
+                let message =
+                    Message::new_task_response(_agent_id.clone(), "broadcast", task_id, status);
+                sign_and_broadcast(&_identity, &_network_manager, message).await;
+            },
+            abort_registration,
+        ));
+
+        Ok(Some(task_id))
     }
 
     /// Gets the status of a task.
@@ -692,6 +1623,47 @@ impl Agent {
         }
     }
 
+    /// Checks that the node has the resources to accept another task request.
+    ///
+    /// Fails only if `AgentConfig::resource_limits` are exceeded; a node that
+    /// cannot measure its usage accepts the request.
+    async fn admit_task(&self) -> Result<(), ResourceError> {
+        let Some(monitor) = &self.resource_monitor else {
+            return Ok(());
+        };
+        match monitor.check_current_limits().await {
+            Err(e @ (ResourceError::CpuLimitExceeded | ResourceError::MemoryLimitExceeded)) => {
+                Err(e)
+            }
+            Err(e) => {
+                tracing::warn!("Admitting task without resource check: {}", e);
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+
+    /// Checks a task request from `requester` against the quotas of its
+    /// reputation tier, counting it if accepted.
+    ///
+    /// Requesters without a reputation yet start at the default score.
+    async fn admit_requester(&self, requester: &str) -> Result<(), QuotaExceeded> {
+        let tier = {
+            let mut reputation = self.reputation_manager.write().await;
+            if reputation.get_score(requester).is_err() {
+                reputation.register_agent(requester.to_string());
+            }
+            reputation
+                .get_tier(requester)
+                .unwrap_or(ReputationTier::Newcomer)
+        };
+        let active = self.task_manager.active_requests_from(requester).await;
+        self.requester_quotas
+            .lock()
+            .await
+            .admit(requester, tier, active, std::time::Instant::now())
+    }
+
     /// Handles an incoming incoming network message.
     pub async fn handle_message(&self, message: Message) -> anyhow::Result<()> {
         // 0. Filter by Recipient
@@ -746,14 +1718,57 @@ impl Agent {
         match message.content {
             MessageType::TaskRequest(task) => {
                 println!("Agent received TaskRequest: {}", task.id);
+                let task_id = task.id;
+                // Repeated requests are still answered, they cost nothing to serve
+                if self.task_manager.find_duplicate(&task).await.is_none() {
+                    let rejection = match self.admit_task().await {
+                        Err(e) => Some((e.to_string(), ADMISSION_RETRY_AFTER)),
+                        Ok(()) => self.admit_requester(&message.sender).await.err().map(|e| {
+                            let retry_after = e.retry_after().unwrap_or(ADMISSION_RETRY_AFTER);
+                            (e.to_string(), retry_after)
+                        }),
+                    };
+                    if let Some((reason, retry_after)) = rejection {
+                        tracing::info!("Rejecting task {}: {}", task_id, reason);
+                        let reply = Message::new_task_rejected(
+                            self.id(),
+                            "broadcast",
+                            task_id,
+                            reason,
+                            retry_after,
+                        );
+                        let _ = self.broadcast_message(reply).await;
+                        return Ok(());
+                    }
+                }
+                let mut task = *task;
+                task.requested_by = Some(message.sender.clone());
                 // Submit the task to the local manager
                 // We trust the sender for now (Identity verification to be added later)
-                self.submit_task(*task).await;
+                let known_status = match self.task_manager.try_add_task(task).await {
+                    // Re-delivered or retried request: answer with what we already know
+                    // (the result once completed) instead of running it again
+                    SubmitOutcome::Duplicate(existing) => Some(existing.status),
+                    // Settled on submission, e.g. from the result cache
+                    SubmitOutcome::Added(id) => self
+                        .task_status(&id)
+                        .await
+                        .ok()
+                        .filter(|status| *status != TaskStatus::Queued),
+                };
+                if let Some(status) = known_status {
+                    let reply = Message::new_task_response(self.id(), "broadcast", task_id, status);
+                    let _ = self.broadcast_message(reply).await;
+                }
             }
             MessageType::TaskResponse { task_id, status } => {
                 println!("Agent received TaskResponse for {}: {:?}", task_id, status);
+                // Redundantly executed tasks are decided by vote
+                if self.record_vote(task_id, &message.sender, &status).await? {
+                    return Ok(());
+                }
                 // Update local state if we are tracking this remote task
-                if let Some(_task) = self.task_manager.get_task(task_id).await {
+                if let Some(task) = self.task_manager.get_task(task_id).await {
                     // Only update if the task is not already in a final state locally
                     // or if we decide to trust the remote update.
                     // For now, we update it if we have it.
@@ -762,6 +1777,10 @@ impl Agent {
                     // e.g. from Queued/Running -> Completed/Failed.
 
                     let _ = self.task_manager.update_status(task_id, status).await;
+
+                    if task.shard_of.is_some() {
+                        self.on_shard_update(task_id).await?;
+                    }
                 } else {
                     tracing::warn!("Received response for unknown task: {}", task_id);
                 }
@@ -775,6 +1794,7 @@ impl Agent {
             MessageType::CapabilityAnnouncement {
                 capabilities,
                 models,
+                schemas,
             } => {
                 println!(
                     "Agent received CapabilityAnnouncement from {}: {:?} (Models: {:?})",
@@ -821,10 +1841,7 @@ impl Agent {
                         addresses: vec![],
                         last_seen: chrono::Utc::now(),
                         reputation: 50,
-                        capabilities: PeerCapabilities {
-                            supported_tasks: vec![],
-                            supported_models: vec![],
-                        },
+                        capabilities: PeerCapabilities::default(),
                         status: ConnectionStatus::Connected, // Assume connected if we heard them via gossipsub
                     }
                 };
@@ -832,11 +1849,128 @@ impl Agent {
                 // Update capabilities
                 peer_info.capabilities.supported_tasks = supported_tasks;
                 peer_info.capabilities.supported_models = supported_models;
+                peer_info.capabilities.schemas = schemas;
                 peer_info.last_seen = chrono::Utc::now();
 
                 // Write back to cache
                 nm.peer_cache.upsert_peer(peer_info).await;
             }
+            MessageType::TaskProgress { task_id, update } => {
+                tracing::debug!(
+                    "Agent received TaskProgress for {}: {}%",
+                    task_id,
+                    update.percent
+                );
+                let Some(task) = self.task_manager.get_task(task_id).await else {
+                    tracing::warn!("Received progress for unknown task: {}", task_id);
+                    return Ok(());
+                };
+                // Only peers running the task can report its progress
+                let sender = message.sender.as_str();
+                let running_it = task.assigned_to.as_deref() == Some(sender)
+                    || task.replicas.iter().any(|peer| peer == sender);
+                if !running_it {
+                    tracing::warn!(
+                        "Ignoring progress for {} from {}, which is not running it",
+                        task_id,
+                        sender
+                    );
+                    return Ok(());
+                }
+                // The first report shows a dispatched task has started remotely
+                if task.status == TaskStatus::Queued && task.assigned_to.is_some() {
+                    self.task_manager
+                        .update_status(task_id, TaskStatus::Running)
+                        .await?;
+                }
+                self.task_manager.report_progress(task_id, update).await?;
+            }
+            MessageType::TaskCheckpoint {
+                task_id,
+                checkpoint,
+            } => {
+                // Only the peer running the task can checkpoint it
+                let Some(task) = self.task_manager.get_task(task_id).await else {
+                    return Ok(());
+                };
+                if task.assigned_to.as_deref() == Some(message.sender.as_str()) {
+                    tracing::debug!(
+                        "Agent received checkpoint {} for {}",
+                        checkpoint.sequence,
+                        task_id
+                    );
+                    self.task_manager
+                        .record_checkpoint(task_id, checkpoint)
+                        .await?;
+                }
+            }
+            MessageType::TaskStatusQuery { task_id } => {
+                let reply = match self.task_manager.get_task(task_id).await {
+                    Some(task) => {
+                        Message::new_task_response(self.id(), "broadcast", task_id, task.status)
+                    }
+                    None => Message::new_task_unknown(self.id(), "broadcast", task_id),
+                };
+                let _ = self.broadcast_message(reply).await;
+            }
+            MessageType::TaskUnknown { task_id } => {
+                // Only the peer running the task can declare it lost
+                let Some(task) = self.task_manager.get_task(task_id).await else {
+                    return Ok(());
+                };
+                let lost = task.assigned_to.as_deref() == Some(message.sender.as_str())
+                    && matches!(task.status, TaskStatus::Queued | TaskStatus::Running);
+                if lost {
+                    tracing::info!("Peer {} lost task {}, retrying", message.sender, task_id);
+                    self.retry_task(task_id).await?;
+                }
+            }
+            MessageType::TaskRejected {
+                task_id,
+                reason,
+                retry_after,
+            } => {
+                // Only the peer the task was sent to can reject it
+                let Some(task) = self.task_manager.get_task(task_id).await else {
+                    return Ok(());
+                };
+                let rejected = task.assigned_to.as_deref() == Some(message.sender.as_str())
+                    && matches!(task.status, TaskStatus::Queued | TaskStatus::Running);
+                if rejected {
+                    tracing::info!(
+                        "Peer {} rejected task {} ({}, busy for {:?}), trying another peer",
+                        message.sender,
+                        task_id,
+                        reason,
+                        retry_after
+                    );
+                    self.reroute_rejected_task(task_id, &message.sender, retry_after)
+                        .await?;
+                }
+            }
+            MessageType::TaskOffer {
+                task_id,
+                task_type,
+                model,
+            } => {
+                let capable = self.executor_registry.supports(&task_type)
+                    && model.is_none_or(|model| self.config.models.contains(&model));
+                // A node over its limits would reject the task anyway
+                if capable && self.admit_task().await.is_ok() {
+                    let bid = self.config.bidding.bid(
+                        self.task_manager.queued_count().await,
+                        self.worker_pool.active_workers(),
+                        self.worker_pool.max_concurrent(),
+                    );
+                    let reply = Message::new_task_bid(self.id(), "broadcast", task_id, bid);
+                    let _ = self.broadcast_message(reply).await;
+                }
+            }
+            MessageType::TaskBid { task_id, bid } => {
+                if let Some(round) = self.bid_rounds.lock().await.get_mut(&task_id) {
+                    round.record(&message.sender, bid);
+                }
+            }
             MessageType::TaskCancellation { task_id } => {
                 println!(
                     "Agent received TaskCancellation from {} for task {}",
@@ -868,6 +2002,98 @@ impl Agent {
     }
 }
 
+/// Interval between checks for timed out remote tasks and due scheduled tasks.
+const HOUSEKEEPING_INTERVAL_MS: u64 = 250;
+
+/// Time a node over its resource limits asks requesters to wait before
+/// sending it tasks again.
+const ADMISSION_RETRY_AFTER: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Builds the executor registry of a new agent.
+///
+/// Every built-in task type gets its executor, which runs tasks locally but is
+/// only advertised if listed in `AgentConfig::capabilities`. Listed `Custom`
+/// types are served by the [`SimulatedExecutor`], which also runs other
+/// `Custom` tasks submitted locally without advertising them. Listed types no
+/// executor supports are not advertised. Commands allow-listed in
+/// `AgentConfig::subprocess` take precedence over the simulation and are
+/// advertised.
+fn default_executors(config: &AgentConfig, model_manager: &Arc<ModelManager>) -> ExecutorRegistry {
+    let registry = ExecutorRegistry::new();
+    let text_processing = TextProcessingExecutor::new(model_manager.clone());
+    // Inference ops such as "embed" are handled by the text processing
+    // executor, sharing its engine and loaded models
+    registry.register_local(TaskType::AiInference, text_processing.clone());
+    registry.register_local(TaskType::TextProcessing, text_processing);
+    registry.register_local(TaskType::VectorComputation, VectorComputationExecutor);
+    registry.register_local(
+        TaskType::AiModelDownload,
+        ModelDownloadExecutor::new(model_manager.clone()),
+    );
+    #[cfg(feature = "wasm")]
+    registry.register_local(
+        TaskType::Custom(executors::wasm::WASM_TASK_TYPE.to_string()),
+        executors::WasmExecutor::new(config.wasm.clone()),
+    );
+    for task_type in &config.capabilities {
+        if let TaskType::Custom(_) = task_type {
+            if !registry.supports(task_type) {
+                registry.register_local(task_type.clone(), SimulatedExecutor);
+            }
+        }
+        if registry.supports(task_type) {
+            registry.advertise(task_type.clone());
+        } else {
+            tracing::warn!("Not advertising {}: no executor supports it", task_type);
+        }
+    }
+    for (task_type, executor) in config.subprocess.executors() {
+        registry.register(task_type, executor);
+    }
+    registry.register_fallback(SimulatedExecutor);
+    registry
+}
+
+/// Signs a message with the agent identity and broadcasts it.
+///
+/// Used from spawned execution tasks, which cannot call `send_network_message`
+/// without cloning the whole Agent.
+async fn sign_and_broadcast(
+    identity: &AgentIdentity,
+    network_manager: &Mutex<NetworkManager>,
+    mut message: Message,
+) {
+    let signable_bytes = message.to_signable_bytes();
+    match identity.sign_data(&signable_bytes) {
+        Ok(signature) => {
+            message.signature = Some(signature);
+            message.public_key = Some(identity.public_key_bytes());
+
+            if let Ok(bytes) = serde_json::to_vec(&message) {
+                let msg = NetworkMessage {
+                    from: message.sender.clone(),
+                    to: "broadcast".to_string(),
+                    content: bytes,
+                };
+                network_manager.lock().await.send_message(msg).await;
+            }
+        }
+        Err(_) => tracing::error!("Failed to sign message {}", message.id),
+    }
+}
+
+/// Holds a worker permit for a running task and signals the processing loop on release.
+struct ReleaseOnDrop {
+    _permit: worker_pool::WorkerPermit,
+    notify: Arc<tokio::sync::Notify>,
+}
+
+impl Drop for ReleaseOnDrop {
+    fn drop(&mut self) {
+        self.notify.notify_one();
+    }
+}
+
 /// Errors specific to the Agent module.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -977,27 +2203,12 @@ impl DefaultAgent {
         self.agent.clone().start().await?;
 
         let agent_clone = self.agent.clone();
-        let mut shutdown_rx = self.agent.shutdown_tx.subscribe();
 
         // Setup Network Manager & Message Loop
         {
             let mut nm = self.agent.network_manager.lock().await;
 
-            // Construct agent version string with capabilities
-            // e.g. "/p2p-ai-agents/1.0.0/TextProcessor,VectorComputer"
-            let mut version = "/p2p-ai-agents/1.0.0".to_string();
-            if !self.agent.config.capabilities.is_empty() {
-                version.push('/');
-                let caps: Vec<String> = self
-                    .agent
-                    .config
-                    .capabilities
-                    .iter()
-                    .map(|c| c.to_string())
-                    .collect();
-                version.push_str(&caps.join(","));
-            }
-            nm.set_agent_version(version);
+            nm.set_agent_version(self.agent.agent_version());
 
             // Channel for network messages -> agent
             let (tx, mut rx) = mpsc::channel::<Vec<u8>>(100);
@@ -1035,28 +2246,7 @@ impl DefaultAgent {
             });
         }
 
-        // Spawn background task processing loop
-        tokio::spawn(async move {
-            loop {
-                tokio::select! {
-                    _ = shutdown_rx.recv() => {
-                        // Received shutdown signal
-                        break;
-                    }
-                    _ = tokio::time::sleep(std::time::Duration::from_millis(50)) => {
-                        // Poll for tasks
-                        if let Err(e) = agent_clone.process_next_task().await {
-                             eprintln!("Error processing task: {:?}", e);
-                        }
-
-                        // Check for task timeouts
-                        if let Err(e) = agent_clone.check_task_timeouts().await {
-                             eprintln!("Error checking timeouts: {:?}", e);
-                        }
-                    }
-                }
-            }
-        });
+        // Task processing runs in the loop spawned by `Agent::start`.
 
         Ok(())
     }
@@ -1086,6 +2276,49 @@ impl DefaultAgent {
         self.agent.cancel_task(*id).await
     }
 
+    /// Register an executor for a task type and announce it to peers
+    pub async fn register_executor<E>(&self, task_type: TaskType, executor: E)
+    where
+        E: TaskExecutor + 'static,
+    {
+        self.agent.register_executor(task_type, executor).await
+    }
+
+    /// Get the task types this agent can execute
+    pub fn capabilities(&self) -> Vec<TaskType> {
+        self.agent.capabilities()
+    }
+
+    /// Query submitted tasks with filters, sorting and pagination
+    pub async fn query_tasks(&self, query: &TaskQuery) -> anyhow::Result<TaskPage> {
+        Ok(self.agent.query_tasks(query).await)
+    }
+
+    /// Submit a batch of related tasks as a group
+    pub async fn submit_batch(&self, tasks: Vec<Task>) -> anyhow::Result<GroupId> {
+        Ok(self.agent.submit_batch(tasks).await)
+    }
+
+    /// Get the number of tasks of a group per status
+    pub async fn group_status(&self, group: &GroupId) -> anyhow::Result<GroupStatus> {
+        Ok(self.agent.group_status(*group).await)
+    }
+
+    /// Cancels every unfinished task of a group
+    pub async fn cancel_group(&self, group: &GroupId) -> anyhow::Result<usize> {
+        self.agent.cancel_group(*group).await
+    }
+
+    /// Wait until every task of a group has finished
+    pub async fn wait_for_group(&self, group: &GroupId) -> anyhow::Result<GroupStatus> {
+        Ok(self.agent.wait_for_group(*group).await)
+    }
+
+    /// Get the bus task lifecycle events are published on
+    pub fn event_bus(&self) -> &EventBus {
+        self.agent.event_bus()
+    }
+
     /// Get the status of a submitted task
     pub async fn task_status(&self, id: &TaskId) -> anyhow::Result<TaskStatus> {
         self.agent.task_status(id).await
@@ -0,0 +1,197 @@
+//! Peer selection strategies for task dispatch.
+//!
+//! `Agent::dispatch_task` collects the connected peers able to run a task into
+//! [`PeerCandidate`]s and asks its [`PeerSelector`] to pick one. Peers listed in
+//! `Task::excluded_peers` (e.g. peers that already timed out on the task) are
+//! never offered as candidates.
+
+use crate::agent::task::Task;
+use crate::network::PeerId;
+use rand::distributions::{Distribution, WeightedIndex};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A peer that can run a task, with the data selectors rank on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerCandidate {
+    /// ID of the peer.
+    pub peer_id: PeerId,
+    /// Reputation score of the peer.
+    pub reputation: i32,
+    /// Number of our tasks currently assigned to the peer and not yet finished.
+    pub active_tasks: usize,
+    /// Average round-trip latency to the peer, if measured.
+    pub avg_latency_ms: Option<u64>,
+}
+
+/// Strategy for choosing the peer a task is dispatched to.
+pub trait PeerSelector: Send + Sync {
+    /// Picks one of `candidates` for `task`, or `None` if none is acceptable.
+    fn select(&self, task: &Task, candidates: &[PeerCandidate]) -> Option<PeerId>;
+}
+
+/// Built-in peer selection strategies, selectable from `AgentConfig`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PeerSelectionStrategy {
+    /// Cycle through candidates in turn.
+    RoundRobin,
+    /// Pick randomly, weighted by reputation.
+    ReputationWeighted,
+    /// Pick the candidate with the fewest active tasks.
+    #[default]
+    LeastLoaded,
+    /// Pick the candidate with the lowest measured latency.
+    LowestLatency,
+}
+
+impl PeerSelectionStrategy {
+    /// Creates the selector implementing this strategy.
+    pub fn selector(self) -> Arc<dyn PeerSelector> {
+        match self {
+            PeerSelectionStrategy::RoundRobin => Arc::new(RoundRobinSelector::default()),
+            PeerSelectionStrategy::ReputationWeighted => Arc::new(ReputationWeightedSelector),
+            PeerSelectionStrategy::LeastLoaded => Arc::new(LeastLoadedSelector),
+            PeerSelectionStrategy::LowestLatency => Arc::new(LowestLatencySelector),
+        }
+    }
+}
+
+/// Cycles through candidates in a stable (peer ID) order.
+#[derive(Debug, Default)]
+pub struct RoundRobinSelector {
+    next: AtomicUsize,
+}
+
+impl PeerSelector for RoundRobinSelector {
+    fn select(&self, _task: &Task, candidates: &[PeerCandidate]) -> Option<PeerId> {
+        if candidates.is_empty() {
+            return None;
+        }
+        // Candidate order is not stable between calls, so rotate over sorted IDs
+        let mut ids: Vec<&PeerId> = candidates.iter().map(|c| &c.peer_id).collect();
+        ids.sort_by(|a, b| a.0.cmp(&b.0));
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % ids.len();
+        Some(ids[index].clone())
+    }
+}
+
+/// Picks randomly with probability proportional to reputation.
+///
+/// Every candidate keeps a small chance of being picked, so new peers can build
+/// up reputation.
+#[derive(Debug, Default)]
+pub struct ReputationWeightedSelector;
+
+impl PeerSelector for ReputationWeightedSelector {
+    fn select(&self, _task: &Task, candidates: &[PeerCandidate]) -> Option<PeerId> {
+        let weights = candidates.iter().map(|c| c.reputation.max(0) as u64 + 1);
+        let index = WeightedIndex::new(weights).ok()?;
+        Some(
+            candidates[index.sample(&mut rand::thread_rng())]
+                .peer_id
+                .clone(),
+        )
+    }
+}
+
+/// Picks the candidate with the fewest active tasks, preferring higher reputation on ties.
+#[derive(Debug, Default)]
+pub struct LeastLoadedSelector;
+
+impl PeerSelector for LeastLoadedSelector {
+    fn select(&self, _task: &Task, candidates: &[PeerCandidate]) -> Option<PeerId> {
+        candidates
+            .iter()
+            .min_by(|a, b| {
+                a.active_tasks
+                    .cmp(&b.active_tasks)
+                    .then(b.reputation.cmp(&a.reputation))
+            })
+            .map(|c| c.peer_id.clone())
+    }
+}
+
+/// Picks the candidate with the lowest latency. Unmeasured peers come last.
+#[derive(Debug, Default)]
+pub struct LowestLatencySelector;
+
+impl PeerSelector for LowestLatencySelector {
+    fn select(&self, _task: &Task, candidates: &[PeerCandidate]) -> Option<PeerId> {
+        candidates
+            .iter()
+            .min_by_key(|c| (c.avg_latency_ms.unwrap_or(u64::MAX), c.active_tasks))
+            .map(|c| c.peer_id.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(
+        id: &str,
+        reputation: i32,
+        active_tasks: usize,
+        latency: Option<u64>,
+    ) -> PeerCandidate {
+        PeerCandidate {
+            peer_id: PeerId(id.to_string()),
+            reputation,
+            active_tasks,
+            avg_latency_ms: latency,
+        }
+    }
+
+    #[test]
+    fn test_round_robin_cycles() {
+        let task = Task::new("rr");
+        let candidates = vec![candidate("b", 0, 0, None), candidate("a", 0, 0, None)];
+        let selector = RoundRobinSelector::default();
+
+        let picks: Vec<String> = (0..4)
+            .map(|_| selector.select(&task, &candidates).unwrap().0)
+            .collect();
+        assert_eq!(picks, vec!["a", "b", "a", "b"]);
+    }
+
+    #[test]
+    fn test_least_loaded_and_lowest_latency() {
+        let task = Task::new("load");
+        let candidates = vec![
+            candidate("busy", 900, 3, Some(5)),
+            candidate("idle-low-rep", 10, 0, None),
+            candidate("idle", 500, 0, Some(40)),
+        ];
+
+        assert_eq!(
+            LeastLoadedSelector.select(&task, &candidates).unwrap().0,
+            "idle"
+        );
+        assert_eq!(
+            LowestLatencySelector.select(&task, &candidates).unwrap().0,
+            "busy"
+        );
+    }
+
+    #[test]
+    fn test_reputation_weighted_prefers_reputable_peers() {
+        let task = Task::new("weighted");
+        let candidates = vec![
+            candidate("good", 1000, 0, None),
+            candidate("bad", 0, 0, None),
+        ];
+
+        let good_picks = (0..200)
+            .filter(|_| {
+                ReputationWeightedSelector
+                    .select(&task, &candidates)
+                    .unwrap()
+                    .0
+                    == "good"
+            })
+            .count();
+        assert!(good_picks > 180);
+        assert!(ReputationWeightedSelector.select(&task, &[]).is_none());
+    }
+}
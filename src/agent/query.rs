@@ -0,0 +1,447 @@
+//! Filtered, sorted and paginated task listings.
+//!
+//! A [`TaskQuery`] selects tasks by status, type, priority, assigned peer and
+//! creation or completion time, sorts them by one [`SortKey`], and returns one
+//! [`TaskPage`] at a time. Only the tasks of the returned page are cloned.
+//!
+//! Pages are chained with a [`TaskCursor`] pointing after the last task of the
+//! previous page, rather than an offset, so tasks added or removed between two
+//! requests do not shift later pages. Cursors render as strings (see
+//! [`TaskCursor::from_str`]) so they can be handed to dashboards and CLI users.
+
+use crate::agent::task::{Task, TaskId, TaskPriority, TaskStatus, TaskType};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Page size used when a query does not set one.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Largest page a query returns.
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Errors of task queries.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum QueryError {
+    /// The cursor string could not be parsed.
+    #[error("Invalid task cursor: {0}")]
+    InvalidCursor(String),
+}
+
+/// Status of a task without its result or error message, for filtering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StatusKind {
+    /// `TaskStatus::Queued`.
+    Queued,
+    /// `TaskStatus::Running`.
+    Running,
+    /// `TaskStatus::Completed`.
+    Completed,
+    /// `TaskStatus::Failed`.
+    Failed,
+    /// `TaskStatus::Cancelled`.
+    Cancelled,
+    /// `TaskStatus::Timeout`.
+    Timeout,
+}
+
+impl StatusKind {
+    /// Returns the kind of a task status.
+    pub fn of(status: &TaskStatus) -> Self {
+        match status {
+            TaskStatus::Queued => Self::Queued,
+            TaskStatus::Running => Self::Running,
+            TaskStatus::Completed(_) => Self::Completed,
+            TaskStatus::Failed(_) => Self::Failed,
+            TaskStatus::Cancelled => Self::Cancelled,
+            TaskStatus::Timeout => Self::Timeout,
+        }
+    }
+}
+
+/// Field tasks are sorted by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortKey {
+    /// Creation time.
+    #[default]
+    CreatedAt,
+    /// Completion time. Unfinished tasks sort after all finished ones.
+    CompletedAt,
+    /// Priority, from `Low` to `Critical`.
+    Priority,
+}
+
+impl SortKey {
+    /// Returns the sort value of a task.
+    fn value(self, task: &Task) -> u128 {
+        match self {
+            SortKey::CreatedAt => nanos_since_epoch(task.created_at),
+            SortKey::CompletedAt => task.completed_at.map_or(u128::MAX, nanos_since_epoch),
+            SortKey::Priority => match task.priority {
+                TaskPriority::Low => 0,
+                TaskPriority::Normal => 1,
+                TaskPriority::High => 2,
+                TaskPriority::Critical => 3,
+            },
+        }
+    }
+}
+
+fn nanos_since_epoch(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos())
+}
+
+/// Direction of the sort.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortOrder {
+    /// Smallest values first.
+    #[default]
+    Ascending,
+    /// Largest values first.
+    Descending,
+}
+
+/// Position after the last task of a page.
+///
+/// A cursor is only meaningful for a query with the same sort key and order as
+/// the one that produced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskCursor {
+    value: u128,
+    id: TaskId,
+}
+
+impl TaskCursor {
+    fn after(task: &Task, key: SortKey) -> Self {
+        Self {
+            value: key.value(task),
+            id: task.id,
+        }
+    }
+
+    /// Position of the cursor in ascending order; ties are broken by task ID.
+    fn position(&self) -> (u128, TaskId) {
+        (self.value, self.id)
+    }
+}
+
+impl fmt::Display for TaskCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.value, self.id)
+    }
+}
+
+impl FromStr for TaskCursor {
+    type Err = QueryError;
+
+    /// Parses a cursor rendered by its `Display` implementation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || QueryError::InvalidCursor(s.to_string());
+        let (value, id) = s.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            value: value.parse().map_err(|_| invalid())?,
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Selection, order and page of a task listing.
+///
+/// Empty filter lists and unset bounds match every task. Time ranges include
+/// their start and exclude their end.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskQuery {
+    /// Statuses to include.
+    pub statuses: Vec<StatusKind>,
+    /// Task types to include. Tasks without a payload have no type and only
+    /// match when this is empty.
+    pub task_types: Vec<TaskType>,
+    /// Priorities to include.
+    pub priorities: Vec<TaskPriority>,
+    /// Peer the tasks are assigned to.
+    pub assigned_to: Option<String>,
+    /// Earliest creation time.
+    pub created_after: Option<SystemTime>,
+    /// Creation time the tasks precede.
+    pub created_before: Option<SystemTime>,
+    /// Earliest completion time. Unfinished tasks do not match.
+    pub completed_after: Option<SystemTime>,
+    /// Completion time the tasks precede. Unfinished tasks do not match.
+    pub completed_before: Option<SystemTime>,
+    /// Field to sort by.
+    pub sort_by: SortKey,
+    /// Direction of the sort.
+    pub order: SortOrder,
+    /// Maximum number of tasks per page, capped at [`MAX_PAGE_SIZE`].
+    pub limit: usize,
+    /// Cursor returned with the previous page, if any.
+    pub cursor: Option<TaskCursor>,
+}
+
+impl Default for TaskQuery {
+    fn default() -> Self {
+        Self {
+            statuses: Vec::new(),
+            task_types: Vec::new(),
+            priorities: Vec::new(),
+            assigned_to: None,
+            created_after: None,
+            created_before: None,
+            completed_after: None,
+            completed_before: None,
+            sort_by: SortKey::default(),
+            order: SortOrder::default(),
+            limit: DEFAULT_PAGE_SIZE,
+            cursor: None,
+        }
+    }
+}
+
+impl TaskQuery {
+    /// Creates a query matching every task, oldest first.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Includes tasks with the given status.
+    pub fn with_status(mut self, status: StatusKind) -> Self {
+        self.statuses.push(status);
+        self
+    }
+
+    /// Includes tasks of the given type.
+    pub fn with_task_type(mut self, task_type: TaskType) -> Self {
+        self.task_types.push(task_type);
+        self
+    }
+
+    /// Includes tasks with the given priority.
+    pub fn with_priority(mut self, priority: TaskPriority) -> Self {
+        self.priorities.push(priority);
+        self
+    }
+
+    /// Only includes tasks assigned to `peer`.
+    pub fn assigned_to(mut self, peer: impl Into<String>) -> Self {
+        self.assigned_to = Some(peer.into());
+        self
+    }
+
+    /// Only includes tasks created in `[after, before)`.
+    pub fn created_between(mut self, after: SystemTime, before: SystemTime) -> Self {
+        self.created_after = Some(after);
+        self.created_before = Some(before);
+        self
+    }
+
+    /// Only includes tasks completed in `[after, before)`.
+    pub fn completed_between(mut self, after: SystemTime, before: SystemTime) -> Self {
+        self.completed_after = Some(after);
+        self.completed_before = Some(before);
+        self
+    }
+
+    /// Sorts by `key` in the given order.
+    pub fn sorted_by(mut self, key: SortKey, order: SortOrder) -> Self {
+        self.sort_by = key;
+        self.order = order;
+        self
+    }
+
+    /// Sets the page size.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Continues after the page that returned `cursor`.
+    pub fn after(mut self, cursor: TaskCursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// Returns true if the task passes every filter.
+    pub fn matches(&self, task: &Task) -> bool {
+        let in_range = |time: Option<SystemTime>, after: Option<SystemTime>, before| match (
+            time, after, before,
+        ) {
+            (_, None, None) => true,
+            (None, _, _) => false,
+            (Some(time), after, before) => {
+                after.is_none_or(|after| time >= after) && before.is_none_or(|before| time < before)
+            }
+        };
+
+        (self.statuses.is_empty() || self.statuses.contains(&StatusKind::of(&task.status)))
+            && (self.task_types.is_empty()
+                || task
+                    .payload
+                    .as_ref()
+                    .is_some_and(|payload| self.task_types.contains(&payload.task_type)))
+            && (self.priorities.is_empty() || self.priorities.contains(&task.priority))
+            && self
+                .assigned_to
+                .as_ref()
+                .is_none_or(|peer| task.assigned_to.as_ref() == Some(peer))
+            && in_range(
+                Some(task.created_at),
+                self.created_after,
+                self.created_before,
+            )
+            && in_range(
+                task.completed_at,
+                self.completed_after,
+                self.completed_before,
+            )
+    }
+
+    /// Compares two tasks in the query's order.
+    fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        let ascending = (self.sort_by.value(a), a.id).cmp(&(self.sort_by.value(b), b.id));
+        match self.order {
+            SortOrder::Ascending => ascending,
+            SortOrder::Descending => ascending.reverse(),
+        }
+    }
+
+    /// Returns true if the task comes after the query's cursor.
+    fn is_after_cursor(&self, task: &Task) -> bool {
+        let Some(cursor) = &self.cursor else {
+            return true;
+        };
+        let position = (self.sort_by.value(task), task.id);
+        match self.order {
+            SortOrder::Ascending => position > cursor.position(),
+            SortOrder::Descending => position < cursor.position(),
+        }
+    }
+
+    /// Runs the query over `tasks`.
+    pub fn run<'a>(&self, tasks: impl IntoIterator<Item = &'a Task>) -> TaskPage {
+        let mut matching: Vec<&Task> = tasks
+            .into_iter()
+            .filter(|task| self.matches(task) && self.is_after_cursor(task))
+            .collect();
+        matching.sort_unstable_by(|a, b| self.compare(a, b));
+
+        let limit = self.limit.clamp(1, MAX_PAGE_SIZE);
+        let next_cursor =
+            (matching.len() > limit).then(|| TaskCursor::after(matching[limit - 1], self.sort_by));
+        TaskPage {
+            tasks: matching.into_iter().take(limit).cloned().collect(),
+            next_cursor,
+        }
+    }
+}
+
+/// One page of a task listing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskPage {
+    /// Tasks of the page, in query order.
+    pub tasks: Vec<Task>,
+    /// Cursor for the next page, or `None` if this is the last one.
+    pub next_cursor: Option<TaskCursor>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::task::TaskPayload;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn task(secs: u64, priority: TaskPriority, task_type: TaskType) -> Task {
+        let mut task = Task::with_payload(
+            priority,
+            TaskPayload {
+                task_type,
+                data: serde_json::Value::Null,
+                parameters: HashMap::new(),
+            },
+        );
+        task.created_at = UNIX_EPOCH + Duration::from_secs(secs);
+        task
+    }
+
+    #[test]
+    fn test_filters_combine() {
+        let mut done = task(10, TaskPriority::High, TaskType::TextProcessing);
+        done.status = TaskStatus::Completed(serde_json::json!("ok"));
+        done.completed_at = Some(UNIX_EPOCH + Duration::from_secs(20));
+        done.assigned_to = Some("peer-a".to_string());
+        let queued = task(15, TaskPriority::High, TaskType::TextProcessing);
+        let vector = task(12, TaskPriority::Low, TaskType::VectorComputation);
+        let untyped = Task::new("no payload");
+
+        let query = TaskQuery::new()
+            .with_task_type(TaskType::TextProcessing)
+            .with_priority(TaskPriority::High);
+        assert!(query.matches(&done) && query.matches(&queued));
+        assert!(!query.matches(&vector) && !query.matches(&untyped));
+
+        let query = TaskQuery::new().with_status(StatusKind::Completed);
+        assert!(query.matches(&done) && !query.matches(&queued));
+
+        assert!(TaskQuery::new().assigned_to("peer-a").matches(&done));
+        assert!(!TaskQuery::new().assigned_to("peer-b").matches(&done));
+
+        let query = TaskQuery::new().created_between(
+            UNIX_EPOCH + Duration::from_secs(10),
+            UNIX_EPOCH + Duration::from_secs(15),
+        );
+        assert!(query.matches(&done) && query.matches(&vector));
+        assert!(!query.matches(&queued));
+
+        let query =
+            TaskQuery::new().completed_between(UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(30));
+        assert!(query.matches(&done));
+        // Unfinished tasks have no completion time in range
+        assert!(!query.matches(&queued));
+    }
+
+    #[test]
+    fn test_pages_follow_cursor_in_both_orders() {
+        let tasks: Vec<Task> = (0..7)
+            .map(|secs| task(secs, TaskPriority::Normal, TaskType::TextProcessing))
+            .collect();
+        let created = |page: &TaskPage| -> Vec<SystemTime> {
+            page.tasks.iter().map(|task| task.created_at).collect()
+        };
+
+        for order in [SortOrder::Ascending, SortOrder::Descending] {
+            let mut query = TaskQuery::new()
+                .sorted_by(SortKey::CreatedAt, order)
+                .limit(3);
+            let mut seen = Vec::new();
+            loop {
+                let page = query.run(&tasks);
+                seen.extend(created(&page));
+                match page.next_cursor {
+                    // Cursors survive a round trip through their string form
+                    Some(cursor) => query = query.after(cursor.to_string().parse().unwrap()),
+                    None => break,
+                }
+            }
+
+            let mut expected: Vec<SystemTime> = tasks.iter().map(|t| t.created_at).collect();
+            if order == SortOrder::Descending {
+                expected.reverse();
+            }
+            assert_eq!(seen, expected);
+        }
+    }
+
+    #[test]
+    fn test_invalid_cursor_is_rejected() {
+        assert!(matches!(
+            "not-a-cursor".parse::<TaskCursor>(),
+            Err(QueryError::InvalidCursor(_))
+        ));
+        assert!("12:not-a-uuid".parse::<TaskCursor>().is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! Per-requester task quotas.
+//!
+//! Every peer sending task requests is held to the quotas [`QuotaConfig`] sets
+//! for its [`ReputationTier`]: at most `concurrent` unfinished tasks at once,
+//! and at most `per_window` accepted requests per sliding window. Requests over
+//! either quota are answered with a `TaskRejected`.
+
+use crate::network::reputation::ReputationTier;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Default window `TierQuota::per_window` applies to.
+pub const QUOTA_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Task quotas of the requesters in one reputation tier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TierQuota {
+    /// Unfinished tasks a requester may have at once.
+    pub concurrent: u32,
+    /// Tasks accepted from a requester per window.
+    pub per_window: u32,
+}
+
+/// Task quotas of requesters, by reputation tier.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaConfig {
+    /// Length of the sliding window `per_window` quotas apply to.
+    pub window: Duration,
+    /// Quotas of `ReputationTier::Newcomer` requesters.
+    pub newcomer: TierQuota,
+    /// Quotas of `ReputationTier::Established` requesters.
+    pub established: TierQuota,
+    /// Quotas of `ReputationTier::Trusted` requesters.
+    pub trusted: TierQuota,
+    /// Quotas of `ReputationTier::Elite` requesters.
+    pub elite: TierQuota,
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        Self {
+            window: QUOTA_WINDOW,
+            newcomer: TierQuota {
+                concurrent: 2,
+                per_window: 10,
+            },
+            established: TierQuota {
+                concurrent: 5,
+                per_window: 50,
+            },
+            trusted: TierQuota {
+                concurrent: 20,
+                per_window: 200,
+            },
+            elite: TierQuota {
+                concurrent: 50,
+                per_window: 1000,
+            },
+        }
+    }
+}
+
+impl QuotaConfig {
+    /// Returns the quotas of `tier`.
+    pub fn tier(&self, tier: ReputationTier) -> TierQuota {
+        match tier {
+            ReputationTier::Newcomer => self.newcomer,
+            ReputationTier::Established => self.established,
+            ReputationTier::Trusted => self.trusted,
+            ReputationTier::Elite => self.elite,
+        }
+    }
+}
+
+/// Why a request was over its requester's quota.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum QuotaExceeded {
+    /// The requester already has as many unfinished tasks as its tier allows.
+    #[error("Concurrent task quota of {limit} for {tier:?} requesters exceeded")]
+    Concurrent {
+        /// Tier of the requester.
+        tier: ReputationTier,
+        /// Unfinished tasks allowed at once.
+        limit: u32,
+    },
+    /// The requester already sent as many tasks in the window as its tier allows.
+    #[error("Task quota of {limit} per {window:?} for {tier:?} requesters exceeded")]
+    Window {
+        /// Tier of the requester.
+        tier: ReputationTier,
+        /// Tasks allowed per window.
+        limit: u32,
+        /// Length of the window.
+        window: Duration,
+        /// Time until the oldest counted request leaves the window.
+        retry_after: Duration,
+    },
+}
+
+impl QuotaExceeded {
+    /// Returns when the requester may send tasks again, if known.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            QuotaExceeded::Concurrent { .. } => None,
+            QuotaExceeded::Window { retry_after, .. } => Some(*retry_after),
+        }
+    }
+}
+
+/// Sliding-window usage of every requester.
+#[derive(Debug)]
+pub struct RequesterQuotas {
+    config: QuotaConfig,
+    accepted: HashMap<String, VecDeque<Instant>>,
+}
+
+impl Default for RequesterQuotas {
+    fn default() -> Self {
+        Self::new(QuotaConfig::default())
+    }
+}
+
+impl RequesterQuotas {
+    /// Creates a tracker enforcing the quotas of `config`.
+    pub fn new(config: QuotaConfig) -> Self {
+        Self {
+            config,
+            accepted: HashMap::new(),
+        }
+    }
+
+    /// Checks a request from `requester`, which has `active` unfinished tasks
+    /// here, against the quotas of `tier`, and counts it if accepted.
+    pub fn admit(
+        &mut self,
+        requester: &str,
+        tier: ReputationTier,
+        active: usize,
+        now: Instant,
+    ) -> Result<(), QuotaExceeded> {
+        let quota = self.config.tier(tier);
+        let limit = quota.concurrent;
+        if active >= limit as usize {
+            return Err(QuotaExceeded::Concurrent { tier, limit });
+        }
+
+        let window = self.config.window;
+        let accepted = self.accepted.entry(requester.to_string()).or_default();
+        while accepted
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= window)
+        {
+            accepted.pop_front();
+        }
+
+        let limit = quota.per_window;
+        if accepted.len() >= limit as usize {
+            let oldest = accepted[accepted.len() - limit as usize];
+            return Err(QuotaExceeded::Window {
+                tier,
+                limit,
+                window,
+                retry_after: window.saturating_sub(now.duration_since(oldest)),
+            });
+        }
+        accepted.push_back(now);
+        Ok(())
+    }
+
+    /// Returns the number of requests from `requester` counted in the window
+    /// ending at `now`.
+    pub fn usage(&self, requester: &str, now: Instant) -> usize {
+        self.accepted.get(requester).map_or(0, |accepted| {
+            accepted
+                .iter()
+                .filter(|t| now.duration_since(**t) < self.config.window)
+                .count()
+        })
+    }
+
+    /// Forgets requesters without requests in the window ending at `now`.
+    pub fn prune(&mut self, now: Instant) {
+        let window = self.config.window;
+        self.accepted.retain(|_, accepted| {
+            accepted
+                .back()
+                .is_some_and(|t| now.duration_since(*t) < window)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_quota_slides() {
+        let window = Duration::from_secs(60);
+        let config = QuotaConfig {
+            window,
+            ..Default::default()
+        };
+        let quota = config.newcomer.per_window as usize;
+        let mut quotas = RequesterQuotas::new(config);
+        let start = Instant::now();
+
+        for i in 0..quota {
+            let at = start + Duration::from_secs(i as u64);
+            quotas
+                .admit("peer", ReputationTier::Newcomer, 0, at)
+                .unwrap();
+        }
+        let at = start + Duration::from_secs(30);
+        let err = quotas
+            .admit("peer", ReputationTier::Newcomer, 0, at)
+            .unwrap_err();
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(30)));
+        // Rejected requests do not count
+        assert_eq!(quotas.usage("peer", at), quota);
+
+        // Other requesters have their own window
+        quotas
+            .admit("other", ReputationTier::Newcomer, 0, at)
+            .unwrap();
+
+        // Once the first request left the window, one more is accepted
+        let at = start + window;
+        quotas
+            .admit("peer", ReputationTier::Newcomer, 0, at)
+            .unwrap();
+        assert!(quotas
+            .admit("peer", ReputationTier::Newcomer, 0, at)
+            .is_err());
+
+        quotas.prune(start + 3 * window);
+        assert_eq!(quotas.usage("peer", start + 3 * window), 0);
+    }
+
+    #[test]
+    fn test_concurrent_quota_depends_on_tier() {
+        let mut quotas = RequesterQuotas::default();
+        let now = Instant::now();
+        let limit = QuotaConfig::default().newcomer.concurrent as usize;
+
+        assert!(matches!(
+            quotas.admit("peer", ReputationTier::Newcomer, limit, now),
+            Err(QuotaExceeded::Concurrent { .. })
+        ));
+        quotas
+            .admit("peer", ReputationTier::Elite, limit, now)
+            .unwrap();
+        assert_eq!(quotas.usage("peer", now), 1);
+    }
+}
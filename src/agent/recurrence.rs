@@ -0,0 +1,156 @@
+//! Cron-style recurrence for scheduled tasks.
+//!
+//! A task with `Task::recurrence` set acts as a template: each time it comes due
+//! the `TaskManager` submits a new occurrence of it and moves the template's
+//! `not_before` to the next matching time. Specs use the usual five cron fields
+//! (`min hour day-of-month month day-of-week`), with days of the week numbered
+//! 0-7 from Sunday (0 and 7 both being Sunday) or named. A six or seven field
+//! spec with leading seconds (and trailing year) is accepted as well; it is
+//! read as by the `cron` crate, which numbers days 1-7 from Sunday, so use day
+//! names there.
+//!
+//! ```text
+//! "0 3 * * *"       every day at 03:00 UTC
+//! "*/15 * * * *"    every 15 minutes
+//! "0 9 * * 1-5"     weekdays at 09:00 UTC
+//! "30 0 9 * * Mon"  Mondays at 09:00:30 UTC
+//! ```
+
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+/// Day names, indexed by standard cron day-of-week number.
+const DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Parses a recurrence spec.
+pub fn parse(spec: &str) -> anyhow::Result<Schedule> {
+    let spec = spec.trim();
+    let fields: Vec<&str> = spec.split_whitespace().collect();
+    // The cron crate expects a leading seconds field, and numbers days differently
+    let normalized = if let [minute, hour, day, month, day_of_week] = fields[..] {
+        let day_of_week = day_of_week_field(day_of_week)
+            .map_err(|e| anyhow::anyhow!("Invalid recurrence '{}': {}", spec, e))?;
+        format!("0 {} {} {} {} {}", minute, hour, day, month, day_of_week)
+    } else {
+        spec.to_string()
+    };
+
+    Schedule::from_str(&normalized)
+        .map_err(|e| anyhow::anyhow!("Invalid recurrence '{}': {}", spec, e))
+}
+
+/// Rewrites a standard day-of-week field with day names, which the cron crate
+/// reads the same way as everyone else.
+fn day_of_week_field(field: &str) -> anyhow::Result<String> {
+    if !field.chars().any(|c| c.is_ascii_digit()) {
+        return Ok(field.to_string());
+    }
+
+    let mut days = [false; 7];
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>()?),
+            None => (item, 1),
+        };
+        anyhow::ensure!(step > 0, "day-of-week step must be positive");
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (0, 6),
+            Some((start, end)) => (day_number(start)?, day_number(end)?),
+            // `n/step` runs to the end of the week
+            None if step > 1 => (day_number(range)?, 7),
+            None => (day_number(range)?, day_number(range)?),
+        };
+        anyhow::ensure!(start <= end, "day-of-week range '{}' is reversed", range);
+        for day in (start..=end).step_by(step) {
+            days[day % 7] = true;
+        }
+    }
+
+    let names: Vec<&str> = DAYS
+        .iter()
+        .zip(days)
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| *name)
+        .collect();
+    Ok(names.join(","))
+}
+
+/// Parses a day of the week, numbered 0-7 from Sunday or named.
+fn day_number(day: &str) -> anyhow::Result<usize> {
+    if let Ok(number) = day.parse::<usize>() {
+        anyhow::ensure!(number <= 7, "day of week {} is out of range 0-7", number);
+        return Ok(number);
+    }
+    DAYS.iter()
+        .position(|name| name.eq_ignore_ascii_case(day))
+        .ok_or_else(|| anyhow::anyhow!("unknown day of week '{}'", day))
+}
+
+/// Returns the first time matching `spec` strictly after `after`.
+///
+/// `None` means the schedule has no further occurrences (e.g. a past year).
+pub fn next_occurrence(spec: &str, after: SystemTime) -> anyhow::Result<Option<SystemTime>> {
+    let schedule = parse(spec)?;
+    let after: DateTime<Utc> = after.into();
+    Ok(schedule.after(&after).next().map(SystemTime::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Timelike};
+
+    #[test]
+    fn test_five_field_spec() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 10, 7, 12).unwrap();
+        let next = next_occurrence("*/15 * * * *", start.into())
+            .unwrap()
+            .unwrap();
+        let next: DateTime<Utc> = next.into();
+        assert_eq!((next.hour(), next.minute(), next.second()), (10, 15, 0));
+    }
+
+    #[test]
+    fn test_seconds_field_and_exhausted_schedule() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let next = next_occurrence("30 0 9 * * Mon", start.into())
+            .unwrap()
+            .unwrap();
+        // 2024-01-01 is a Monday
+        assert_eq!(
+            DateTime::<Utc>::from(next),
+            Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 30).unwrap()
+        );
+
+        assert!(next_occurrence("0 0 0 1 1 * 2020", start.into())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_numeric_days_of_week_count_from_sunday() {
+        let at_nine = |day| Utc.with_ymd_and_hms(2024, 1, day, 9, 0, 0).unwrap();
+        let next = |spec, after: DateTime<Utc>| -> DateTime<Utc> {
+            next_occurrence(spec, after.into()).unwrap().unwrap().into()
+        };
+
+        // 2024-01-05 is a Friday: weekdays skip to Monday the 8th
+        assert_eq!(next("0 9 * * 1-5", at_nine(5)), at_nine(8));
+        assert_eq!(next("0 9 * * Mon-Fri", at_nine(5)), at_nine(8));
+        // Sunday is 0 or 7
+        assert_eq!(next("0 9 * * 0", at_nine(1)), at_nine(7));
+        assert_eq!(next("0 9 * * 7", at_nine(1)), at_nine(7));
+        assert_eq!(next("0 9 * * 5-7", at_nine(1)), at_nine(5));
+        assert_eq!(next("0 9 * * */3", at_nine(1)), at_nine(3));
+        assert!(parse("* * * * 0").is_ok());
+        assert!(parse("* * * * 8").is_err());
+        assert!(parse("* * * * 5-1").is_err());
+    }
+
+    #[test]
+    fn test_invalid_spec() {
+        assert!(parse("every tuesday").is_err());
+    }
+}
@@ -121,6 +121,16 @@ impl ResourceMonitor {
         })
     }
 
+    /// Refresh the usage of this process and check it against the limits
+    ///
+    /// Cheaper than `update_usage` followed by `check_limits`, as only the
+    /// current process is refreshed.
+    pub async fn check_current_limits(&self) -> Result<()> {
+        let pid = get_current_pid().map_err(|_| ResourceError::ProcessNotFound)?;
+        self.system.write().await.refresh_process(pid);
+        self.check_limits().await
+    }
+
     /// Check if resource usage is within limits
     pub async fn check_limits(&self) -> Result<()> {
         let usage = self.current_usage().await?;
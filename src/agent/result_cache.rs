@@ -0,0 +1,376 @@
+//! Content-addressed cache of deterministic task results.
+//!
+//! Results are keyed by a SHA-256 hash of the task payload (task type, data,
+//! parameters and model name), so identical payloads share one entry no matter
+//! which task submitted them. A `TaskManager` with a cache (see
+//! `TaskManager::with_result_cache`) completes matching tasks from it on
+//! submission and stores the results of completed ones. Since a `TaskRequest`
+//! from a peer goes through the same path, peers answer repeated requests from
+//! their cache too.
+//!
+//! Entries expire after [`ResultCacheConfig::ttl`], and the least recently used
+//! ones are evicted to keep the cache within its entry and byte limits.
+
+use crate::agent::executors::text_processing;
+use crate::agent::task::{TaskPayload, TaskType};
+use crate::storage::local::{ConsistencyLevel, Storage};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+/// Prefix of the storage keys of cache entries.
+pub const CACHE_KEY_PREFIX: &str = "result-";
+
+/// Default time a result stays valid.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Default maximum number of cached results.
+pub const DEFAULT_MAX_CACHE_ENTRIES: usize = 1024;
+
+/// Default maximum total size of cached results, in bytes.
+pub const DEFAULT_MAX_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Configuration for a [`ResultCache`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultCacheConfig {
+    /// Time after which a cached result is no longer used.
+    pub ttl: Duration,
+    /// Maximum number of cached results.
+    pub max_entries: usize,
+    /// Maximum total size of the cached results, in bytes. Larger results are
+    /// not cached at all.
+    pub max_bytes: usize,
+    /// Task types whose results are deterministic and may be cached.
+    pub task_types: Vec<TaskType>,
+}
+
+impl Default for ResultCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: DEFAULT_CACHE_TTL,
+            max_entries: DEFAULT_MAX_CACHE_ENTRIES,
+            max_bytes: DEFAULT_MAX_CACHE_BYTES,
+            task_types: vec![TaskType::VectorComputation, TaskType::TextProcessing],
+        }
+    }
+}
+
+/// A cached result as written to storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResult {
+    result: serde_json::Value,
+    stored_at: SystemTime,
+}
+
+/// In-memory bookkeeping for one entry.
+#[derive(Debug, Clone, Copy)]
+struct EntryMeta {
+    stored_at: SystemTime,
+    last_used: SystemTime,
+    size: usize,
+}
+
+/// Result cache backed by a [`Storage`].
+pub struct ResultCache {
+    storage: Arc<dyn Storage>,
+    config: ResultCacheConfig,
+    /// Entries by storage key, loaded from storage on first use.
+    index: Mutex<Option<HashMap<String, EntryMeta>>>,
+}
+
+impl ResultCache {
+    /// Creates a cache over `storage`. Entries already in the storage are reused.
+    pub fn new(storage: Arc<dyn Storage>, config: ResultCacheConfig) -> Self {
+        Self {
+            storage,
+            config,
+            index: Mutex::new(None),
+        }
+    }
+
+    /// Returns the cache key of a payload, or `None` if its results are not cached.
+    pub fn key(&self, payload: &TaskPayload) -> Option<String> {
+        if !self.config.task_types.contains(&payload.task_type) {
+            return None;
+        }
+        // The model is hashed by its resolved name, so naming the default model
+        // explicitly yields the same key as leaving it out
+        let mut data = payload.data.clone();
+        if let Some(fields) = data.as_object_mut() {
+            fields.remove("model");
+        }
+        let parameters: BTreeMap<_, _> = payload.parameters.iter().collect();
+        let canonical = serde_json::to_vec(&(
+            &payload.task_type,
+            data,
+            parameters,
+            text_processing::model_name(payload),
+        ))
+        .ok()?;
+        Some(format!(
+            "{}{}",
+            CACHE_KEY_PREFIX,
+            hex::encode(Sha256::digest(&canonical))
+        ))
+    }
+
+    /// Returns the cached result for a payload, if there is a valid one.
+    pub async fn get(&self, payload: &TaskPayload) -> Option<serde_json::Value> {
+        let key = self.key(payload)?;
+        let mut index = self.index().await;
+        let now = SystemTime::now();
+
+        let meta = index.get_mut(&key)?;
+        if self.is_expired(meta.stored_at, now) {
+            index.remove(&key);
+            let _ = self.storage.delete(&key, ConsistencyLevel::Strong).await;
+            return None;
+        }
+        meta.last_used = now;
+
+        let bytes = self
+            .storage
+            .get(&key, ConsistencyLevel::Strong)
+            .await
+            .ok()??;
+        match serde_json::from_slice::<CachedResult>(&bytes) {
+            Ok(entry) => Some(entry.result),
+            Err(_) => {
+                index.remove(&key);
+                None
+            }
+        }
+    }
+
+    /// Caches the result of a payload, evicting older entries as needed.
+    pub async fn put(&self, payload: &TaskPayload, result: &serde_json::Value) {
+        let Some(key) = self.key(payload) else {
+            return;
+        };
+        let now = SystemTime::now();
+        let entry = CachedResult {
+            result: result.clone(),
+            stored_at: now,
+        };
+        let Ok(bytes) = serde_json::to_vec(&entry) else {
+            return;
+        };
+        let size = bytes.len();
+        if size > self.config.max_bytes {
+            return;
+        }
+
+        let mut index = self.index().await;
+        if self
+            .storage
+            .put(&key, bytes, ConsistencyLevel::Strong)
+            .await
+            .is_err()
+        {
+            return;
+        }
+        index.insert(
+            key.clone(),
+            EntryMeta {
+                stored_at: now,
+                last_used: now,
+                size,
+            },
+        );
+        self.evict(&mut index, now).await;
+    }
+
+    /// Returns the number of cached results, including expired ones not yet evicted.
+    pub async fn len(&self) -> usize {
+        self.index().await.len()
+    }
+
+    /// Returns true if nothing is cached.
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Drops expired entries, then the least recently used ones until the cache
+    /// is within its limits.
+    async fn evict(&self, index: &mut HashMap<String, EntryMeta>, now: SystemTime) {
+        let mut doomed: Vec<String> = index
+            .iter()
+            .filter(|(_, meta)| self.is_expired(meta.stored_at, now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &doomed {
+            index.remove(key);
+        }
+
+        let mut total: usize = index.values().map(|meta| meta.size).sum();
+        while index.len() > self.config.max_entries || total > self.config.max_bytes {
+            let Some(oldest) = index
+                .iter()
+                .min_by_key(|(_, meta)| meta.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            if let Some(meta) = index.remove(&oldest) {
+                total -= meta.size;
+            }
+            doomed.push(oldest);
+        }
+
+        for key in doomed {
+            let _ = self.storage.delete(&key, ConsistencyLevel::Strong).await;
+        }
+    }
+
+    fn is_expired(&self, stored_at: SystemTime, now: SystemTime) -> bool {
+        now.duration_since(stored_at)
+            .is_ok_and(|age| age >= self.config.ttl)
+    }
+
+    /// Locks the index, loading it from storage on first use.
+    async fn index(&self) -> MappedMutexGuard<'_, HashMap<String, EntryMeta>> {
+        let mut index = self.index.lock().await;
+        if index.is_none() {
+            let mut entries = HashMap::new();
+            let keys = self.storage.list().await.unwrap_or_default();
+            for key in keys.into_iter().filter(|k| k.starts_with(CACHE_KEY_PREFIX)) {
+                if let Ok(Some(bytes)) = self.storage.get(&key, ConsistencyLevel::Strong).await {
+                    if let Ok(entry) = serde_json::from_slice::<CachedResult>(&bytes) {
+                        let meta = EntryMeta {
+                            stored_at: entry.stored_at,
+                            last_used: entry.stored_at,
+                            size: bytes.len(),
+                        };
+                        entries.insert(key, meta);
+                    }
+                }
+            }
+            *index = Some(entries);
+        }
+        MutexGuard::map(index, |index| index.get_or_insert_with(HashMap::new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::local::LocalStorage;
+    use serde_json::json;
+
+    fn cache(dir: &std::path::Path, config: ResultCacheConfig) -> ResultCache {
+        ResultCache::new(Arc::new(LocalStorage::new(dir).unwrap()), config)
+    }
+
+    fn embed_payload(text: &str) -> TaskPayload {
+        TaskPayload {
+            task_type: TaskType::TextProcessing,
+            data: json!({ "operation": "embed", "text": text }),
+            parameters: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_key_is_content_addressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(dir.path(), ResultCacheConfig::default());
+
+        let mut a = embed_payload("hello");
+        a.parameters.insert("x".to_string(), json!(1));
+        a.parameters.insert("y".to_string(), json!(2));
+        let mut b = embed_payload("hello");
+        b.parameters.insert("y".to_string(), json!(2));
+        b.parameters.insert("x".to_string(), json!(1));
+        assert_eq!(cache.key(&a), cache.key(&b));
+
+        // The default model is part of the key, so naming it explicitly is the same payload
+        let mut explicit = embed_payload("hello");
+        explicit.data["model"] = json!("prajjwal1/bert-tiny");
+        assert_eq!(cache.key(&embed_payload("hello")), cache.key(&explicit));
+        explicit.data["model"] = json!("other-model");
+        assert_ne!(cache.key(&embed_payload("hello")), cache.key(&explicit));
+
+        // Only configured task types are cached
+        let mut custom = embed_payload("hello");
+        custom.task_type = TaskType::Custom("random".to_string());
+        assert!(cache.key(&custom).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hit_miss_and_reuse_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = cache(dir.path(), ResultCacheConfig::default());
+
+        assert!(first.get(&embed_payload("hello")).await.is_none());
+        first
+            .put(&embed_payload("hello"), &json!({ "embedding": [0.5] }))
+            .await;
+        assert_eq!(
+            first.get(&embed_payload("hello")).await,
+            Some(json!({ "embedding": [0.5] }))
+        );
+        assert!(first.get(&embed_payload("bye")).await.is_none());
+
+        let restarted = cache(dir.path(), ResultCacheConfig::default());
+        assert_eq!(restarted.len().await, 1);
+        assert!(restarted.get(&embed_payload("hello")).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(
+            dir.path(),
+            ResultCacheConfig {
+                ttl: Duration::from_millis(20),
+                ..Default::default()
+            },
+        );
+
+        cache.put(&embed_payload("hello"), &json!(1)).await;
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(cache.get(&embed_payload("hello")).await.is_none());
+        assert!(cache.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_entries_are_evicted() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache(
+            dir.path(),
+            ResultCacheConfig {
+                max_entries: 2,
+                ..Default::default()
+            },
+        );
+
+        cache.put(&embed_payload("a"), &json!("a")).await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        cache.put(&embed_payload("b"), &json!("b")).await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        // Using "a" makes "b" the least recently used entry
+        assert!(cache.get(&embed_payload("a")).await.is_some());
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        cache.put(&embed_payload("c"), &json!("c")).await;
+
+        assert_eq!(cache.len().await, 2);
+        assert!(cache.get(&embed_payload("a")).await.is_some());
+        assert!(cache.get(&embed_payload("b")).await.is_none());
+        assert!(cache.get(&embed_payload("c")).await.is_some());
+
+        // Results over the byte budget are never cached
+        let small = ResultCache::new(
+            Arc::new(LocalStorage::new(dir.path().join("small")).unwrap()),
+            ResultCacheConfig {
+                max_bytes: 64,
+                ..Default::default()
+            },
+        );
+        small
+            .put(&embed_payload("big"), &json!("x".repeat(100)))
+            .await;
+        assert!(small.is_empty().await);
+    }
+}
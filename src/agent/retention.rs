@@ -0,0 +1,228 @@
+//! Retention of finished tasks.
+//!
+//! Without retention every task stays in the `TaskManager` and its storage
+//! forever. A [`RetentionPolicy`] (see `TaskManager::with_retention`) bounds
+//! finished tasks by age and count, with per-status age overrides, and the
+//! agent runs `TaskManager::compact` every [`RetentionPolicy::interval`].
+//!
+//! Unfinished tasks are never pruned, and neither are finished tasks still
+//! needed by an unfinished one: parents of waiting dependents and shards of
+//! sharded tasks that have not been reduced yet.
+//!
+//! With an [`RetentionPolicy::archive_dir`], pruned tasks are first written to
+//! a gzip-compressed JSON Lines file there; if archiving fails, nothing is
+//! pruned.
+
+use crate::agent::task::{Task, TaskStatus};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default interval between compactions.
+pub const DEFAULT_COMPACTION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Final status of a finished task, used to key per-status rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FinishedStatus {
+    /// `TaskStatus::Completed`.
+    Completed,
+    /// `TaskStatus::Failed`.
+    Failed,
+    /// `TaskStatus::Cancelled`.
+    Cancelled,
+    /// `TaskStatus::Timeout`.
+    Timeout,
+}
+
+impl FinishedStatus {
+    /// Returns the finished status of a task status, or `None` if it is not final.
+    pub fn of(status: &TaskStatus) -> Option<Self> {
+        match status {
+            TaskStatus::Completed(_) => Some(Self::Completed),
+            TaskStatus::Failed(_) => Some(Self::Failed),
+            TaskStatus::Cancelled => Some(Self::Cancelled),
+            TaskStatus::Timeout => Some(Self::Timeout),
+            TaskStatus::Queued | TaskStatus::Running => None,
+        }
+    }
+}
+
+/// Which finished tasks are kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Finished tasks older than this are pruned.
+    pub max_age: Option<Duration>,
+    /// At most this many finished tasks are kept; the oldest are pruned first.
+    pub max_count: Option<usize>,
+    /// Age limits replacing `max_age` for tasks that finished with a given
+    /// status, e.g. to keep failures around for longer.
+    pub status_max_age: HashMap<FinishedStatus, Duration>,
+    /// Directory pruned tasks are archived to before they are removed.
+    pub archive_dir: Option<PathBuf>,
+    /// Interval between compactions run by the agent.
+    pub interval: Duration,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+            max_count: Some(10_000),
+            status_max_age: HashMap::new(),
+            archive_dir: None,
+            interval: DEFAULT_COMPACTION_INTERVAL,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Returns the age limit for tasks that finished with `status`.
+    fn max_age_for(&self, status: FinishedStatus) -> Option<Duration> {
+        self.status_max_age.get(&status).copied().or(self.max_age)
+    }
+
+    /// Selects the tasks to prune among `candidates`, which must all be finished
+    /// and safe to remove.
+    pub(crate) fn select<'a>(&self, candidates: Vec<&'a Task>, now: SystemTime) -> Vec<&'a Task> {
+        let finished_at = |task: &Task| task.completed_at.unwrap_or(task.created_at);
+
+        let (mut expired, mut kept): (Vec<&Task>, Vec<&Task>) =
+            candidates.into_iter().partition(|task| {
+                let Some(status) = FinishedStatus::of(&task.status) else {
+                    return false;
+                };
+                self.max_age_for(status).is_some_and(|max_age| {
+                    now.duration_since(finished_at(task))
+                        .is_ok_and(|age| age > max_age)
+                })
+            });
+
+        if let Some(max_count) = self.max_count {
+            if kept.len() > max_count {
+                // Newest first, so the tail holds the excess
+                kept.sort_by_key(|task| std::cmp::Reverse(finished_at(task)));
+                expired.extend(kept.split_off(max_count));
+            }
+        }
+        expired
+    }
+}
+
+/// Outcome of a compaction.
+#[derive(Debug, Clone, Default)]
+pub struct CompactionReport {
+    /// Number of tasks pruned.
+    pub pruned: usize,
+    /// Archive the pruned tasks were written to, if archiving is enabled.
+    pub archive: Option<PathBuf>,
+}
+
+/// Writes tasks to a new gzip-compressed JSON Lines file in `dir`.
+pub(crate) fn archive(dir: &Path, tasks: &[&Task]) -> anyhow::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    let path = dir.join(format!("tasks-{}.jsonl.gz", stamp));
+
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    for task in tasks {
+        serde_json::to_writer(&mut encoder, task)?;
+        encoder.write_all(b"\n")?;
+    }
+    encoder.finish()?.sync_all()?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished(status: TaskStatus, age: Duration, now: SystemTime) -> Task {
+        let mut task = Task::new("finished");
+        task.status = status;
+        task.completed_at = Some(now - age);
+        task
+    }
+
+    #[test]
+    fn test_select_by_age_and_status() {
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(3600);
+        let policy = RetentionPolicy {
+            max_age: Some(hour),
+            max_count: None,
+            status_max_age: HashMap::from([(FinishedStatus::Failed, 24 * hour)]),
+            ..Default::default()
+        };
+
+        let old_done = finished(TaskStatus::Completed(serde_json::json!(1)), 2 * hour, now);
+        let new_done = finished(TaskStatus::Completed(serde_json::json!(2)), hour / 2, now);
+        let old_failure = finished(TaskStatus::Failed("kept".to_string()), 2 * hour, now);
+        let ancient_failure = finished(TaskStatus::Failed("gone".to_string()), 48 * hour, now);
+
+        let selected = policy.select(
+            vec![&old_done, &new_done, &old_failure, &ancient_failure],
+            now,
+        );
+        let mut ids: Vec<_> = selected.iter().map(|t| t.id).collect();
+        ids.sort();
+        let mut expected = vec![old_done.id, ancient_failure.id];
+        expected.sort();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_select_oldest_beyond_max_count() {
+        let now = SystemTime::now();
+        let policy = RetentionPolicy {
+            max_age: None,
+            max_count: Some(2),
+            ..Default::default()
+        };
+        let tasks: Vec<Task> = (1..=4)
+            .map(|minutes| {
+                finished(
+                    TaskStatus::Cancelled,
+                    Duration::from_secs(60 * minutes),
+                    now,
+                )
+            })
+            .collect();
+
+        let selected = policy.select(tasks.iter().collect(), now);
+        let mut ids: Vec<_> = selected.iter().map(|t| t.id).collect();
+        ids.sort();
+        let mut expected = vec![tasks[2].id, tasks[3].id];
+        expected.sort();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_archive_is_compressed_json_lines() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let dir = tempfile::tempdir().unwrap();
+        let task = finished(
+            TaskStatus::Timeout,
+            Duration::from_secs(1),
+            SystemTime::now(),
+        );
+        let path = archive(dir.path(), &[&task]).unwrap();
+
+        let mut contents = String::new();
+        GzDecoder::new(std::fs::File::open(path).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+        let restored: Task = serde_json::from_str(contents.lines().next().unwrap()).unwrap();
+        assert_eq!(restored.id, task.id);
+        assert_eq!(restored.status, TaskStatus::Timeout);
+    }
+}
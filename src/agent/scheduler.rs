@@ -0,0 +1,206 @@
+//! Priority scheduling for queued tasks.
+//!
+//! The `TaskQueue` orders queued tasks by `TaskPriority` and creation time.
+//! With aging enabled, a waiting task gains one priority level per
+//! `aging_interval`, so a steady stream of high-priority work cannot starve
+//! low-priority tasks forever.
+
+use crate::agent::task::{Task, TaskId, TaskPriority};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default interval after which a waiting task is promoted by one priority level.
+pub const DEFAULT_AGING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Sort key of a queued task. The greatest key is scheduled first.
+type QueueKey = (i128, Reverse<u128>, Reverse<TaskId>);
+
+/// Priority queue of tasks waiting for execution.
+#[derive(Debug, Clone)]
+pub struct TaskQueue {
+    /// Queued tasks ordered by effective priority.
+    ordered: BTreeSet<QueueKey>,
+    /// Lookup of the key each task was inserted with, for removal.
+    keys: HashMap<TaskId, QueueKey>,
+    /// Interval after which a waiting task gains one priority level.
+    aging_interval: Option<Duration>,
+}
+
+impl Default for TaskQueue {
+    fn default() -> Self {
+        Self::new(Some(DEFAULT_AGING_INTERVAL))
+    }
+}
+
+impl TaskQueue {
+    /// Creates an empty queue. `None` disables aging (strict priority order).
+    pub fn new(aging_interval: Option<Duration>) -> Self {
+        Self {
+            ordered: BTreeSet::new(),
+            keys: HashMap::new(),
+            aging_interval: aging_interval.filter(|i| !i.is_zero()),
+        }
+    }
+
+    /// Returns the configured aging interval.
+    pub fn aging_interval(&self) -> Option<Duration> {
+        self.aging_interval
+    }
+
+    /// Adds a task to the queue, replacing any previous entry for it.
+    ///
+    /// Delayed tasks age from their `not_before` time rather than from creation.
+    pub fn push(&mut self, task: &Task) {
+        self.remove(&task.id);
+        let ready_at = task
+            .not_before
+            .map_or(task.created_at, |t| t.max(task.created_at));
+        let key = self.key_for(task.id, &task.priority, ready_at);
+        self.ordered.insert(key);
+        self.keys.insert(task.id, key);
+    }
+
+    /// Removes a task from the queue. Returns whether it was queued.
+    pub fn remove(&mut self, id: &TaskId) -> bool {
+        match self.keys.remove(id) {
+            Some(key) => self.ordered.remove(&key),
+            None => false,
+        }
+    }
+
+    /// Returns the ID of the task that should run next without removing it.
+    pub fn peek(&self) -> Option<TaskId> {
+        self.ordered.last().map(|(_, _, Reverse(id))| *id)
+    }
+
+    /// Removes and returns the ID of the task that should run next.
+    pub fn pop(&mut self) -> Option<TaskId> {
+        let (_, _, Reverse(id)) = self.ordered.pop_last()?;
+        self.keys.remove(&id);
+        Some(id)
+    }
+
+    /// Iterates over queued task IDs in scheduling order.
+    pub fn iter(&self) -> impl Iterator<Item = TaskId> + '_ {
+        self.ordered.iter().rev().map(|(_, _, Reverse(id))| *id)
+    }
+
+    /// Returns whether the task is queued.
+    pub fn contains(&self, id: &TaskId) -> bool {
+        self.keys.contains_key(id)
+    }
+
+    /// Number of queued tasks.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns true if no tasks are queued.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Removes all tasks from the queue.
+    pub fn clear(&mut self) {
+        self.ordered.clear();
+        self.keys.clear();
+    }
+
+    /// Computes the sort key of a task.
+    ///
+    /// With aging, the effective priority at time `now` is
+    /// `rank + (now - created_at) / interval`. Since `now` is the same for every
+    /// task when comparing, ordering by `rank * interval - created_at` yields the
+    /// same order without having to re-sort the queue as time passes.
+    fn key_for(&self, id: TaskId, priority: &TaskPriority, created_at: SystemTime) -> QueueKey {
+        let created_ms = created_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let rank = priority_rank(priority);
+
+        let score = match self.aging_interval {
+            Some(interval) => rank * interval.as_millis() as i128 - created_ms as i128,
+            None => rank,
+        };
+
+        (score, Reverse(created_ms), Reverse(id))
+    }
+}
+
+/// Numeric rank of a priority level (higher runs first).
+fn priority_rank(priority: &TaskPriority) -> i128 {
+    match priority {
+        TaskPriority::Low => 0,
+        TaskPriority::Normal => 1,
+        TaskPriority::High => 2,
+        TaskPriority::Critical => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(priority: TaskPriority, age: Duration) -> Task {
+        let mut task = Task::new("queued");
+        task.priority = priority;
+        task.created_at = SystemTime::now() - age;
+        task
+    }
+
+    #[test]
+    fn test_strict_priority_order() {
+        let mut queue = TaskQueue::new(None);
+        let low = task(TaskPriority::Low, Duration::from_secs(3600));
+        let critical = task(TaskPriority::Critical, Duration::ZERO);
+        let normal = task(TaskPriority::Normal, Duration::ZERO);
+        queue.push(&low);
+        queue.push(&critical);
+        queue.push(&normal);
+
+        assert_eq!(queue.pop(), Some(critical.id));
+        assert_eq!(queue.pop(), Some(normal.id));
+        assert_eq!(queue.pop(), Some(low.id));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_fifo_within_same_priority() {
+        let mut queue = TaskQueue::new(None);
+        let older = task(TaskPriority::High, Duration::from_secs(10));
+        let newer = task(TaskPriority::High, Duration::from_secs(1));
+        queue.push(&newer);
+        queue.push(&older);
+
+        assert_eq!(queue.pop(), Some(older.id));
+        assert_eq!(queue.pop(), Some(newer.id));
+    }
+
+    #[test]
+    fn test_aging_promotes_waiting_tasks() {
+        let mut queue = TaskQueue::new(Some(Duration::from_secs(1)));
+        // Waited for more than 3 intervals: outranks a fresh Critical task
+        let starving = task(TaskPriority::Low, Duration::from_secs(10));
+        let critical = task(TaskPriority::Critical, Duration::ZERO);
+        queue.push(&critical);
+        queue.push(&starving);
+
+        assert_eq!(queue.peek(), Some(starving.id));
+    }
+
+    #[test]
+    fn test_remove_and_requeue() {
+        let mut queue = TaskQueue::default();
+        let t = task(TaskPriority::Normal, Duration::ZERO);
+        queue.push(&t);
+        queue.push(&t);
+        assert_eq!(queue.len(), 1);
+
+        assert_eq!(queue.iter().collect::<Vec<_>>(), vec![t.id]);
+        assert!(queue.remove(&t.id));
+        assert!(!queue.remove(&t.id));
+        assert!(queue.peek().is_none());
+    }
+}
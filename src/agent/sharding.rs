@@ -0,0 +1,188 @@
+//! Map-reduce sharding of large tasks.
+//!
+//! A task with `Task::sharding` set is not sent to a single peer. Instead the
+//! [`TaskSplitter`] registered for its task type breaks the payload into shard
+//! payloads, each shard becomes a child task (`Task::shard_of`) dispatched to a
+//! different capable peer, and once every shard completed the splitter reduces
+//! the shard results into the parent's `TaskStatus::Completed` value.
+//!
+//! Shards are ordinary remote tasks, so a failed or timed out shard is retried
+//! on another peer on its own without restarting its siblings.
+
+use crate::agent::task::TaskPayload;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Key of the document array split by [`CorpusSplitter`].
+pub const CORPUS_KEY: &str = "corpus";
+
+/// How a task is sharded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sharding {
+    /// Upper bound on the number of shards. The actual count is also limited by
+    /// the number of capable peers, since every shard goes to a different peer.
+    pub max_shards: usize,
+}
+
+impl Sharding {
+    /// Creates a sharding setting with at most `max_shards` shards.
+    pub fn new(max_shards: usize) -> Self {
+        Self { max_shards }
+    }
+}
+
+/// Splits a payload into shards and merges the shard results.
+pub trait TaskSplitter: Send + Sync {
+    /// Breaks `payload` into at most `shards` non-empty shard payloads.
+    fn split(&self, payload: &TaskPayload, shards: usize) -> anyhow::Result<Vec<TaskPayload>>;
+
+    /// Reduces the shard results, in shard order, into the result of `payload`.
+    fn reduce(&self, payload: &TaskPayload, results: Vec<Value>) -> anyhow::Result<Value>;
+}
+
+/// Splits `TextProcessing` jobs over a corpus of documents.
+///
+/// The payload holds the documents in `data.corpus`; every shard gets a
+/// contiguous slice of them with the rest of the payload unchanged. Supported
+/// operations are `word_count` (counts are summed) and `embed` (embeddings are
+/// concatenated in document order).
+#[derive(Debug, Default)]
+pub struct CorpusSplitter;
+
+impl CorpusSplitter {
+    fn operation(payload: &TaskPayload) -> anyhow::Result<&str> {
+        let operation = payload
+            .data
+            .get("operation")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        match operation {
+            "word_count" | "embed" => Ok(operation),
+            _ => Err(anyhow::anyhow!(
+                "Text operation {} cannot be sharded",
+                operation
+            )),
+        }
+    }
+}
+
+impl TaskSplitter for CorpusSplitter {
+    fn split(&self, payload: &TaskPayload, shards: usize) -> anyhow::Result<Vec<TaskPayload>> {
+        Self::operation(payload)?;
+        let corpus = payload
+            .data
+            .get(CORPUS_KEY)
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Missing {} array", CORPUS_KEY))?;
+        if corpus.is_empty() || shards == 0 {
+            return Err(anyhow::anyhow!("Nothing to shard"));
+        }
+
+        // Balanced slices: the first `corpus.len() % count` shards get one extra document
+        let count = shards.min(corpus.len());
+        let mut start = 0;
+        Ok((0..count)
+            .map(|i| {
+                let len = corpus.len() / count + usize::from(i < corpus.len() % count);
+                let mut shard = payload.clone();
+                shard.data[CORPUS_KEY] = Value::Array(corpus[start..start + len].to_vec());
+                start += len;
+                shard
+            })
+            .collect())
+    }
+
+    fn reduce(&self, payload: &TaskPayload, results: Vec<Value>) -> anyhow::Result<Value> {
+        match Self::operation(payload)? {
+            "word_count" => {
+                let mut total = 0;
+                for result in &results {
+                    total += result
+                        .get("word_count")
+                        .and_then(|v| v.as_u64())
+                        .ok_or_else(|| anyhow::anyhow!("Shard result missing word_count"))?;
+                }
+                Ok(json!({ "word_count": total }))
+            }
+            _ => {
+                let mut embeddings = Vec::new();
+                for result in &results {
+                    let shard_embeddings = result
+                        .get("embeddings")
+                        .and_then(|v| v.as_array())
+                        .ok_or_else(|| anyhow::anyhow!("Shard result missing embeddings"))?;
+                    embeddings.extend(shard_embeddings.iter().cloned());
+                }
+                let model = results.first().and_then(|r| r.get("model")).cloned();
+                Ok(json!({ "embeddings": embeddings, "model": model }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::task::TaskType;
+    use std::collections::HashMap;
+
+    fn corpus_payload(operation: &str, corpus: Vec<&str>) -> TaskPayload {
+        TaskPayload {
+            task_type: TaskType::TextProcessing,
+            data: json!({ "operation": operation, "corpus": corpus, "model": "tiny" }),
+            parameters: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_split_corpus_into_slices() {
+        let payload = corpus_payload("word_count", vec!["a", "b", "c", "d", "e"]);
+        let shards = CorpusSplitter.split(&payload, 3).unwrap();
+
+        assert_eq!(shards.len(), 3);
+        assert_eq!(shards[0].data["corpus"], json!(["a", "b"]));
+        assert_eq!(shards[1].data["corpus"], json!(["c", "d"]));
+        assert_eq!(shards[2].data["corpus"], json!(["e"]));
+        assert_eq!(shards[2].data["model"], "tiny");
+
+        // Never more shards than documents
+        assert_eq!(CorpusSplitter.split(&payload, 10).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_reduce_word_count_and_embeddings() {
+        let count = corpus_payload("word_count", vec![]);
+        assert_eq!(
+            CorpusSplitter
+                .reduce(
+                    &count,
+                    vec![json!({"word_count": 3}), json!({"word_count": 4})]
+                )
+                .unwrap(),
+            json!({"word_count": 7})
+        );
+
+        let embed = corpus_payload("embed", vec![]);
+        assert_eq!(
+            CorpusSplitter
+                .reduce(
+                    &embed,
+                    vec![
+                        json!({"embeddings": [[1.0], [2.0]], "model": "tiny"}),
+                        json!({"embeddings": [[3.0]], "model": "tiny"}),
+                    ]
+                )
+                .unwrap(),
+            json!({"embeddings": [[1.0], [2.0], [3.0]], "model": "tiny"})
+        );
+    }
+
+    #[test]
+    fn test_unsupported_payloads() {
+        let reverse = corpus_payload("reverse", vec!["a"]);
+        assert!(CorpusSplitter.split(&reverse, 2).is_err());
+
+        let empty = corpus_payload("word_count", vec![]);
+        assert!(CorpusSplitter.split(&empty, 2).is_err());
+    }
+}
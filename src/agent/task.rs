@@ -1,11 +1,26 @@
 //! Task management module for Agents.
 
+use crate::agent::checkpoint::Checkpoint;
+use crate::agent::dependencies::{self, DependencyState};
+use crate::agent::events;
+use crate::agent::execution::{CancellationToken, ExecutionContext, CANCELLATION_GRACE_PERIOD};
+use crate::agent::executors::ExecutorRegistry;
+use crate::agent::groups::{GroupId, GroupStatus};
+use crate::agent::journal::TaskJournal;
+use crate::agent::query::{TaskPage, TaskQuery};
+use crate::agent::recurrence;
+use crate::agent::result_cache::ResultCache;
+use crate::agent::retention::{self, CompactionReport, RetentionPolicy};
+use crate::agent::scheduler::{TaskQueue, DEFAULT_AGING_INTERVAL};
+use crate::agent::sharding::Sharding;
+use crate::agent::voting::Redundancy;
+use crate::core::events::EventBus;
 use crate::storage::local::{ConsistencyLevel, Storage};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::SystemTime;
-use tokio::sync::RwLock;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{mpsc, Notify, RwLock};
 use uuid::Uuid;
 
 /// Unique identifier for a task.
@@ -114,6 +129,61 @@ pub struct Task {
     pub retry_count: u32,
     /// Maximum number of retries allowed (default 3).
     pub max_retries: u32,
+    /// Tasks that must complete before this task is released for execution.
+    #[serde(default)]
+    pub depends_on: Vec<TaskId>,
+    /// Partial output reported by the executor while the task runs.
+    #[serde(default)]
+    pub partial_output: Option<serde_json::Value>,
+    /// Completion time estimated by the executor, if it reported one.
+    #[serde(default)]
+    pub estimated_completion_at: Option<SystemTime>,
+    /// Peers that already failed this task and must not be picked on retry.
+    #[serde(default)]
+    pub excluded_peers: Vec<String>,
+    /// Earliest time the task may run. For recurring tasks, the next due time.
+    #[serde(default)]
+    pub not_before: Option<SystemTime>,
+    /// Cron-style recurrence spec (see [`recurrence`]). Recurring tasks are
+    /// templates that submit a new occurrence each time they come due.
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    /// ID of the recurring task this task is an occurrence of.
+    #[serde(default)]
+    pub recurrence_of: Option<TaskId>,
+    /// Run the task on several peers and accept the result a quorum agrees on.
+    #[serde(default)]
+    pub redundancy: Option<Redundancy>,
+    /// Peers a redundant task was dispatched to.
+    #[serde(default)]
+    pub replicas: Vec<String>,
+    /// Split the task into shards run on different peers (see [`crate::agent::sharding`]).
+    #[serde(default)]
+    pub sharding: Option<Sharding>,
+    /// Shard tasks of a sharded task, in shard order.
+    #[serde(default)]
+    pub shards: Vec<TaskId>,
+    /// ID of the sharded task this task is a shard of.
+    #[serde(default)]
+    pub shard_of: Option<TaskId>,
+    /// Client-chosen key identifying the submission. A task submitted again under
+    /// the same key is not run twice; the earlier task is returned instead.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Peer that sent this task for execution here, if it was received remotely.
+    #[serde(default)]
+    pub requested_by: Option<String>,
+    /// Time by which the task must be finished. Executors stop at their next
+    /// safe point after it, and the task times out.
+    #[serde(default)]
+    pub deadline: Option<SystemTime>,
+    /// Group of the batch this task was submitted in, if any.
+    #[serde(default)]
+    pub group: Option<GroupId>,
+    /// Latest checkpoint saved while the task ran, resumed from when it runs
+    /// again (see [`crate::agent::checkpoint`]).
+    #[serde(default)]
+    pub checkpoint: Option<Checkpoint>,
 }
 
 impl Task {
@@ -136,6 +206,23 @@ impl Task {
             assigned_to: None,
             retry_count: 0,
             max_retries: 3,
+            depends_on: Vec::new(),
+            partial_output: None,
+            estimated_completion_at: None,
+            excluded_peers: Vec::new(),
+            not_before: None,
+            recurrence: None,
+            recurrence_of: None,
+            redundancy: None,
+            replicas: Vec::new(),
+            sharding: None,
+            shards: Vec::new(),
+            shard_of: None,
+            idempotency_key: None,
+            requested_by: None,
+            deadline: None,
+            group: None,
+            checkpoint: None,
         }
     }
 
@@ -158,15 +245,77 @@ impl Task {
             assigned_to: None,
             retry_count: 0,
             max_retries: 3,
+            depends_on: Vec::new(),
+            partial_output: None,
+            estimated_completion_at: None,
+            excluded_peers: Vec::new(),
+            not_before: None,
+            recurrence: None,
+            recurrence_of: None,
+            redundancy: None,
+            replicas: Vec::new(),
+            sharding: None,
+            shards: Vec::new(),
+            shard_of: None,
+            idempotency_key: None,
+            requested_by: None,
+            deadline: None,
+            group: None,
+            checkpoint: None,
         }
     }
 
+    /// Returns true if the task is waiting for its `not_before` time or is a
+    /// recurring template, and so must not enter the scheduling queue.
+    pub fn is_scheduled(&self, now: SystemTime) -> bool {
+        self.is_recurring_template() || self.not_before.is_some_and(|t| t > now)
+    }
+
+    /// Returns true if the task is a recurring template, which only submits
+    /// occurrences and stays unfinished while it recurs.
+    pub fn is_recurring_template(&self) -> bool {
+        self.recurrence.is_some()
+    }
+
+    /// Creates the next occurrence of a recurring task.
+    ///
+    /// Occurrences start fresh and do not inherit the template's schedule or
+    /// dependencies.
+    fn new_occurrence(&self) -> Task {
+        let mut occurrence = match &self.payload {
+            Some(payload) => Task::with_payload(self.priority.clone(), payload.clone()),
+            None => {
+                let mut task = Task::new(self.description.clone());
+                task.priority = self.priority.clone();
+                task
+            }
+        };
+        occurrence.description = self.description.clone();
+        occurrence.max_retries = self.max_retries;
+        occurrence.redundancy = self.redundancy.clone();
+        occurrence.sharding = self.sharding.clone();
+        occurrence.recurrence_of = Some(self.id);
+        occurrence.requested_by = self.requested_by.clone();
+        occurrence.group = self.group;
+        occurrence
+    }
+
     /// Gets estimated completion time in seconds (if available).
+    ///
+    /// An ETA reported by the executor takes precedence over the estimate
+    /// extrapolated from progress and elapsed time.
     pub fn estimated_completion_secs(&self) -> Option<u64> {
         if self.status != TaskStatus::Running {
             return None;
         }
 
+        if let Some(eta) = self.estimated_completion_at {
+            let remaining = eta
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO);
+            return Some(remaining.as_secs_f64().ceil() as u64);
+        }
+
         let progress = self.progress_percent? as f64;
         if progress < 1.0 {
             return None;
@@ -207,21 +356,185 @@ impl Task {
     }
 }
 
+/// Progress report emitted by a running task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressUpdate {
+    /// Completion percentage (0-100).
+    pub percent: u8,
+    /// Partial output produced so far, if any.
+    #[serde(default)]
+    pub partial_output: Option<serde_json::Value>,
+    /// Estimated seconds until completion, if known.
+    #[serde(default)]
+    pub eta_secs: Option<u64>,
+}
+
+impl ProgressUpdate {
+    /// Creates a progress update carrying only a percentage.
+    pub fn percent(percent: u8) -> Self {
+        Self {
+            percent: percent.min(100),
+            partial_output: None,
+            eta_secs: None,
+        }
+    }
+}
+
+/// Handle through which an executor reports progress while it runs.
+///
+/// Reports are forwarded to the local `TaskManager` and to the requester of a
+/// remotely submitted task. Reporting never blocks the executor.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressReporter {
+    tx: Option<mpsc::UnboundedSender<ProgressUpdate>>,
+}
+
+impl ProgressReporter {
+    /// Creates a reporter and the receiver its updates are delivered to.
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<ProgressUpdate>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self { tx: Some(tx) }, rx)
+    }
+
+    /// Creates a reporter that discards all updates.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Reports a progress update.
+    pub fn report(&self, update: ProgressUpdate) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(update);
+        }
+    }
+
+    /// Reports a completion percentage.
+    pub fn percent(&self, percent: u8) {
+        self.report(ProgressUpdate::percent(percent));
+    }
+}
+
 /// Trait for executing tasks.
 #[async_trait::async_trait]
 pub trait TaskExecutor: Send + Sync {
     /// Executes a specific task payload and returns the result.
     async fn execute(&self, payload: &TaskPayload) -> anyhow::Result<serde_json::Value>;
+
+    /// Executes a payload while reporting progress.
+    ///
+    /// Long-running executors should override this; the default ignores the
+    /// reporter and calls `execute`.
+    async fn execute_with_progress(
+        &self,
+        payload: &TaskPayload,
+        progress: &ProgressReporter,
+    ) -> anyhow::Result<serde_json::Value> {
+        let _ = progress;
+        self.execute(payload).await
+    }
+
+    /// Executes a payload within an execution context.
+    ///
+    /// This is what the agent calls. Executors that can stop early should
+    /// override it and call `ExecutionContext::check` at safe points, returning
+    /// its `Interrupted` error; the default calls `execute_with_progress`.
+    async fn execute_with_context(
+        &self,
+        payload: &TaskPayload,
+        ctx: &ExecutionContext,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.execute_with_progress(payload, &ctx.progress).await
+    }
+
+    /// JSON Schema the payload `data` must match for this executor.
+    ///
+    /// Tasks whose payload does not match are failed on submission (see
+    /// `executors::schema`). The default accepts any payload.
+    fn input_schema(&self) -> Option<serde_json::Value> {
+        None
+    }
 }
 
 use futures::future::AbortHandle;
 
+/// A task executing locally.
+struct RunningTask {
+    abort: AbortHandle,
+    cancellation: CancellationToken,
+}
+
+/// Configuration for a `TaskManager`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskManagerConfig {
+    /// Interval after which a queued task is promoted by one priority level.
+    /// `None` disables aging and schedules in strict priority order.
+    pub aging_interval: Option<Duration>,
+}
+
+impl Default for TaskManagerConfig {
+    fn default() -> Self {
+        Self {
+            aging_interval: Some(DEFAULT_AGING_INTERVAL),
+        }
+    }
+}
+
+/// Outcome of adding a task that may repeat an earlier submission.
+#[derive(Debug, Clone)]
+pub enum SubmitOutcome {
+    /// The task was new and has been added.
+    Added(TaskId),
+    /// A task with the same ID or idempotency key already exists; nothing was added.
+    Duplicate(Box<Task>),
+}
+
+impl SubmitOutcome {
+    /// Returns the ID of the added or already existing task.
+    pub fn task_id(&self) -> TaskId {
+        match self {
+            SubmitOutcome::Added(id) => *id,
+            SubmitOutcome::Duplicate(task) => task.id,
+        }
+    }
+}
+
+/// Outcome of `TaskManager::recover`.
+#[derive(Debug, Clone, Default)]
+pub struct Recovery {
+    /// Number of tasks loaded from storage and the journal.
+    pub loaded: usize,
+    /// Interrupted local tasks queued again.
+    pub requeued: Vec<TaskId>,
+    /// Interrupted local tasks failed because their retries are exhausted.
+    pub failed: Vec<TaskId>,
+    /// Unfinished tasks assigned to peers, whose status should be asked for.
+    pub remote: Vec<TaskId>,
+}
+
 /// Manages tasks for an agent.
 #[derive(Clone)]
 pub struct TaskManager {
     tasks: Arc<RwLock<HashMap<TaskId, Task>>>,
-    running_tasks: Arc<RwLock<HashMap<TaskId, AbortHandle>>>,
+    /// Task IDs by idempotency key.
+    idempotency_keys: Arc<RwLock<HashMap<String, TaskId>>>,
+    running_tasks: Arc<RwLock<HashMap<TaskId, RunningTask>>>,
+    queue: Arc<RwLock<TaskQueue>>,
+    /// Wakes the executor loop when a task is queued.
+    queue_notify: Arc<Notify>,
+    /// Wakes group waiters when a task finishes.
+    finished_notify: Arc<Notify>,
     storage: Arc<dyn Storage>,
+    /// Bus task lifecycle events are published on (see [`events`]).
+    events: EventBus,
+    /// Cache of deterministic task results, if enabled.
+    result_cache: Option<Arc<ResultCache>>,
+    /// Write-ahead journal of task state transitions, if enabled.
+    journal: Option<Arc<TaskJournal>>,
+    /// Retention policy for finished tasks, if enabled.
+    retention: Option<Arc<RetentionPolicy>>,
+    /// Executors whose input schemas submitted payloads are checked against,
+    /// if enabled.
+    validation: Option<ExecutorRegistry>,
 }
 
 // Default implementation uses LocalStorage in current directory
@@ -230,55 +543,274 @@ impl Default for TaskManager {
         use crate::storage::local::LocalStorage;
         let storage =
             Arc::new(LocalStorage::new("data/tasks").expect("Failed to create default storage"));
-        Self {
-            tasks: Arc::new(RwLock::new(HashMap::new())),
-            running_tasks: Arc::new(RwLock::new(HashMap::new())),
-            storage,
-        }
+        Self::new(storage)
     }
 }
 
 impl TaskManager {
     /// Creates a new TaskManager with custom storage.
     pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self::with_config(storage, TaskManagerConfig::default())
+    }
+
+    /// Creates a new TaskManager with custom storage and configuration.
+    pub fn with_config(storage: Arc<dyn Storage>, config: TaskManagerConfig) -> Self {
         Self {
             tasks: Arc::new(RwLock::new(HashMap::new())),
+            idempotency_keys: Arc::new(RwLock::new(HashMap::new())),
             running_tasks: Arc::new(RwLock::new(HashMap::new())),
+            queue: Arc::new(RwLock::new(TaskQueue::new(config.aging_interval))),
+            queue_notify: Arc::new(Notify::new()),
+            finished_notify: Arc::new(Notify::new()),
             storage,
+            events: EventBus::new(),
+            result_cache: None,
+            journal: None,
+            retention: None,
+            validation: None,
         }
     }
 
+    /// Enables pruning of finished tasks by `compact` (see [`retention`]).
+    pub fn with_retention(mut self, policy: RetentionPolicy) -> Self {
+        self.retention = Some(Arc::new(policy));
+        self
+    }
+
+    /// Returns the retention policy, if enabled.
+    pub fn retention(&self) -> Option<&RetentionPolicy> {
+        self.retention.as_deref()
+    }
+
+    /// Enables the journal: every state transition is journaled before it is
+    /// written to storage, and replayed by `load_tasks` (see [`journal`]).
+    ///
+    /// [`journal`]: crate::agent::journal
+    pub fn with_journal(mut self, journal: TaskJournal) -> Self {
+        self.journal = Some(Arc::new(journal));
+        self
+    }
+
+    /// Enables the result cache: tasks whose payload has a cached result complete
+    /// on submission without running, and completed results are cached.
+    pub fn with_result_cache(mut self, cache: ResultCache) -> Self {
+        self.result_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Returns the result cache, if enabled.
+    pub fn result_cache(&self) -> Option<&ResultCache> {
+        self.result_cache.as_deref()
+    }
+
+    /// Enables payload validation: tasks whose payload does not match the input
+    /// schema of the executor that would run it fail on submission (see
+    /// [`schema`]). Tasks with `depends_on` are validated once their parents'
+    /// outputs are substituted into their payload.
+    ///
+    /// [`schema`]: crate::agent::executors::schema
+    pub fn with_payload_validation(mut self, executors: ExecutorRegistry) -> Self {
+        self.validation = Some(executors);
+        self
+    }
+
+    /// Publishes task lifecycle events on `bus` instead of a private bus.
+    pub fn with_event_bus(mut self, bus: EventBus) -> Self {
+        self.events = bus;
+        self
+    }
+
+    /// Returns the bus task lifecycle events are published on.
+    pub fn event_bus(&self) -> &EventBus {
+        &self.events
+    }
+
     /// Submits a new task.
     pub async fn submit_task(&self, description: impl Into<String>) -> TaskId {
         let task = Task::new(description);
         let id = task.id;
 
+        let mut tasks = self.tasks.write().await;
         // Persist to storage
-        if let Ok(json) = serde_json::to_vec(&task) {
-            let _ = self
-                .storage
-                .put(&id.to_string(), json, ConsistencyLevel::Strong)
-                .await;
-        }
+        self.persist(&task).await;
+        self.queue.write().await.push(&task);
+        tasks.insert(id, task.clone());
+        self.queue_notify.notify_one();
+        drop(tasks);
 
-        self.tasks.write().await.insert(id, task);
+        events::publish_submitted(&self.events, &task).await;
         id
     }
 
     /// Adds a fully formed task.
+    ///
+    /// Tasks with `depends_on` stay out of the scheduling queue until all of their
+    /// parents have completed (see [`dependencies`]). A task repeating an earlier
+    /// submission is not added again; the ID of the existing task is returned.
     pub async fn add_task(&self, task: Task) -> TaskId {
+        self.try_add_task(task).await.task_id()
+    }
+
+    /// Adds a task unless it repeats an earlier submission.
+    ///
+    /// A task is a duplicate if a task with the same ID (e.g. a re-delivered
+    /// request) or the same idempotency key is already known.
+    pub async fn try_add_task(&self, mut task: Task) -> SubmitOutcome {
         let id = task.id;
+        let mut tasks = self.tasks.write().await;
+        let mut keys = self.idempotency_keys.write().await;
+
+        let existing = tasks.get(&id).or_else(|| {
+            task.idempotency_key
+                .as_ref()
+                .and_then(|key| keys.get(key))
+                .and_then(|existing_id| tasks.get(existing_id))
+        });
+        if let Some(existing) = existing {
+            return SubmitOutcome::Duplicate(Box::new(existing.clone()));
+        }
+        if let Some(key) = &task.idempotency_key {
+            keys.insert(key.clone(), id);
+        }
+        drop(keys);
+
+        // Malformed tasks are rejected before they can be queued or dispatched.
+        // Payloads of dependent tasks are checked once parent outputs are
+        // substituted into them.
+        if task.status == TaskStatus::Queued && task.depends_on.is_empty() {
+            if let Err(e) = self.validate(&task) {
+                apply_status(&mut task, TaskStatus::Failed(e));
+            }
+        }
+
+        if task.status == TaskStatus::Queued && dependencies::has_cycle(&task, &tasks) {
+            apply_status(
+                &mut task,
+                TaskStatus::Failed("Dependency cycle detected".to_string()),
+            );
+        }
+
+        // Recurring tasks start at their first matching time
+        if let (TaskStatus::Queued, Some(spec), None) =
+            (&task.status, &task.recurrence, task.not_before)
+        {
+            match recurrence::next_occurrence(spec, SystemTime::now()) {
+                Ok(next) => task.not_before = next,
+                Err(e) => apply_status(&mut task, TaskStatus::Failed(e.to_string())),
+            }
+        }
+
+        // Tasks that could run right away are answered from the cache if possible
+        if let Some(result) = self.cached_result(&task).await {
+            apply_status(&mut task, TaskStatus::Completed(result));
+        }
 
         // Persist to storage
-        if let Ok(json) = serde_json::to_vec(&task) {
-            let _ = self
-                .storage
-                .put(&id.to_string(), json, ConsistencyLevel::Strong)
-                .await;
+        self.persist(&task).await;
+
+        self.queue.write().await.remove(&id);
+        tasks.insert(id, task);
+
+        // Release the task if it can run, and settle dependents that were
+        // submitted before this task
+        self.settle_dependencies(&mut tasks, id).await;
+        let cascaded = self.propagate_to_dependents(&mut tasks, id).await;
+
+        let added = tasks[&id].clone();
+        let mut transitions = statuses_of(&tasks, cascaded);
+        drop(tasks);
+
+        events::publish_submitted(&self.events, &added).await;
+        // Tasks can be settled right away, e.g. by a dependency cycle
+        transitions.insert(0, (id, added.status));
+        self.publish_transitions(transitions).await;
+        SubmitOutcome::Added(id)
+    }
+
+    /// Returns the known task a submission of `task` would duplicate, if any.
+    ///
+    /// Uses the same rules as [`TaskManager::try_add_task`].
+    pub async fn find_duplicate(&self, task: &Task) -> Option<Task> {
+        let tasks = self.tasks.read().await;
+        let keys = self.idempotency_keys.read().await;
+        tasks
+            .get(&task.id)
+            .or_else(|| {
+                task.idempotency_key
+                    .as_ref()
+                    .and_then(|key| keys.get(key))
+                    .and_then(|existing_id| tasks.get(existing_id))
+            })
+            .cloned()
+    }
+
+    /// Returns the number of unfinished tasks received from `requester`.
+    ///
+    /// Recurring templates are not counted, only their occurrences.
+    pub async fn active_requests_from(&self, requester: &str) -> usize {
+        self.tasks
+            .read()
+            .await
+            .values()
+            .filter(|task| {
+                task.requested_by.as_deref() == Some(requester)
+                    && !is_terminal(&task.status)
+                    && !task.is_recurring_template()
+            })
+            .count()
+    }
+
+    /// Adds a batch of tasks as a new group and returns the group's ID.
+    ///
+    /// Tasks repeating an earlier submission are not added and stay out of the
+    /// group (see [`TaskManager::try_add_task`]).
+    pub async fn add_group(&self, tasks: Vec<Task>) -> GroupId {
+        let group = Uuid::new_v4();
+        for mut task in tasks {
+            task.group = Some(group);
+            self.try_add_task(task).await;
         }
+        group
+    }
 
-        self.tasks.write().await.insert(id, task);
-        id
+    /// Returns the tasks of a group.
+    pub async fn group_tasks(&self, group: GroupId) -> Vec<Task> {
+        self.tasks
+            .read()
+            .await
+            .values()
+            .filter(|task| task.group == Some(group))
+            .cloned()
+            .collect()
+    }
+
+    /// Counts the tasks of a group per status.
+    ///
+    /// Recurring templates are left out, since they never finish while they
+    /// recur; their occurrences are counted instead.
+    pub async fn group_status(&self, group: GroupId) -> GroupStatus {
+        let tasks = self.tasks.read().await;
+        GroupStatus::of(
+            tasks
+                .values()
+                .filter(|task| task.group == Some(group) && !task.is_recurring_template()),
+        )
+    }
+
+    /// Waits until every task of a group has finished and returns its final
+    /// status.
+    pub async fn wait_for_group(&self, group: GroupId) -> GroupStatus {
+        loop {
+            let notified = self.finished_notify.notified();
+            tokio::pin!(notified);
+            // Register before checking, so a task finishing meanwhile is not missed
+            notified.as_mut().enable();
+            let status = self.group_status(group).await;
+            if status.is_finished() {
+                return status;
+            }
+            notified.await;
+        }
     }
 
     /// Assigns a task to a peer.
@@ -287,84 +819,350 @@ impl TaskManager {
         if let Some(task) = tasks.get_mut(&id) {
             task.assigned_to = Some(peer_id);
             // Persist
-            if let Ok(json) = serde_json::to_vec(&task) {
-                let _ = self
-                    .storage
-                    .put(&id.to_string(), json, ConsistencyLevel::Strong)
-                    .await;
-            }
+            self.persist(task).await;
             Ok(())
         } else {
             Err(anyhow::anyhow!("Task not found"))
         }
     }
 
+    /// Records the peers a redundant task was dispatched to.
+    pub async fn set_replicas(&self, id: TaskId, peers: Vec<String>) -> anyhow::Result<()> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks
+            .get_mut(&id)
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+        task.replicas = peers;
+        self.persist(task).await;
+        Ok(())
+    }
+
+    /// Records the shard tasks a sharded task was split into.
+    pub async fn set_shards(&self, id: TaskId, shards: Vec<TaskId>) -> anyhow::Result<()> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks
+            .get_mut(&id)
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+        task.shards = shards;
+        self.persist(task).await;
+        Ok(())
+    }
+
+    /// Excludes a peer from future dispatches of a task.
+    pub async fn exclude_peer(&self, id: TaskId, peer_id: String) -> anyhow::Result<()> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks
+            .get_mut(&id)
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+        if !task.excluded_peers.contains(&peer_id) {
+            task.excluded_peers.push(peer_id);
+            self.persist(task).await;
+        }
+        Ok(())
+    }
+
+    /// Counts unfinished tasks per assigned peer.
+    pub async fn active_assignments(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for task in self.tasks.read().await.values() {
+            if let Some(peer) = &task.assigned_to {
+                if !is_terminal(&task.status) {
+                    *counts.entry(peer.clone()).or_insert(0) += 1;
+                }
+            }
+        }
+        counts
+    }
+
     /// Updates the status of a task.
+    ///
+    /// Completing a task releases its dependents; failing, cancelling or finally
+    /// timing out a task cascades to them.
     pub async fn update_status(&self, id: TaskId, status: TaskStatus) -> anyhow::Result<()> {
         let mut tasks = self.tasks.write().await;
-        if let Some(task) = tasks.get_mut(&id) {
-            // Update timestamps based on status transition
-            match (&task.status, &status) {
-                (TaskStatus::Queued, TaskStatus::Running) => {
-                    task.started_at = Some(SystemTime::now());
+        let task = tasks
+            .get_mut(&id)
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+
+        if is_terminal(&status) {
+            // Clean up running tasks
+            self.running_tasks.write().await.remove(&id);
+        }
+        let newly_completed = match (&task.status, &status, &task.payload) {
+            (TaskStatus::Completed(_), _, _) => None,
+            (_, TaskStatus::Completed(result), Some(payload)) => {
+                Some((payload.clone(), result.clone()))
+            }
+            _ => None,
+        };
+        apply_status(task, status.clone());
+
+        // Keep the scheduling queue in sync with the status
+        self.queue.write().await.remove(&id);
+
+        // Update in storage
+        self.persist(task).await;
+
+        self.settle_dependencies(&mut tasks, id).await;
+        let cascaded = self.propagate_to_dependents(&mut tasks, id).await;
+        let mut transitions = statuses_of(&tasks, cascaded);
+        drop(tasks);
+
+        if let (Some(cache), Some((payload, result))) = (&self.result_cache, newly_completed) {
+            cache.put(&payload, &result).await;
+        }
+        transitions.insert(0, (id, status));
+        self.publish_transitions(transitions).await;
+        Ok(())
+    }
+
+    /// Checks the task's payload against the input schema of its executor, if
+    /// payload validation is enabled.
+    fn validate(&self, task: &Task) -> Result<(), String> {
+        match (&self.validation, &task.payload) {
+            (Some(executors), Some(payload)) => {
+                executors.validate(payload).map_err(|e| e.to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Looks up the cached result of a task that is ready to run.
+    async fn cached_result(&self, task: &Task) -> Option<serde_json::Value> {
+        let cache = self.result_cache.as_ref()?;
+        let ready = task.status == TaskStatus::Queued
+            && task.depends_on.is_empty()
+            && task.not_before.is_none()
+            && task.recurrence.is_none()
+            // Redundant execution asks for independently computed results
+            && task.redundancy.is_none();
+        if !ready {
+            return None;
+        }
+        cache.get(task.payload.as_ref()?).await
+    }
+
+    /// Publishes the lifecycle event of every status change, in order.
+    ///
+    /// Called after the task lock is released, so handlers may query the manager.
+    async fn publish_transitions(&self, transitions: Vec<(TaskId, TaskStatus)>) {
+        if transitions.iter().any(|(_, status)| is_terminal(status)) {
+            self.finished_notify.notify_waiters();
+        }
+        for (id, status) in transitions {
+            events::publish_status(&self.events, id, &status).await;
+        }
+    }
+
+    /// Returns true if every dependency of the task has completed.
+    pub async fn dependencies_satisfied(&self, id: TaskId) -> bool {
+        let tasks = self.tasks.read().await;
+        tasks.get(&id).is_some_and(|task| {
+            dependencies::dependency_state(task, &tasks) == DependencyState::Ready
+        })
+    }
+
+    /// Re-evaluates a queued task against its dependencies.
+    ///
+    /// Ready tasks get parent outputs substituted into their payload and enter the
+    /// scheduling queue; they fail instead if the resolved payload is invalid.
+    /// Tasks whose parents failed or were cancelled take that status themselves.
+    /// Returns true if the task status changed.
+    async fn settle_dependencies(&self, tasks: &mut HashMap<TaskId, Task>, id: TaskId) -> bool {
+        let Some(task) = tasks.get(&id) else {
+            return false;
+        };
+        if task.status != TaskStatus::Queued || task.is_scheduled(SystemTime::now()) {
+            return false;
+        }
+
+        if task.depends_on.is_empty() {
+            self.queue.write().await.push(task);
+            self.queue_notify.notify_one();
+            return false;
+        }
+
+        let outcome = match dependencies::dependency_state(task, tasks) {
+            DependencyState::Ready => {
+                let mut released = task.clone();
+                match dependencies::resolve_payload(&mut released, tasks) {
+                    Ok(()) => self
+                        .validate(&released)
+                        .map(|()| released)
+                        .map_err(TaskStatus::Failed),
+                    Err(e) => Err(TaskStatus::Failed(format!(
+                        "Failed to resolve dependency output: {}",
+                        e
+                    ))),
                 }
-                (_, TaskStatus::Completed(_))
-                | (_, TaskStatus::Failed(_))
-                | (_, TaskStatus::Cancelled)
-                | (_, TaskStatus::Timeout) => {
-                    task.completed_at = Some(SystemTime::now());
-
-                    // Calculate final execution time
-                    if let Some(started) = task.started_at {
-                        if let Ok(duration) = SystemTime::now().duration_since(started) {
-                            task.execution_time_ms = Some(duration.as_millis() as u64);
-                        }
-                    }
+            }
+            DependencyState::Blocked => return false,
+            DependencyState::Failed(reason) => Err(TaskStatus::Failed(reason)),
+            DependencyState::Cancelled(_) => Err(TaskStatus::Cancelled),
+        };
+
+        match outcome {
+            Ok(released) => {
+                self.persist(&released).await;
+                self.queue.write().await.push(&released);
+                tasks.insert(id, released);
+                self.queue_notify.notify_one();
+                false
+            }
+            Err(status) => {
+                let Some(task) = tasks.get_mut(&id) else {
+                    return false;
+                };
+                apply_status(task, status);
+                self.persist(task).await;
+                true
+            }
+        }
+    }
 
-                    // Calculate result size for completed tasks
-                    if let TaskStatus::Completed(ref result) = status {
-                        let result_str = result.to_string();
-                        task.result_size_bytes = Some(result_str.len());
-                    }
+    /// Releases scheduled tasks whose `not_before` time has passed.
+    ///
+    /// Delayed tasks enter the scheduling queue (subject to their dependencies).
+    /// Recurring tasks submit a new occurrence and move on to their next due time;
+    /// occurrences missed while the node was down are collapsed into one.
+    /// Returns the IDs of the tasks released or submitted.
+    pub async fn release_due_tasks(&self) -> Vec<TaskId> {
+        let now = SystemTime::now();
+        let mut tasks = self.tasks.write().await;
+        let due: Vec<TaskId> = tasks
+            .values()
+            .filter(|t| t.status == TaskStatus::Queued && t.not_before.is_some_and(|nb| nb <= now))
+            .map(|t| t.id)
+            .collect();
 
-                    // Store error details for failed tasks
-                    if let TaskStatus::Failed(ref reason) = status {
-                        task.error_reason = Some(reason.clone());
-                        task.error_details = Some(format!("Task failed: {}", reason));
+        let mut released = Vec::new();
+        let mut submitted = Vec::new();
+        let mut transitions = Vec::new();
+        for id in due {
+            let Some(task) = tasks.get_mut(&id) else {
+                continue;
+            };
+            let Some(spec) = task.recurrence.clone() else {
+                if !self.queue.read().await.contains(&id) {
+                    self.settle_dependencies(&mut tasks, id).await;
+                    if self.queue.read().await.contains(&id) {
+                        released.push(id);
                     }
-
-                    // Clean up running tasks
-                    self.running_tasks.write().await.remove(&id);
                 }
-                _ => {}
+                continue;
+            };
+
+            let occurrence = task.new_occurrence();
+            match recurrence::next_occurrence(&spec, now) {
+                Ok(Some(next)) => task.not_before = Some(next),
+                // The schedule has no further occurrences
+                Ok(None) => {
+                    task.not_before = None;
+                    apply_status(
+                        task,
+                        TaskStatus::Completed(serde_json::json!({"recurrence": "finished"})),
+                    );
+                }
+                Err(e) => apply_status(task, TaskStatus::Failed(e.to_string())),
+            }
+            self.persist(task).await;
+            if task.status != TaskStatus::Queued {
+                transitions.push((id, task.status.clone()));
             }
 
-            task.status = status;
+            let occurrence_id = occurrence.id;
+            self.persist(&occurrence).await;
+            submitted.push(occurrence.clone());
+            tasks.insert(occurrence_id, occurrence);
+            self.settle_dependencies(&mut tasks, occurrence_id).await;
+            released.push(occurrence_id);
+        }
+        drop(tasks);
 
-            // Update in storage
-            if let Ok(json) = serde_json::to_vec(&task) {
-                let _ = self
-                    .storage
-                    .put(&id.to_string(), json, ConsistencyLevel::Strong)
-                    .await;
+        for occurrence in &submitted {
+            events::publish_submitted(&self.events, occurrence).await;
+        }
+        self.publish_transitions(transitions).await;
+        released
+    }
+
+    /// Settles all waiting (transitive) dependents of a task that changed status.
+    ///
+    /// Returns the dependents whose status changed as a result.
+    async fn propagate_to_dependents(
+        &self,
+        tasks: &mut HashMap<TaskId, Task>,
+        id: TaskId,
+    ) -> Vec<TaskId> {
+        let mut changed = vec![id];
+        let mut cascaded = Vec::new();
+
+        while let Some(parent_id) = changed.pop() {
+            if !tasks
+                .get(&parent_id)
+                .is_some_and(|t| is_terminal(&t.status))
+            {
+                continue;
+            }
+            for child_id in dependencies::dependents_of(parent_id, tasks) {
+                if self.queue.read().await.contains(&child_id) {
+                    continue;
+                }
+                if self.settle_dependencies(tasks, child_id).await {
+                    changed.push(child_id);
+                    cascaded.push(child_id);
+                }
             }
+        }
+        cascaded
+    }
 
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("Task not found"))
+    /// Records a task state change: journals it, if enabled, then writes it to storage.
+    async fn persist(&self, task: &Task) {
+        if let Some(journal) = &self.journal {
+            if let Err(e) = journal.append(task) {
+                tracing::error!("Failed to journal task {}: {}", task.id, e);
+            }
+        }
+        self.store(task).await;
+    }
+
+    /// Writes a task to storage.
+    async fn store(&self, task: &Task) {
+        if let Ok(json) = serde_json::to_vec(task) {
+            let _ = self
+                .storage
+                .put(&task.id.to_string(), json, ConsistencyLevel::Strong)
+                .await;
         }
     }
 
-    /// Registers a running task handle.
-    pub async fn register_running_task(&self, id: TaskId, handle: AbortHandle) {
-        self.running_tasks.write().await.insert(id, handle);
+    /// Registers a running task, with the token that asks it to stop and the
+    /// handle that aborts it.
+    pub async fn register_running_task(
+        &self,
+        id: TaskId,
+        handle: AbortHandle,
+        cancellation: CancellationToken,
+    ) {
+        let running = RunningTask {
+            abort: handle,
+            cancellation,
+        };
+        self.running_tasks.write().await.insert(id, running);
     }
 
     /// Cancels a task.
+    ///
+    /// A running task is asked to stop through its cancellation token, and is
+    /// aborted if still running after `CANCELLATION_GRACE_PERIOD`.
     pub async fn cancel_task(&self, id: TaskId) -> anyhow::Result<()> {
-        // First check if task exists and abort if running
-        if let Some(handle) = self.running_tasks.write().await.remove(&id) {
-            handle.abort();
+        if let Some(running) = self.running_tasks.write().await.remove(&id) {
+            running.cancellation.cancel();
+            tokio::spawn(async move {
+                tokio::time::sleep(CANCELLATION_GRACE_PERIOD).await;
+                running.abort.abort();
+            });
         }
 
         self.update_status(id, TaskStatus::Cancelled).await
@@ -383,13 +1181,68 @@ impl TaskManager {
                     .put(&id.to_string(), json, ConsistencyLevel::Strong)
                     .await;
             }
+            drop(tasks);
 
+            events::publish_progress(&self.events, id, ProgressUpdate::percent(progress_percent))
+                .await;
             Ok(())
         } else {
             Err(anyhow::anyhow!("Task not found"))
         }
     }
 
+    /// Applies a progress report from the task's executor.
+    ///
+    /// Reports arriving after the task finished are ignored, since progress and
+    /// the final response may be delivered out of order.
+    pub async fn report_progress(&self, id: TaskId, update: ProgressUpdate) -> anyhow::Result<()> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks
+            .get_mut(&id)
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+        if is_terminal(&task.status) {
+            return Ok(());
+        }
+
+        task.progress_percent = Some(update.percent.min(100));
+        if update.partial_output.is_some() {
+            task.partial_output = update.partial_output.clone();
+        }
+        task.estimated_completion_at = update
+            .eta_secs
+            .map(|secs| SystemTime::now() + Duration::from_secs(secs));
+
+        // Progress is not a state transition, so it is not journaled
+        self.store(task).await;
+        drop(tasks);
+
+        events::publish_progress(&self.events, id, update).await;
+        Ok(())
+    }
+
+    /// Records a checkpoint of an unfinished task, unless the task already has
+    /// a more recent one.
+    ///
+    /// Returns true if the checkpoint was recorded.
+    pub async fn record_checkpoint(
+        &self,
+        id: TaskId,
+        checkpoint: Checkpoint,
+    ) -> anyhow::Result<bool> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks
+            .get_mut(&id)
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+        if is_terminal(&task.status) || !checkpoint.supersedes(task.checkpoint.as_ref()) {
+            return Ok(false);
+        }
+
+        task.checkpoint = Some(checkpoint);
+        // Like progress, checkpoints are not state transitions
+        self.store(task).await;
+        Ok(true)
+    }
+
     /// Updates task retry count.
     pub async fn update_retry_count(&self, id: TaskId, count: u32) -> anyhow::Result<()> {
         let mut tasks = self.tasks.write().await;
@@ -438,7 +1291,19 @@ impl TaskManager {
         self.tasks.read().await.values().cloned().collect()
     }
 
+    /// Returns one page of the tasks matching `query` (see [`query`]).
+    ///
+    /// Only the tasks of the page are cloned.
+    ///
+    /// [`query`]: crate::agent::query
+    pub async fn query_tasks(&self, query: &TaskQuery) -> TaskPage {
+        query.run(self.tasks.read().await.values())
+    }
+
     /// Loads all tasks from storage into memory
+    ///
+    /// The scheduling queue is rebuilt from the loaded tasks, so queued tasks keep
+    /// their original priority and age across restarts.
     pub async fn load_tasks(&self) -> anyhow::Result<usize> {
         let keys = self.storage.list().await?;
         let mut count = 0;
@@ -452,21 +1317,179 @@ impl TaskManager {
                 }
             }
         }
+
+        // The journal holds transitions that may not have reached storage
+        if let Some(journal) = &self.journal {
+            let mut replayed = HashMap::new();
+            for entry in journal.replay()? {
+                replayed.insert(entry.task.id, entry.task);
+            }
+            for (id, task) in replayed {
+                self.store(&task).await;
+                if tasks.insert(id, task).is_none() {
+                    count += 1;
+                }
+            }
+            journal.checkpoint()?;
+        }
+
+        let mut idempotency_keys = self.idempotency_keys.write().await;
+        for task in tasks.values() {
+            if let Some(key) = &task.idempotency_key {
+                idempotency_keys.insert(key.clone(), task.id);
+            }
+        }
+        drop(idempotency_keys);
+
+        self.queue.write().await.clear();
+        let queued: Vec<TaskId> = tasks
+            .values()
+            .filter(|t| t.status == TaskStatus::Queued)
+            .map(|t| t.id)
+            .collect();
+        for id in queued {
+            // Also catches dependents whose parents finished right before a restart
+            if self.settle_dependencies(&mut tasks, id).await {
+                self.propagate_to_dependents(&mut tasks, id).await;
+            }
+        }
+
         Ok(count)
     }
 
+    /// Prunes finished tasks according to the retention policy, archiving them
+    /// first if configured. Does nothing without a policy.
+    pub async fn compact(&self) -> anyhow::Result<CompactionReport> {
+        let Some(policy) = &self.retention else {
+            return Ok(CompactionReport::default());
+        };
+        let mut tasks = self.tasks.write().await;
+
+        // Finished tasks that unfinished ones still need
+        let mut needed = HashSet::new();
+        for task in tasks.values().filter(|t| !is_terminal(&t.status)) {
+            needed.extend(task.depends_on.iter().copied());
+            needed.extend(task.shards.iter().copied());
+        }
+        let candidates = tasks
+            .values()
+            .filter(|t| is_terminal(&t.status) && !needed.contains(&t.id))
+            .collect();
+        let victims = policy.select(candidates, SystemTime::now());
+        if victims.is_empty() {
+            return Ok(CompactionReport::default());
+        }
+
+        let archive = match &policy.archive_dir {
+            Some(dir) => Some(retention::archive(dir, &victims)?),
+            None => None,
+        };
+        let pruned: Vec<TaskId> = victims.iter().map(|t| t.id).collect();
+
+        // A pruned task no longer blocks resubmission under its idempotency key
+        let mut keys = self.idempotency_keys.write().await;
+        for id in &pruned {
+            if let Some(key) = tasks.remove(id).and_then(|t| t.idempotency_key) {
+                if keys.get(&key) == Some(id) {
+                    keys.remove(&key);
+                }
+            }
+            let _ = self
+                .storage
+                .delete(&id.to_string(), ConsistencyLevel::Strong)
+                .await;
+        }
+        drop(keys);
+
+        // Replaying the journal would bring pruned tasks back. Transitions are
+        // only persisted under the task lock, so storage is up to date here.
+        if let Some(journal) = &self.journal {
+            journal.checkpoint()?;
+        }
+
+        Ok(CompactionReport {
+            pruned: pruned.len(),
+            archive,
+        })
+    }
+
+    /// Loads persisted tasks and settles the ones a crash interrupted.
+    ///
+    /// Tasks that were running locally are queued again as a retry, or failed
+    /// once their retries are exhausted. Unfinished tasks assigned to peers are
+    /// left as they are and returned in [`Recovery::remote`], since only the
+    /// peers know how far they got.
+    pub async fn recover(&self) -> anyhow::Result<Recovery> {
+        let mut recovery = Recovery {
+            loaded: self.load_tasks().await?,
+            ..Default::default()
+        };
+
+        for task in self.list_tasks().await {
+            let remote = task.assigned_to.is_some() || !task.replicas.is_empty();
+            match task.status {
+                TaskStatus::Queued | TaskStatus::Running if remote => {
+                    recovery.remote.push(task.id);
+                }
+                // Sharded tasks settle through their shards
+                TaskStatus::Running if task.shards.is_empty() => {
+                    if task.retry_count < task.max_retries {
+                        self.update_retry_count(task.id, task.retry_count + 1)
+                            .await?;
+                        self.update_status(task.id, TaskStatus::Queued).await?;
+                        recovery.requeued.push(task.id);
+                    } else {
+                        let reason = "Interrupted by restart after exhausting retries";
+                        self.update_status(task.id, TaskStatus::Failed(reason.to_string()))
+                            .await?;
+                        recovery.failed.push(task.id);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(recovery)
+    }
+
     /// Gets the next pending task based on priority.
+    ///
+    /// Tasks are ordered by `TaskPriority` and then by `created_at`, with waiting
+    /// tasks promoted over time according to the configured aging interval.
     pub async fn get_next_pending_task(&self) -> Option<Task> {
         let tasks = self.tasks.read().await;
-        let mut pending_tasks: Vec<&Task> = tasks
-            .values()
-            .filter(|t| t.status == TaskStatus::Queued)
-            .collect();
+        let id = self.queue.read().await.peek()?;
+        tasks.get(&id).cloned()
+    }
 
-        // Sort by priority (Critical > High > Normal > Low)
-        pending_tasks.sort_by(|a, b| b.priority.cmp(&a.priority));
+    /// Gets the highest-priority pending task accepted by `filter`.
+    ///
+    /// Used by the worker pool to skip tasks whose type is at its concurrency limit
+    /// without blocking the tasks queued behind them.
+    pub async fn get_next_pending_task_where<F>(&self, filter: F) -> Option<Task>
+    where
+        F: Fn(&Task) -> bool,
+    {
+        let tasks = self.tasks.read().await;
+        let queue = self.queue.read().await;
+        let next = queue
+            .iter()
+            .filter_map(|id| tasks.get(&id))
+            .find(|task| filter(task))
+            .cloned();
+        next
+    }
 
-        pending_tasks.first().map(|t| (*t).clone())
+    /// Returns the notifier signalled whenever a task enters the queue.
+    ///
+    /// A notification is stored if nobody is waiting, so a task queued between
+    /// checking the queue and awaiting `notified()` is not missed.
+    pub fn queue_notifier(&self) -> Arc<Notify> {
+        self.queue_notify.clone()
+    }
+
+    /// Returns the number of tasks waiting in the scheduling queue.
+    pub async fn queued_count(&self) -> usize {
+        self.queue.read().await.len()
     }
 
     /// Gets detailed task status information.
@@ -476,6 +1499,60 @@ impl TaskManager {
     }
 }
 
+/// Returns true for statuses after which a task no longer runs.
+fn is_terminal(status: &TaskStatus) -> bool {
+    matches!(
+        status,
+        TaskStatus::Completed(_)
+            | TaskStatus::Failed(_)
+            | TaskStatus::Cancelled
+            | TaskStatus::Timeout
+    )
+}
+
+/// Returns the current status of each of the given tasks.
+fn statuses_of(tasks: &HashMap<TaskId, Task>, ids: Vec<TaskId>) -> Vec<(TaskId, TaskStatus)> {
+    ids.into_iter()
+        .filter_map(|id| tasks.get(&id).map(|t| (id, t.status.clone())))
+        .collect()
+}
+
+/// Sets a task's status, updating timestamps and result/error details.
+fn apply_status(task: &mut Task, status: TaskStatus) {
+    match (&task.status, &status) {
+        (TaskStatus::Queued, TaskStatus::Running) => {
+            task.started_at = Some(SystemTime::now());
+        }
+        (_, status) if is_terminal(status) => {
+            task.completed_at = Some(SystemTime::now());
+
+            // Calculate final execution time
+            if let Some(started) = task.started_at {
+                if let Ok(duration) = SystemTime::now().duration_since(started) {
+                    task.execution_time_ms = Some(duration.as_millis() as u64);
+                }
+            }
+
+            // Calculate result size for completed tasks; their checkpoint is
+            // no longer needed
+            if let TaskStatus::Completed(result) = status {
+                let result_str = result.to_string();
+                task.result_size_bytes = Some(result_str.len());
+                task.checkpoint = None;
+            }
+
+            // Store error details for failed tasks
+            if let TaskStatus::Failed(reason) = status {
+                task.error_reason = Some(reason.clone());
+                task.error_details = Some(format!("Task failed: {}", reason));
+            }
+        }
+        _ => {}
+    }
+
+    task.status = status;
+}
+
 /// Detailed task status information for querying.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskStatusInfo {
@@ -495,6 +1572,8 @@ pub struct TaskStatusInfo {
     pub result_size_bytes: Option<usize>,
     /// Result preview (first 100 chars, for completed tasks).
     pub result_preview: Option<String>,
+    /// Partial output reported so far (for running tasks).
+    pub partial_output: Option<serde_json::Value>,
     /// Error reason (for failed tasks).
     pub error_reason: Option<String>,
     /// Error details (for failed tasks).
@@ -515,6 +1594,7 @@ impl TaskStatusInfo {
             completion_timestamp: task.completed_at,
             result_size_bytes: task.result_size_bytes,
             result_preview: task.result_preview(),
+            partial_output: task.partial_output.clone(),
             error_reason: task.error_reason.clone(),
             error_details: task.error_details.clone(),
             timestamp_failed: match &task.status {
@@ -824,4 +1904,545 @@ mod tests {
         let expected_size = result.to_string().len();
         assert_eq!(task.result_size_bytes, Some(expected_size));
     }
+
+    fn temp_manager(dir: &std::path::Path, config: TaskManagerConfig) -> TaskManager {
+        use crate::storage::local::LocalStorage;
+        TaskManager::with_config(Arc::new(LocalStorage::new(dir).unwrap()), config)
+    }
+
+    fn prioritized_task(priority: TaskPriority) -> Task {
+        let mut task = Task::new("Prioritized task");
+        task.priority = priority;
+        task
+    }
+
+    #[tokio::test]
+    async fn test_next_pending_task_respects_priority() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let low = manager.add_task(prioritized_task(TaskPriority::Low)).await;
+        let critical = manager
+            .add_task(prioritized_task(TaskPriority::Critical))
+            .await;
+        let high = manager.add_task(prioritized_task(TaskPriority::High)).await;
+
+        for expected in [critical, high, low] {
+            let next = manager.get_next_pending_task().await.unwrap();
+            assert_eq!(next.id, expected);
+            manager
+                .update_status(next.id, TaskStatus::Running)
+                .await
+                .unwrap();
+        }
+        assert!(manager.get_next_pending_task().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_and_requeued_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let id = manager.add_task(prioritized_task(TaskPriority::High)).await;
+        manager.cancel_task(id).await.unwrap();
+        assert_eq!(manager.queued_count().await, 0);
+
+        manager.update_status(id, TaskStatus::Queued).await.unwrap();
+        assert_eq!(manager.get_next_pending_task().await.unwrap().id, id);
+    }
+
+    #[tokio::test]
+    async fn test_load_tasks_rebuilds_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TaskManagerConfig {
+            aging_interval: Some(Duration::from_secs(1)),
+        };
+
+        let manager = temp_manager(dir.path(), config.clone());
+        let mut old_low = prioritized_task(TaskPriority::Low);
+        old_low.created_at = SystemTime::now() - Duration::from_secs(60);
+        let old_low = manager.add_task(old_low).await;
+        let running = manager
+            .add_task(prioritized_task(TaskPriority::Critical))
+            .await;
+        manager
+            .update_status(running, TaskStatus::Running)
+            .await
+            .unwrap();
+        manager.add_task(prioritized_task(TaskPriority::High)).await;
+
+        // Simulate a restart with a fresh manager over the same storage
+        let restarted = temp_manager(dir.path(), config);
+        assert_eq!(restarted.load_tasks().await.unwrap(), 3);
+        assert_eq!(restarted.queued_count().await, 2);
+        assert_eq!(restarted.get_next_pending_task().await.unwrap().id, old_low);
+    }
+
+    fn text_task(data: serde_json::Value) -> Task {
+        Task::with_payload(
+            TaskPriority::Normal,
+            TaskPayload {
+                task_type: TaskType::TextProcessing,
+                data,
+                parameters: HashMap::new(),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_dependent_task_released_with_parent_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let parent = manager
+            .add_task(text_task(serde_json::json!({"text": "hello"})))
+            .await;
+        let mut child = text_task(serde_json::json!({
+            "operation": "reverse",
+            "text": {"$from_task": parent.to_string(), "$pointer": "/text"}
+        }));
+        child.depends_on = vec![parent];
+        let child = manager.add_task(child).await;
+
+        // Only the parent is runnable while it is pending
+        assert_eq!(manager.queued_count().await, 1);
+        assert!(!manager.dependencies_satisfied(child).await);
+        manager
+            .update_status(parent, TaskStatus::Running)
+            .await
+            .unwrap();
+        assert!(manager.get_next_pending_task().await.is_none());
+
+        manager
+            .update_status(
+                parent,
+                TaskStatus::Completed(serde_json::json!({"text": "olleh"})),
+            )
+            .await
+            .unwrap();
+
+        let next = manager.get_next_pending_task().await.unwrap();
+        assert_eq!(next.id, child);
+        assert_eq!(next.payload.unwrap().data["text"], "olleh");
+    }
+
+    #[tokio::test]
+    async fn test_dependency_failure_cascades() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let root = manager.add_task(Task::new("root")).await;
+        let mut child = Task::new("child");
+        child.depends_on = vec![root];
+        let child = manager.add_task(child).await;
+        let mut grandchild = Task::new("grandchild");
+        grandchild.depends_on = vec![child];
+        let grandchild = manager.add_task(grandchild).await;
+
+        manager
+            .update_status(root, TaskStatus::Failed("boom".to_string()))
+            .await
+            .unwrap();
+
+        for id in [child, grandchild] {
+            let task = manager.get_task(id).await.unwrap();
+            assert!(matches!(task.status, TaskStatus::Failed(_)));
+            assert!(task.completed_at.is_some());
+        }
+        assert_eq!(manager.queued_count().await, 0);
+
+        // Cancellation cascades as cancellation
+        let other = manager.add_task(Task::new("other")).await;
+        let mut waiting = Task::new("waiting");
+        waiting.depends_on = vec![other];
+        let waiting = manager.add_task(waiting).await;
+        manager.cancel_task(other).await.unwrap();
+        assert_eq!(
+            manager.get_task(waiting).await.unwrap().status,
+            TaskStatus::Cancelled
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dependency_cycle_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let mut a = Task::new("a");
+        let mut b = Task::new("b");
+        b.depends_on = vec![a.id];
+        a.depends_on = vec![b.id];
+        let b = manager.add_task(b).await;
+        let a = manager.add_task(a).await;
+
+        // Adding `a` closes the cycle: it fails and the failure cascades to `b`
+        for id in [a, b] {
+            assert!(matches!(
+                manager.get_task(id).await.unwrap().status,
+                TaskStatus::Failed(_)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_tasks_releases_ready_dependents() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let parent = manager.add_task(Task::new("parent")).await;
+        let mut child = Task::new("child");
+        child.depends_on = vec![parent];
+        let child = manager.add_task(child).await;
+        manager
+            .update_status(parent, TaskStatus::Running)
+            .await
+            .unwrap();
+
+        let restarted = temp_manager(dir.path(), TaskManagerConfig::default());
+        restarted.load_tasks().await.unwrap();
+        assert_eq!(restarted.queued_count().await, 0);
+
+        restarted
+            .update_status(parent, TaskStatus::Completed(serde_json::json!(null)))
+            .await
+            .unwrap();
+        assert_eq!(restarted.get_next_pending_task().await.unwrap().id, child);
+    }
+
+    #[tokio::test]
+    async fn test_report_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+        let id = manager.add_task(Task::new("progress")).await;
+        manager
+            .update_status(id, TaskStatus::Running)
+            .await
+            .unwrap();
+
+        manager
+            .report_progress(
+                id,
+                ProgressUpdate {
+                    percent: 40,
+                    partial_output: Some(serde_json::json!({"rows": 40})),
+                    eta_secs: Some(90),
+                },
+            )
+            .await
+            .unwrap();
+
+        let info = manager.get_task_status(id).await.unwrap();
+        assert_eq!(info.progress_percent, Some(40));
+        assert_eq!(info.partial_output, Some(serde_json::json!({"rows": 40})));
+        // The reported ETA wins over the extrapolated estimate
+        let eta = info.estimated_completion_secs.unwrap();
+        assert!((89..=90).contains(&eta));
+
+        // Late reports do not touch finished tasks
+        manager
+            .update_status(id, TaskStatus::Completed(serde_json::json!(null)))
+            .await
+            .unwrap();
+        manager
+            .report_progress(id, ProgressUpdate::percent(60))
+            .await
+            .unwrap();
+        assert_eq!(
+            manager.get_task(id).await.unwrap().progress_percent,
+            Some(40)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delayed_task_released_when_due() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let mut task = Task::new("delayed");
+        task.not_before = Some(SystemTime::now() + Duration::from_millis(200));
+        let id = manager.add_task(task).await;
+
+        assert!(manager.get_next_pending_task().await.is_none());
+        assert!(manager.release_due_tasks().await.is_empty());
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(manager.release_due_tasks().await, vec![id]);
+        assert_eq!(manager.get_next_pending_task().await.unwrap().id, id);
+    }
+
+    #[tokio::test]
+    async fn test_recurring_template_neither_blocks_group_nor_counts_as_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let mut template = Task::new("recurring");
+        template.recurrence = Some("0 0 * * *".to_string());
+        template.requested_by = Some("peer".to_string());
+        let once = Task::new("once");
+        let once_id = once.id;
+        let group = manager.add_group(vec![template, once]).await;
+        assert_eq!(manager.active_requests_from("peer").await, 0);
+
+        manager
+            .update_status(once_id, TaskStatus::Completed(serde_json::json!(1)))
+            .await
+            .unwrap();
+        let status = tokio::time::timeout(Duration::from_secs(1), manager.wait_for_group(group))
+            .await
+            .unwrap();
+        assert_eq!(status.total, 1);
+        assert_eq!(status.completed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_recurring_task_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let mut template = text_task(serde_json::json!({"operation": "embed"}));
+        template.recurrence = Some("* * * * * *".to_string());
+        let template_id = manager.add_task(template).await;
+
+        let scheduled = manager.get_task(template_id).await.unwrap();
+        assert!(scheduled.not_before.is_some());
+        assert_eq!(manager.queued_count().await, 0);
+
+        // A restarted node picks the schedule up from storage
+        let restarted = temp_manager(dir.path(), TaskManagerConfig::default());
+        restarted.load_tasks().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+
+        let released = restarted.release_due_tasks().await;
+        assert_eq!(released.len(), 1);
+        let occurrence = restarted.get_next_pending_task().await.unwrap();
+        assert_eq!(occurrence.id, released[0]);
+        assert_eq!(occurrence.recurrence_of, Some(template_id));
+        assert!(occurrence.recurrence.is_none());
+
+        // The template stays scheduled for its next occurrence
+        let template = restarted.get_task(template_id).await.unwrap();
+        assert_eq!(template.status, TaskStatus::Queued);
+        assert!(template.not_before.unwrap() > scheduled.not_before.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_submissions_are_not_added() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let mut task = Task::new("idempotent");
+        task.idempotency_key = Some("order-42".to_string());
+        let id = manager.add_task(task.clone()).await;
+        manager
+            .update_status(id, TaskStatus::Completed(serde_json::json!({"ok": true})))
+            .await
+            .unwrap();
+
+        // Re-delivery of the same task does not reset it
+        match manager.try_add_task(task).await {
+            SubmitOutcome::Duplicate(existing) => assert_eq!(
+                existing.status,
+                TaskStatus::Completed(serde_json::json!({"ok": true}))
+            ),
+            SubmitOutcome::Added(_) => panic!("Expected duplicate"),
+        }
+
+        // A retry under the same key maps to the original task, also after a restart
+        let restarted = temp_manager(dir.path(), TaskManagerConfig::default());
+        restarted.load_tasks().await.unwrap();
+        let mut retry = Task::new("idempotent retry");
+        retry.idempotency_key = Some("order-42".to_string());
+        assert_eq!(restarted.add_task(retry).await, id);
+        assert_eq!(restarted.list_tasks().await.len(), 1);
+        assert_eq!(restarted.queued_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cached_result_completes_task_on_submission() {
+        use crate::agent::result_cache::{ResultCache, ResultCacheConfig};
+        use crate::storage::local::LocalStorage;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResultCache::new(
+            Arc::new(LocalStorage::new(dir.path().join("cache")).unwrap()),
+            ResultCacheConfig::default(),
+        );
+        let manager =
+            temp_manager(dir.path(), TaskManagerConfig::default()).with_result_cache(cache);
+        let payload = TaskPayload {
+            task_type: TaskType::VectorComputation,
+            data: serde_json::json!({ "operation": "dot", "a": [1.0], "b": [2.0] }),
+            parameters: HashMap::new(),
+        };
+
+        let first = manager
+            .add_task(Task::with_payload(TaskPriority::Normal, payload.clone()))
+            .await;
+        assert_eq!(manager.queued_count().await, 1);
+        manager
+            .update_status(first, TaskStatus::Completed(serde_json::json!(2.0)))
+            .await
+            .unwrap();
+
+        // The same payload completes without being queued
+        let second = manager
+            .add_task(Task::with_payload(TaskPriority::Normal, payload.clone()))
+            .await;
+        assert_eq!(
+            manager.get_task(second).await.unwrap().status,
+            TaskStatus::Completed(serde_json::json!(2.0))
+        );
+        assert_eq!(manager.queued_count().await, 0);
+
+        // Redundant tasks want independently computed results
+        let mut redundant = Task::with_payload(TaskPriority::Normal, payload);
+        redundant.redundancy = Some(Redundancy::new(2, 3));
+        let redundant = manager.add_task(redundant).await;
+        assert_eq!(
+            manager.get_task(redundant).await.unwrap().status,
+            TaskStatus::Queued
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recover_interrupted_tasks_from_journal() {
+        use crate::agent::journal::TaskJournal;
+        use crate::storage::local::LocalStorage;
+
+        let dir = tempfile::tempdir().unwrap();
+        let journaled = |dir: &std::path::Path| {
+            let storage = Arc::new(LocalStorage::new(dir.join("tasks")).unwrap());
+            let journal = TaskJournal::open(dir.join("journal.log")).unwrap();
+            (
+                TaskManager::new(storage.clone()).with_journal(journal),
+                storage,
+            )
+        };
+
+        let (manager, storage) = journaled(dir.path());
+        let local = manager.add_task(Task::new("local")).await;
+        manager
+            .update_status(local, TaskStatus::Running)
+            .await
+            .unwrap();
+        let mut exhausted = Task::new("exhausted");
+        exhausted.max_retries = 0;
+        let exhausted = manager.add_task(exhausted).await;
+        manager
+            .update_status(exhausted, TaskStatus::Running)
+            .await
+            .unwrap();
+        let remote = manager.add_task(Task::new("remote")).await;
+        manager
+            .assign_task(remote, "peer-a".to_string())
+            .await
+            .unwrap();
+        let done = manager.add_task(Task::new("done")).await;
+        manager
+            .update_status(done, TaskStatus::Completed(serde_json::json!(1)))
+            .await
+            .unwrap();
+
+        // A crash tore the storage entry of the completed task
+        storage
+            .put(
+                &done.to_string(),
+                b"{\"id\":".to_vec(),
+                ConsistencyLevel::Strong,
+            )
+            .await
+            .unwrap();
+
+        let (restarted, _) = journaled(dir.path());
+        let recovery = restarted.recover().await.unwrap();
+        assert_eq!(recovery.loaded, 4);
+        assert_eq!(recovery.requeued, vec![local]);
+        assert_eq!(recovery.failed, vec![exhausted]);
+        assert_eq!(recovery.remote, vec![remote]);
+
+        let requeued = restarted.get_task(local).await.unwrap();
+        assert_eq!(requeued.status, TaskStatus::Queued);
+        assert_eq!(requeued.retry_count, 1);
+        assert_eq!(restarted.queued_count().await, 2);
+        assert_eq!(
+            restarted.get_task(done).await.unwrap().status,
+            TaskStatus::Completed(serde_json::json!(1))
+        );
+
+        // Replayed tasks were written back, so the journal is no longer needed
+        let (third, _) = journaled(dir.path());
+        third.load_tasks().await.unwrap();
+        assert!(third.get_task(done).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_compact_prunes_and_archives_finished_tasks() {
+        use crate::agent::journal::TaskJournal;
+        use crate::agent::retention::RetentionPolicy;
+
+        let dir = tempfile::tempdir().unwrap();
+        let policy = RetentionPolicy {
+            max_age: None,
+            max_count: Some(0),
+            archive_dir: Some(dir.path().join("archive")),
+            ..Default::default()
+        };
+        let manager = temp_manager(&dir.path().join("tasks"), TaskManagerConfig::default())
+            .with_journal(TaskJournal::open(dir.path().join("journal.log")).unwrap())
+            .with_retention(policy);
+
+        let mut keyed = Task::new("keyed");
+        keyed.idempotency_key = Some("job-1".to_string());
+        let keyed = manager.add_task(keyed).await;
+        let parent = manager.add_task(Task::new("parent")).await;
+        let other = manager.add_task(Task::new("other parent")).await;
+        let mut child = Task::new("child");
+        child.depends_on = vec![parent, other];
+        let child = manager.add_task(child).await;
+        let running = manager.add_task(Task::new("running")).await;
+        manager
+            .update_status(running, TaskStatus::Running)
+            .await
+            .unwrap();
+        for id in [keyed, parent] {
+            manager
+                .update_status(id, TaskStatus::Completed(serde_json::json!(id)))
+                .await
+                .unwrap();
+        }
+
+        let report = manager.compact().await.unwrap();
+        assert_eq!(report.pruned, 1);
+        assert!(report.archive.unwrap().exists());
+
+        // Unfinished tasks and parents of waiting dependents are kept
+        assert!(manager.get_task(keyed).await.is_none());
+        for id in [parent, other, child, running] {
+            assert!(manager.get_task(id).await.is_some());
+        }
+
+        // Pruned tasks are gone from storage and the journal, and free their key
+        let restarted = temp_manager(&dir.path().join("tasks"), TaskManagerConfig::default())
+            .with_journal(TaskJournal::open(dir.path().join("journal.log")).unwrap());
+        restarted.load_tasks().await.unwrap();
+        assert!(restarted.get_task(keyed).await.is_none());
+        let mut again = Task::new("keyed again");
+        again.idempotency_key = Some("job-1".to_string());
+        let again_id = again.id;
+        assert_eq!(manager.add_task(again).await, again_id);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_recurrence_fails_task() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path(), TaskManagerConfig::default());
+
+        let mut task = Task::new("bad schedule");
+        task.recurrence = Some("whenever".to_string());
+        let id = manager.add_task(task).await;
+        assert!(matches!(
+            manager.get_task(id).await.unwrap().status,
+            TaskStatus::Failed(_)
+        ));
+    }
 }
@@ -0,0 +1,362 @@
+//! Redundant execution with k-of-n result voting.
+//!
+//! A task with `Task::redundancy` set is dispatched to `replicas` peers at once.
+//! Its result is accepted as soon as `quorum` peers returned matching results;
+//! peers that returned a different result are reported to the reputation system.
+
+use crate::agent::task::TaskStatus;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Reputation gained by a peer whose result matched the accepted one.
+pub const AGREEMENT_REWARD: i32 = 10;
+/// Reputation lost by a peer whose result contradicted the accepted one.
+pub const DISAGREEMENT_PENALTY: i32 = 50;
+
+/// How a task is executed redundantly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Redundancy {
+    /// Number of peers the task is sent to (n).
+    pub replicas: usize,
+    /// Number of matching results needed to accept one (k).
+    pub quorum: usize,
+    /// How results are compared.
+    #[serde(default)]
+    pub comparator: ResultComparator,
+}
+
+impl Redundancy {
+    /// Creates a k-of-n setting using exact result comparison.
+    pub fn new(quorum: usize, replicas: usize) -> Self {
+        Self {
+            replicas,
+            quorum,
+            comparator: ResultComparator::Exact,
+        }
+    }
+
+    /// Checks that the quorum can be reached at all.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.quorum == 0 || self.quorum > self.replicas {
+            return Err(anyhow::anyhow!(
+                "Invalid redundancy: quorum {} of {} replicas",
+                self.quorum,
+                self.replicas
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Comparison used to decide whether two results agree.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum ResultComparator {
+    /// Results must be identical JSON values.
+    #[default]
+    Exact,
+    /// Numbers (including inside arrays and objects, e.g. embedding vectors) may
+    /// differ by at most `epsilon`; everything else must match exactly.
+    FloatTolerance {
+        /// Maximum absolute difference between two numbers.
+        epsilon: f64,
+    },
+}
+
+impl ResultComparator {
+    /// Returns true if the two results agree.
+    pub fn matches(&self, a: &Value, b: &Value) -> bool {
+        match self {
+            ResultComparator::Exact => a == b,
+            ResultComparator::FloatTolerance { epsilon } => approx_eq(a, b, *epsilon),
+        }
+    }
+}
+
+fn approx_eq(a: &Value, b: &Value, epsilon: f64) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => match (x.as_f64(), y.as_f64()) {
+            (Some(x), Some(y)) => (x - y).abs() <= epsilon,
+            _ => x == y,
+        },
+        (Value::Array(xs), Value::Array(ys)) => {
+            xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| approx_eq(x, y, epsilon))
+        }
+        (Value::Object(xs), Value::Object(ys)) => {
+            xs.len() == ys.len()
+                && xs
+                    .iter()
+                    .all(|(k, x)| ys.get(k).is_some_and(|y| approx_eq(x, y, epsilon)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Result of recording a peer's response.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VoteOutcome {
+    /// No decision yet (or the response was not a vote).
+    Pending,
+    /// A quorum agreed on `result`.
+    Accepted {
+        /// The accepted result.
+        result: Value,
+        /// Peers that returned the accepted result.
+        agreeing: Vec<String>,
+        /// Peers that returned a different result.
+        disagreeing: Vec<String>,
+    },
+    /// The quorum can no longer be reached.
+    NoQuorum(String),
+    /// A response arriving after the decision.
+    Late {
+        /// The responding peer.
+        peer: String,
+        /// Whether its result matched the accepted one.
+        agrees: bool,
+    },
+}
+
+/// Collects the responses of the peers running a redundant task.
+#[derive(Debug, Clone)]
+pub struct VoteTally {
+    redundancy: Redundancy,
+    peers: Vec<String>,
+    /// Distinct results with the peers that returned them.
+    groups: Vec<(Value, Vec<String>)>,
+    failed: Vec<String>,
+    decided: Option<usize>,
+    /// Set once the quorum became unreachable.
+    closed: bool,
+}
+
+impl VoteTally {
+    /// Creates a tally for a task sent to `peers`.
+    pub fn new(redundancy: Redundancy, peers: Vec<String>) -> Self {
+        Self {
+            redundancy,
+            peers,
+            groups: Vec::new(),
+            failed: Vec::new(),
+            decided: None,
+            closed: false,
+        }
+    }
+
+    /// Returns true once every peer has responded.
+    pub fn is_complete(&self) -> bool {
+        self.responded() == self.peers.len()
+    }
+
+    /// Returns the peers that have not responded yet.
+    pub fn outstanding(&self) -> Vec<String> {
+        self.peers
+            .iter()
+            .filter(|peer| !self.has_voted(peer))
+            .cloned()
+            .collect()
+    }
+
+    fn responded(&self) -> usize {
+        self.failed.len() + self.groups.iter().map(|(_, p)| p.len()).sum::<usize>()
+    }
+
+    fn has_voted(&self, peer: &str) -> bool {
+        self.failed.iter().any(|p| p == peer)
+            || self
+                .groups
+                .iter()
+                .any(|(_, peers)| peers.iter().any(|p| p == peer))
+    }
+
+    /// Records a final status reported by `peer`.
+    ///
+    /// Non-final statuses, unknown peers, repeated votes and votes arriving after
+    /// the quorum became unreachable are ignored.
+    pub fn record(&mut self, peer: &str, status: &TaskStatus) -> VoteOutcome {
+        if !self.peers.iter().any(|p| p == peer) || self.has_voted(peer) {
+            return VoteOutcome::Pending;
+        }
+
+        let group = match status {
+            TaskStatus::Completed(result) => {
+                let comparator = &self.redundancy.comparator;
+                let index = match self
+                    .groups
+                    .iter()
+                    .position(|(value, _)| comparator.matches(value, result))
+                {
+                    Some(index) => index,
+                    None => {
+                        self.groups.push((result.clone(), Vec::new()));
+                        self.groups.len() - 1
+                    }
+                };
+                self.groups[index].1.push(peer.to_string());
+                Some(index)
+            }
+            TaskStatus::Failed(_) | TaskStatus::Cancelled | TaskStatus::Timeout => {
+                self.failed.push(peer.to_string());
+                None
+            }
+            TaskStatus::Queued | TaskStatus::Running => return VoteOutcome::Pending,
+        };
+
+        if self.closed {
+            return VoteOutcome::Pending;
+        }
+        if let Some(decided) = self.decided {
+            // Failures are not a contradicting result
+            return match group {
+                Some(group) => VoteOutcome::Late {
+                    peer: peer.to_string(),
+                    agrees: group == decided,
+                },
+                None => VoteOutcome::Pending,
+            };
+        }
+
+        if let Some(index) = self
+            .groups
+            .iter()
+            .position(|(_, peers)| peers.len() >= self.redundancy.quorum)
+        {
+            self.decided = Some(index);
+            let disagreeing = self
+                .groups
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != index)
+                .flat_map(|(_, (_, peers))| peers.iter().cloned())
+                .collect();
+            return VoteOutcome::Accepted {
+                result: self.groups[index].0.clone(),
+                agreeing: self.groups[index].1.clone(),
+                disagreeing,
+            };
+        }
+
+        let best = self.groups.iter().map(|(_, p)| p.len()).max().unwrap_or(0);
+        let outstanding = self.peers.len() - self.responded();
+        if best + outstanding < self.redundancy.quorum {
+            self.closed = true;
+            return VoteOutcome::NoQuorum(format!(
+                "No {} of {} peers agreed ({} distinct results, {} failed)",
+                self.redundancy.quorum,
+                self.peers.len(),
+                self.groups.len(),
+                self.failed.len()
+            ));
+        }
+
+        VoteOutcome::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn peers(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("peer-{}", i)).collect()
+    }
+
+    #[test]
+    fn test_two_of_three_accepts_and_reports_dissent() {
+        let mut tally = VoteTally::new(Redundancy::new(2, 3), peers(3));
+
+        assert_eq!(
+            tally.record("peer-0", &TaskStatus::Completed(json!(1))),
+            VoteOutcome::Pending
+        );
+        assert_eq!(
+            tally.record("peer-1", &TaskStatus::Completed(json!(2))),
+            VoteOutcome::Pending
+        );
+        assert_eq!(
+            tally.record("peer-2", &TaskStatus::Completed(json!(1))),
+            VoteOutcome::Accepted {
+                result: json!(1),
+                agreeing: vec!["peer-0".to_string(), "peer-2".to_string()],
+                disagreeing: vec!["peer-1".to_string()],
+            }
+        );
+        assert!(tally.is_complete());
+    }
+
+    #[test]
+    fn test_late_votes_and_ignored_responses() {
+        let mut tally = VoteTally::new(Redundancy::new(1, 2), peers(2));
+
+        assert_eq!(
+            tally.record("stranger", &TaskStatus::Completed(json!(1))),
+            VoteOutcome::Pending
+        );
+        assert_eq!(
+            tally.record("peer-0", &TaskStatus::Running),
+            VoteOutcome::Pending
+        );
+        assert!(matches!(
+            tally.record("peer-0", &TaskStatus::Completed(json!("a"))),
+            VoteOutcome::Accepted { .. }
+        ));
+        assert_eq!(
+            tally.record("peer-1", &TaskStatus::Completed(json!("b"))),
+            VoteOutcome::Late {
+                peer: "peer-1".to_string(),
+                agrees: false
+            }
+        );
+    }
+
+    #[test]
+    fn test_no_quorum() {
+        let mut tally = VoteTally::new(Redundancy::new(2, 3), peers(3));
+        tally.record("peer-0", &TaskStatus::Completed(json!(1)));
+        assert!(matches!(
+            tally.record("peer-1", &TaskStatus::Failed("oom".to_string())),
+            VoteOutcome::Pending
+        ));
+        assert!(matches!(
+            tally.record("peer-2", &TaskStatus::Completed(json!(2))),
+            VoteOutcome::NoQuorum(_)
+        ));
+        assert!(tally.is_complete());
+    }
+
+    #[test]
+    fn test_outstanding_peers_time_out() {
+        let mut tally = VoteTally::new(Redundancy::new(2, 3), peers(3));
+        tally.record("peer-1", &TaskStatus::Completed(json!(1)));
+        assert_eq!(tally.outstanding(), vec!["peer-0", "peer-2"]);
+
+        assert_eq!(
+            tally.record("peer-0", &TaskStatus::Timeout),
+            VoteOutcome::Pending
+        );
+        assert!(matches!(
+            tally.record("peer-2", &TaskStatus::Timeout),
+            VoteOutcome::NoQuorum(_)
+        ));
+        assert!(tally.outstanding().is_empty());
+    }
+
+    #[test]
+    fn test_float_tolerance_comparator() {
+        let comparator = ResultComparator::FloatTolerance { epsilon: 1e-4 };
+        assert!(comparator.matches(
+            &json!({"embedding": [0.1, 0.2000001]}),
+            &json!({"embedding": [0.10005, 0.2]})
+        ));
+        assert!(!comparator.matches(&json!([0.1, 0.2]), &json!([0.1, 0.3])));
+        assert!(!comparator.matches(&json!([0.1]), &json!([0.1, 0.2])));
+        assert!(!ResultComparator::Exact.matches(&json!([0.1]), &json!([0.1000001])));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Redundancy::new(2, 3).validate().is_ok());
+        assert!(Redundancy::new(0, 3).validate().is_err());
+        assert!(Redundancy::new(4, 3).validate().is_err());
+    }
+}
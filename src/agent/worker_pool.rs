@@ -0,0 +1,145 @@
+//! Bounded worker pool for local task execution.
+//!
+//! The pool hands out permits for running tasks: a global permit bounding the
+//! total number of concurrently executing tasks, plus an optional per-`TaskType`
+//! permit (e.g. one `AiInference` at a time while many `TextProcessing` tasks run).
+
+use crate::agent::task::TaskType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Default maximum number of tasks executed concurrently by an agent.
+pub const DEFAULT_MAX_CONCURRENT_TASKS: usize = 4;
+
+/// Concurrency limit for a single task type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcurrencyLimit {
+    /// The task type the limit applies to.
+    pub task_type: TaskType,
+    /// Maximum number of tasks of this type running at the same time.
+    pub max_concurrent: usize,
+}
+
+/// Permits held by a running task. Capacity is released when this is dropped.
+#[derive(Debug)]
+pub struct WorkerPermit {
+    _global: OwnedSemaphorePermit,
+    _task_type: Option<OwnedSemaphorePermit>,
+}
+
+/// Pool of execution slots shared by all locally executed tasks.
+#[derive(Debug, Clone)]
+pub struct WorkerPool {
+    max_concurrent: usize,
+    global: Arc<Semaphore>,
+    per_type: HashMap<TaskType, Arc<Semaphore>>,
+}
+
+impl Default for WorkerPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_TASKS, &[])
+    }
+}
+
+impl WorkerPool {
+    /// Creates a pool with a global limit and optional per-type limits.
+    ///
+    /// A limit of zero is treated as one so that tasks can always make progress.
+    pub fn new(max_concurrent: usize, limits: &[ConcurrencyLimit]) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        let per_type = limits
+            .iter()
+            .map(|limit| {
+                let permits = limit.max_concurrent.clamp(1, max_concurrent);
+                (limit.task_type.clone(), Arc::new(Semaphore::new(permits)))
+            })
+            .collect();
+
+        Self {
+            max_concurrent,
+            global: Arc::new(Semaphore::new(max_concurrent)),
+            per_type,
+        }
+    }
+
+    /// Maximum number of tasks that may run concurrently.
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// Number of tasks currently holding a permit.
+    pub fn active_workers(&self) -> usize {
+        self.max_concurrent - self.global.available_permits()
+    }
+
+    /// Returns true if at least one execution slot is free.
+    pub fn has_capacity(&self) -> bool {
+        self.global.available_permits() > 0
+    }
+
+    /// Returns true if a task of the given type could start right now.
+    pub fn can_run(&self, task_type: Option<&TaskType>) -> bool {
+        self.has_capacity()
+            && task_type
+                .and_then(|t| self.per_type.get(t))
+                .is_none_or(|sem| sem.available_permits() > 0)
+    }
+
+    /// Tries to reserve an execution slot for a task of the given type.
+    ///
+    /// Returns `None` if either the global or the per-type limit is reached.
+    pub fn try_acquire(&self, task_type: Option<&TaskType>) -> Option<WorkerPermit> {
+        let global = self.global.clone().try_acquire_owned().ok()?;
+        let task_type = match task_type.and_then(|t| self.per_type.get(t)) {
+            Some(sem) => Some(sem.clone().try_acquire_owned().ok()?),
+            None => None,
+        };
+
+        Some(WorkerPermit {
+            _global: global,
+            _task_type: task_type,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_limit() {
+        let pool = WorkerPool::new(2, &[]);
+        let a = pool.try_acquire(Some(&TaskType::TextProcessing)).unwrap();
+        let _b = pool.try_acquire(None).unwrap();
+        assert_eq!(pool.active_workers(), 2);
+        assert!(pool.try_acquire(Some(&TaskType::TextProcessing)).is_none());
+
+        drop(a);
+        assert!(pool.has_capacity());
+        assert!(pool.try_acquire(Some(&TaskType::TextProcessing)).is_some());
+    }
+
+    #[test]
+    fn test_per_type_limit() {
+        let pool = WorkerPool::new(
+            4,
+            &[ConcurrencyLimit {
+                task_type: TaskType::AiInference,
+                max_concurrent: 1,
+            }],
+        );
+
+        let inference = pool.try_acquire(Some(&TaskType::AiInference)).unwrap();
+        assert!(!pool.can_run(Some(&TaskType::AiInference)));
+        assert!(pool.try_acquire(Some(&TaskType::AiInference)).is_none());
+        // Other types still run while inference is saturated
+        assert!(pool.can_run(Some(&TaskType::TextProcessing)));
+        let _text = pool.try_acquire(Some(&TaskType::TextProcessing)).unwrap();
+        assert_eq!(pool.active_workers(), 2);
+
+        drop(inference);
+        assert!(pool.can_run(Some(&TaskType::AiInference)));
+    }
+}
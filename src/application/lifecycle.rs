@@ -130,6 +130,7 @@ impl LifecycleManager {
                             crate::agent::task::TaskType::VectorComputation,
                         ],
                         models: vec![],
+                        ..Default::default()
                     };
 
                     // Create default agent
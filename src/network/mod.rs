@@ -558,6 +558,7 @@ impl NetworkManager {
                                 capabilities: PeerCapabilities {
                                     supported_tasks,
                                     supported_models: vec![], // Identify protocol doesn't support model advertisement in this MVP yet
+                                    ..Default::default()
                                 },
                                 status: ConnectionStatus::Connected,
                             };
@@ -7,6 +7,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use super::{Multiaddr, PeerId};
+use crate::agent::executors::schema::TaskSchema;
 use crate::agent::task::TaskType;
 
 /// Capabilities supported by a peer
@@ -17,6 +18,21 @@ pub struct PeerCapabilities {
     /// List of AI models available on this peer (e.g., "prajjwal1/bert-tiny")
     #[serde(default)]
     pub supported_models: Vec<String>,
+    /// Input schemas the peer published for its task types
+    #[serde(default)]
+    pub schemas: Vec<TaskSchema>,
+}
+
+impl PeerCapabilities {
+    /// Creates capabilities for the given task types and models, with no
+    /// published schemas.
+    pub fn new(supported_tasks: Vec<TaskType>, supported_models: Vec<String>) -> Self {
+        Self {
+            supported_tasks,
+            supported_models,
+            ..Default::default()
+        }
+    }
 }
 
 /// Connection status of a peer
@@ -126,6 +142,12 @@ impl PeerCache {
         peers.values().map(|state| state.info.clone()).collect()
     }
 
+    /// Get peer metrics
+    pub async fn get_metrics(&self, peer_id: &PeerId) -> Option<PeerMetrics> {
+        let peers = self.peers.read().await;
+        peers.get(peer_id).map(|state| state.metrics.clone())
+    }
+
     /// Update peer metrics
     pub async fn update_metrics<F>(&self, peer_id: &PeerId, f: F)
     where
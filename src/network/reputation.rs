@@ -157,21 +157,6 @@ impl ReputationManager {
         Ok(new_score)
     }
 
-    /// Check if an agent can accept a new task based on their tier quota.
-    ///
-    /// # Arguments
-    ///
-    /// * `agent_id` - Agent identifier
-    /// * `current_tasks` - Number of tasks currently assigned to agent in the last hour
-    pub fn can_accept_task(
-        &self,
-        agent_id: &str,
-        current_tasks: u32,
-    ) -> Result<bool, ReputationError> {
-        let tier = self.get_tier(agent_id)?;
-        Ok(current_tasks < tier.task_quota())
-    }
-
     /// Get all agents and their scores.
     pub fn all_scores(&self) -> &HashMap<String, i32> {
         &self.scores
@@ -280,38 +265,6 @@ mod tests {
         assert_eq!(manager.get_score("agent1").unwrap(), MIN_REPUTATION);
     }
 
-    #[test]
-    fn test_can_accept_task() {
-        let mut manager = ReputationManager::new();
-        manager.register_agent("agent1".to_string());
-
-        // Newcomer tier has quota of 10
-        assert!(manager.can_accept_task("agent1", 5).unwrap());
-        assert!(manager.can_accept_task("agent1", 9).unwrap());
-        assert!(!manager.can_accept_task("agent1", 10).unwrap());
-        assert!(!manager.can_accept_task("agent1", 15).unwrap());
-    }
-
-    #[test]
-    fn test_can_accept_task_different_tiers() {
-        let mut manager = ReputationManager::new();
-        manager.register_agent("newcomer".to_string());
-        manager.register_agent("established".to_string());
-        manager.register_agent("trusted".to_string());
-        manager.register_agent("elite".to_string());
-
-        manager.increase_reputation("established", 150).unwrap(); // 250
-        manager.increase_reputation("trusted", 400).unwrap(); // 500
-        manager.increase_reputation("elite", 650).unwrap(); // 750
-
-        assert!(!manager.can_accept_task("newcomer", 10).unwrap());
-        assert!(manager.can_accept_task("established", 10).unwrap());
-        assert!(!manager.can_accept_task("established", 50).unwrap());
-        assert!(manager.can_accept_task("trusted", 50).unwrap());
-        assert!(!manager.can_accept_task("trusted", 200).unwrap());
-        assert!(manager.can_accept_task("elite", 500).unwrap());
-    }
-
     #[test]
     fn test_multiple_agents() {
         let mut manager = ReputationManager::new();
@@ -0,0 +1,193 @@
+//! Integration tests for admission control of incoming task requests.
+
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::Message;
+use p2p_ai_agents::agent::task::{Task, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::{Agent, AgentConfig, ResourceLimits};
+use p2p_ai_agents::network::{ConnectionStatus, Multiaddr, PeerCapabilities, PeerId, PeerInfo};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+use tempfile::TempDir;
+
+mod common;
+use common::{signed, TestAgent};
+
+async fn create_agent(dir: &TempDir, resource_limits: Option<ResourceLimits>) -> Agent {
+    TestAgent::new(dir)
+        .config(AgentConfig {
+            name: "admission-agent".to_string(),
+            resource_limits,
+            ..Default::default()
+        })
+        .build()
+        .await
+}
+
+/// Limits no running process can stay under.
+fn exhausted_limits() -> ResourceLimits {
+    ResourceLimits {
+        max_cpu: 1.0,
+        max_memory: 1,
+        max_storage: u64::MAX,
+        max_bandwidth: u64::MAX,
+        max_connections: 100,
+    }
+}
+
+/// Adds a trusted text processing peer to the agent and returns its identity.
+async fn add_peer(agent: &Agent, name: &str) -> AgentIdentity {
+    let identity = AgentIdentity::new(20, semaphore::Field::from(0))
+        .await
+        .unwrap();
+    agent
+        .identity
+        .trust_peer(&identity.public_key_bytes())
+        .unwrap();
+
+    let nm = agent.network_manager.lock().await;
+    nm.peer_cache
+        .upsert_peer(PeerInfo {
+            peer_id: PeerId(name.to_string()),
+            addresses: vec![Multiaddr("/ip4/127.0.0.1/tcp/0".to_string())],
+            last_seen: chrono::Utc::now(),
+            reputation: 100,
+            capabilities: PeerCapabilities::new(vec![TaskType::TextProcessing], vec![]),
+            status: ConnectionStatus::Connected,
+        })
+        .await;
+    identity
+}
+
+fn word_count_task() -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::TextProcessing,
+            data: json!({ "operation": "word_count", "text": "one two" }),
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_requests_are_rejected_over_resource_limits() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, Some(exhausted_limits())).await;
+    let requester = add_peer(&agent, "requester").await;
+
+    let task = word_count_task();
+    let message = Message::new_task_request("requester", agent.id(), task.clone());
+    agent
+        .handle_message(signed(&requester, message))
+        .await
+        .unwrap();
+    assert!(agent.task_manager.get_task(task.id).await.is_none());
+
+    // A task the node already holds is still answered under pressure
+    let known = word_count_task();
+    agent.task_manager.add_task(known.clone()).await;
+    let message = Message::new_task_request("requester", agent.id(), known.clone());
+    agent
+        .handle_message(signed(&requester, message))
+        .await
+        .unwrap();
+    assert_eq!(
+        agent.task_status(&known.id).await.unwrap(),
+        TaskStatus::Queued
+    );
+}
+
+#[tokio::test]
+async fn test_requests_are_accepted_without_limits() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, None).await;
+    let requester = add_peer(&agent, "requester").await;
+
+    let task = word_count_task();
+    let message = Message::new_task_request("requester", agent.id(), task.clone());
+    agent
+        .handle_message(signed(&requester, message))
+        .await
+        .unwrap();
+    assert!(agent.task_manager.get_task(task.id).await.is_some());
+}
+
+#[tokio::test]
+async fn test_rejected_task_is_dispatched_to_another_peer() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, None).await;
+    let exec_a = add_peer(&agent, "exec-a").await;
+    let exec_b = add_peer(&agent, "exec-b").await;
+
+    let id = agent.submit_task(word_count_task()).await;
+    agent.dispatch_task(id).await.unwrap();
+    let assigned = agent.task_manager.get_task(id).await.unwrap().assigned_to;
+    let (owner, owner_identity, other, other_identity) = if assigned.as_deref() == Some("exec-a") {
+        ("exec-a", &exec_a, "exec-b", &exec_b)
+    } else {
+        ("exec-b", &exec_b, "exec-a", &exec_a)
+    };
+
+    // Only the peer the task was sent to can reject it
+    let message =
+        Message::new_task_rejected(other, "broadcast", id, "busy", Duration::from_secs(5));
+    agent
+        .handle_message(signed(other_identity, message))
+        .await
+        .unwrap();
+    assert_eq!(
+        agent.task_manager.get_task(id).await.unwrap().assigned_to,
+        Some(owner.to_string())
+    );
+
+    let message =
+        Message::new_task_rejected(owner, "broadcast", id, "busy", Duration::from_secs(5));
+    agent
+        .handle_message(signed(owner_identity, message))
+        .await
+        .unwrap();
+    let task = agent.task_manager.get_task(id).await.unwrap();
+    assert_eq!(task.assigned_to, Some(other.to_string()));
+    // Rejections do not use up retries
+    assert_eq!(task.retry_count, 0);
+}
+
+#[tokio::test]
+async fn test_task_rejected_by_every_peer_fails() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, None).await;
+    let peers = [
+        ("exec-a", add_peer(&agent, "exec-a").await),
+        ("exec-b", add_peer(&agent, "exec-b").await),
+    ];
+    let retry_after = Duration::from_millis(300);
+
+    let id = agent.submit_task(word_count_task()).await;
+    agent.dispatch_task(id).await.unwrap();
+    for _ in 0..peers.len() {
+        let assigned = agent.task_manager.get_task(id).await.unwrap().assigned_to;
+        let (name, identity) = peers
+            .iter()
+            .find(|(name, _)| assigned.as_deref() == Some(*name))
+            .unwrap();
+        let message = Message::new_task_rejected(*name, "broadcast", id, "busy", retry_after);
+        agent
+            .handle_message(signed(identity, message))
+            .await
+            .unwrap();
+    }
+
+    let task = agent.task_manager.get_task(id).await.unwrap();
+    assert_eq!(task.retry_count, 0);
+    match task.status {
+        TaskStatus::Failed(reason) => assert!(reason.starts_with("No peer accepted the task")),
+        status => panic!("Expected the task to fail, got {:?}", status),
+    }
+
+    // Busy peers are skipped by other tasks until they asked to be retried
+    let next = agent.submit_task(word_count_task()).await;
+    assert!(agent.dispatch_task(next).await.is_err());
+    tokio::time::sleep(retry_after).await;
+    agent.dispatch_task(next).await.unwrap();
+}
@@ -0,0 +1,162 @@
+//! Integration tests for BERT embeddings, checked against reference vectors of
+//! the tiny model in `tests/fixtures/tiny-bert` (see `scripts/generate_tiny_bert.py`).
+#![cfg(feature = "ai")]
+
+use p2p_ai_agents::agent::ai::{EmbeddingOptions, InferenceEngine, ModelManager, Pooling};
+use p2p_ai_agents::agent::executors::TextProcessingExecutor;
+use p2p_ai_agents::agent::task::{TaskExecutor, TaskPayload, TaskType};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Largest difference allowed between an embedding and its reference.
+const TOLERANCE: f32 = 1e-4;
+
+#[derive(serde::Deserialize)]
+struct Reference {
+    text: String,
+    mean: Vec<f32>,
+    mean_normalized: Vec<f32>,
+    cls: Vec<f32>,
+    cls_normalized: Vec<f32>,
+}
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tiny-bert")
+}
+
+fn references() -> Vec<Reference> {
+    let json = std::fs::read(fixture_dir().join("reference.json")).unwrap();
+    serde_json::from_slice(&json).unwrap()
+}
+
+fn assert_close(actual: &[f32], expected: &[f32], text: &str) {
+    assert_eq!(actual.len(), expected.len(), "{}", text);
+    for (a, e) in actual.iter().zip(expected) {
+        assert!(
+            (a - e).abs() < TOLERANCE,
+            "{}: {:?} != {:?}",
+            text,
+            actual,
+            expected
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_embeddings_match_reference_vectors() {
+    let engine = InferenceEngine::new();
+    let model = fixture_dir();
+
+    for reference in references() {
+        for (pooling, normalize, expected) in [
+            (Pooling::Mean, false, &reference.mean),
+            (Pooling::Mean, true, &reference.mean_normalized),
+            (Pooling::Cls, false, &reference.cls),
+            (Pooling::Cls, true, &reference.cls_normalized),
+        ] {
+            let options = EmbeddingOptions { pooling, normalize };
+            let embedding = engine
+                .embed_with(&model, &reference.text, &options)
+                .await
+                .unwrap();
+            assert_close(&embedding, expected, &reference.text);
+        }
+
+        // Mean pooling with normalization is the default
+        let embedding = engine.embed(&model, &reference.text).await.unwrap();
+        assert_close(&embedding, &reference.mean_normalized, &reference.text);
+    }
+}
+
+#[tokio::test]
+async fn test_missing_weights_fail_to_load() {
+    let dir = TempDir::new().unwrap();
+    for file in ["config.json", "tokenizer.json"] {
+        std::fs::copy(fixture_dir().join(file), dir.path().join(file)).unwrap();
+    }
+
+    let error = InferenceEngine::new()
+        .embed(dir.path(), "hello world")
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("Model weights not found"));
+}
+
+#[tokio::test]
+async fn test_embed_task_uses_payload_options() {
+    let dir = TempDir::new().unwrap();
+    // Seed the model cache, so the executor does not download the model
+    let cached = dir.path().join("models/tiny-bert");
+    std::fs::create_dir_all(&cached).unwrap();
+    for file in ["config.json", "tokenizer.json", "model.safetensors"] {
+        std::fs::copy(fixture_dir().join(file), cached.join(file)).unwrap();
+    }
+    let executor = TextProcessingExecutor::new(Arc::new(ModelManager::new(dir.path())));
+    let references = references();
+
+    let payload = TaskPayload {
+        task_type: TaskType::TextProcessing,
+        data: json!({
+            "operation": "embed",
+            "text": references[1].text,
+            "model": "tiny-bert",
+            "pooling": "cls",
+            "normalize": false,
+        }),
+        parameters: HashMap::new(),
+    };
+    let result = executor.execute(&payload).await.unwrap();
+    let embedding: Vec<f32> = serde_json::from_value(result["embedding"].clone()).unwrap();
+    assert_close(&embedding, &references[1].cls, &references[1].text);
+
+    let payload = TaskPayload {
+        task_type: TaskType::TextProcessing,
+        data: json!({
+            "operation": "embed",
+            "corpus": [references[0].text, references[2].text],
+            "model": "tiny-bert",
+        }),
+        parameters: HashMap::new(),
+    };
+    let result = executor.execute(&payload).await.unwrap();
+    let embeddings: Vec<Vec<f32>> = serde_json::from_value(result["embeddings"].clone()).unwrap();
+    assert_close(
+        &embeddings[0],
+        &references[0].mean_normalized,
+        &references[0].text,
+    );
+    assert_close(
+        &embeddings[1],
+        &references[2].mean_normalized,
+        &references[2].text,
+    );
+}
+
+#[tokio::test]
+async fn test_loaded_model_is_reused_across_tasks() {
+    let dir = TempDir::new().unwrap();
+    let cached = dir.path().join("models/tiny-bert");
+    std::fs::create_dir_all(&cached).unwrap();
+    for file in ["config.json", "tokenizer.json", "model.safetensors"] {
+        std::fs::copy(fixture_dir().join(file), cached.join(file)).unwrap();
+    }
+    let executor = TextProcessingExecutor::new(Arc::new(ModelManager::new(dir.path())));
+    let reference = &references()[0];
+    let payload = TaskPayload {
+        task_type: TaskType::TextProcessing,
+        data: json!({ "operation": "embed", "text": reference.text, "model": "tiny-bert" }),
+        parameters: HashMap::new(),
+    };
+    executor.execute(&payload).await.unwrap();
+
+    // Later tasks, including those of clones, embed without reloading the weights
+    std::fs::remove_file(cached.join("model.safetensors")).unwrap();
+    for executor in [executor.clone(), executor] {
+        let result = executor.execute(&payload).await.unwrap();
+        let embedding: Vec<f32> = serde_json::from_value(result["embedding"].clone()).unwrap();
+        assert_close(&embedding, &reference.mean_normalized, &reference.text);
+    }
+}
@@ -0,0 +1,122 @@
+//! Integration tests for the bidding round run before dispatching a task.
+
+use p2p_ai_agents::agent::bidding::{Bid, BiddingConfig};
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::Message;
+use p2p_ai_agents::agent::task::{Task, TaskPayload, TaskPriority, TaskType};
+use p2p_ai_agents::agent::{Agent, AgentConfig};
+use p2p_ai_agents::network::{ConnectionStatus, Multiaddr, PeerCapabilities, PeerId, PeerInfo};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+mod common;
+use common::{signed, TestAgent};
+
+async fn create_agent(dir: &TempDir, deadline: Duration) -> Arc<Agent> {
+    TestAgent::new(dir)
+        .config(AgentConfig {
+            name: "bidding-requester".to_string(),
+            bidding: BiddingConfig {
+                enabled: true,
+                deadline,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .build_arc()
+        .await
+}
+
+/// Adds a trusted text processing peer to the requester and returns its identity.
+async fn add_executor(agent: &Agent, name: &str) -> AgentIdentity {
+    let identity = AgentIdentity::new(20, semaphore::Field::from(0))
+        .await
+        .unwrap();
+    agent
+        .identity
+        .trust_peer(&identity.public_key_bytes())
+        .unwrap();
+
+    let nm = agent.network_manager.lock().await;
+    nm.peer_cache
+        .upsert_peer(PeerInfo {
+            peer_id: PeerId(name.to_string()),
+            addresses: vec![Multiaddr("/ip4/127.0.0.1/tcp/0".to_string())],
+            last_seen: chrono::Utc::now(),
+            reputation: 100,
+            capabilities: PeerCapabilities::new(vec![TaskType::TextProcessing], vec![]),
+            status: ConnectionStatus::Connected,
+        })
+        .await;
+    identity
+}
+
+fn word_count_task() -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::TextProcessing,
+            data: json!({ "operation": "word_count", "text": "one two" }),
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_task_goes_to_best_bidder() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, Duration::from_secs(10)).await;
+    let busy = add_executor(&agent, "busy").await;
+    let idle = add_executor(&agent, "idle").await;
+
+    let id = agent.submit_task(word_count_task()).await;
+    let started = Instant::now();
+    let dispatch = tokio::spawn({
+        let agent = agent.clone();
+        async move { agent.dispatch_task(id).await }
+    });
+    // Let the round open before bidding
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let bids = [
+        ("busy", &busy, Duration::from_secs(3), 6),
+        ("idle", &idle, Duration::ZERO, 0),
+    ];
+    for (name, identity, estimated_start, queue_depth) in bids {
+        let bid = Bid {
+            estimated_start,
+            queue_depth,
+            cost: 1.0,
+        };
+        let message = Message::new_task_bid(name, "broadcast", id, bid);
+        agent
+            .handle_message(signed(identity, message))
+            .await
+            .unwrap();
+    }
+
+    // Every candidate bid, so the round closes well before its deadline
+    dispatch.await.unwrap().unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(
+        agent.task_manager.get_task(id).await.unwrap().assigned_to,
+        Some("idle".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_falls_back_to_selector_without_bids() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, Duration::from_millis(100)).await;
+    add_executor(&agent, "silent").await;
+
+    let id = agent.submit_task(word_count_task()).await;
+    agent.dispatch_task(id).await.unwrap();
+    assert_eq!(
+        agent.task_manager.get_task(id).await.unwrap().assigned_to,
+        Some("silent".to_string())
+    );
+}
@@ -1,6 +1,14 @@
 #![allow(dead_code)]
 
+use p2p_ai_agents::agent::ai::ModelManager;
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::{Message, MessageType};
+use p2p_ai_agents::agent::task::TaskManager;
+use p2p_ai_agents::agent::{Agent, AgentConfig};
+use p2p_ai_agents::network::{NetworkConfig, ProtocolConfig, ResourceLimits, SecurityConfig};
+use p2p_ai_agents::storage::local::LocalStorage;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tempfile::TempDir;
 
 pub struct TestContext {
@@ -22,3 +30,117 @@ pub fn setup_test_agent() -> TestContext {
 pub fn cleanup_test_agent(_ctx: TestContext) {
     // TempDir handles cleanup automatically
 }
+
+/// Network configuration of test agents: a local listener and no bootstrap peers.
+pub fn network_config() -> NetworkConfig {
+    NetworkConfig {
+        listen_addr: "127.0.0.1:0".parse().unwrap(),
+        bootstrap_peers: vec![],
+        max_peers: 50,
+        protocol_config: ProtocolConfig {},
+        resource_limits: ResourceLimits {
+            max_bandwidth: 1024 * 1024,
+            max_memory: 512 * 1024 * 1024,
+            max_connections: 50,
+        },
+        security_config: SecurityConfig {
+            trusted_authorities: vec![],
+            local_certificate: None,
+        },
+    }
+}
+
+/// Builder of agents for integration tests, keeping tasks and models in a
+/// temporary directory.
+pub struct TestAgent<'a> {
+    dir: &'a TempDir,
+    config: AgentConfig,
+    task_manager: Option<TaskManager>,
+}
+
+impl<'a> TestAgent<'a> {
+    /// Starts an agent with the default config, storing tasks in `dir/tasks`.
+    pub fn new(dir: &'a TempDir) -> Self {
+        Self {
+            dir,
+            config: AgentConfig::default(),
+            task_manager: None,
+        }
+    }
+
+    /// Sets the agent's name.
+    pub fn name(mut self, name: &str) -> Self {
+        self.config.name = name.to_string();
+        self
+    }
+
+    /// Replaces the agent's config.
+    pub fn config(mut self, config: AgentConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Replaces the task manager, e.g. to enable a result cache.
+    pub fn task_manager(mut self, task_manager: TaskManager) -> Self {
+        self.task_manager = Some(task_manager);
+        self
+    }
+
+    /// Creates the agent.
+    pub async fn build(self) -> Agent {
+        let task_manager = self
+            .task_manager
+            .unwrap_or_else(|| TaskManager::new(storage(self.dir)));
+        let model_manager = Arc::new(ModelManager::new(self.dir.path()));
+        let identity = AgentIdentity::new(20, semaphore::Field::from(0))
+            .await
+            .unwrap();
+
+        Agent::new(
+            identity,
+            self.config,
+            network_config(),
+            task_manager,
+            model_manager,
+        )
+    }
+
+    /// Creates the agent, shared for tests that spawn tasks using it.
+    pub async fn build_arc(self) -> Arc<Agent> {
+        Arc::new(self.build().await)
+    }
+}
+
+/// Task storage of the agent built in `dir`.
+pub fn storage(dir: &TempDir) -> Arc<LocalStorage> {
+    Arc::new(LocalStorage::new(dir.path().join("tasks")).unwrap())
+}
+
+/// Returns an identity of a peer the agent trusts.
+pub async fn trusted_peer(agent: &Agent) -> AgentIdentity {
+    let identity = AgentIdentity::new(20, semaphore::Field::from(0))
+        .await
+        .unwrap();
+    agent
+        .identity
+        .trust_peer(&identity.public_key_bytes())
+        .unwrap();
+    identity
+}
+
+/// Signs `message` with `identity`.
+pub fn signed(identity: &AgentIdentity, mut message: Message) -> Message {
+    message.signature = Some(identity.sign_data(&message.to_signable_bytes()).unwrap());
+    message.public_key = Some(identity.public_key_bytes());
+    message
+}
+
+/// Returns the contents of the messages the agent broadcast so far.
+pub async fn broadcasts(agent: &Agent) -> Vec<MessageType> {
+    let messages = agent.network_manager.lock().await.get_messages().await;
+    messages
+        .iter()
+        .filter_map(|msg| serde_json::from_slice::<Message>(&msg.content).ok())
+        .map(|message| message.content)
+        .collect()
+}
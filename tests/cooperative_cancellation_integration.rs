@@ -0,0 +1,100 @@
+//! Integration tests for cooperative cancellation and deadlines of local tasks.
+
+use p2p_ai_agents::agent::task::{Task, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::Agent;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Arc<Agent> {
+    TestAgent::new(dir)
+        .name("cancelling-agent")
+        .build_arc()
+        .await
+}
+
+fn custom_task(data: serde_json::Value) -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::Custom("simulated".to_string()),
+            data,
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+/// Waits until no worker is busy and returns how long that took.
+async fn wait_until_idle(agent: &Agent) -> Duration {
+    let started = Instant::now();
+    while agent.worker_pool.active_workers() > 0 {
+        assert!(started.elapsed() < Duration::from_secs(10));
+        sleep(Duration::from_millis(10)).await;
+    }
+    started.elapsed()
+}
+
+#[tokio::test]
+async fn test_cancelled_executor_stops_before_grace_period() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+
+    let id = agent
+        .submit_task(custom_task(json!({ "duration_ms": 20_000 })))
+        .await;
+    agent.process_next_task().await.unwrap();
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(agent.task_status(&id).await.unwrap(), TaskStatus::Running);
+
+    agent.cancel_task(id).await.unwrap();
+    // The executor noticed the token instead of waiting to be aborted
+    assert!(wait_until_idle(&agent).await < Duration::from_secs(1));
+    assert_eq!(agent.task_status(&id).await.unwrap(), TaskStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn test_executor_stops_at_deadline() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+
+    let mut task = custom_task(json!({ "duration_ms": 20_000 }));
+    task.deadline = Some(SystemTime::now() + Duration::from_millis(100));
+    let id = agent.submit_task(task).await;
+    agent.process_next_task().await.unwrap();
+
+    assert!(wait_until_idle(&agent).await < Duration::from_secs(2));
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(agent.task_status(&id).await.unwrap(), TaskStatus::Timeout);
+}
+
+#[tokio::test]
+async fn test_dependents_of_task_past_deadline_fail() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+
+    let mut parent = custom_task(json!({ "duration_ms": 20_000 }));
+    parent.deadline = Some(SystemTime::now() + Duration::from_millis(100));
+    let mut child = custom_task(json!({ "duration_ms": 10 }));
+    child.depends_on = vec![parent.id];
+    let parent_id = agent.submit_task(parent).await;
+    let child_id = agent.submit_task(child).await;
+    agent.process_next_task().await.unwrap();
+
+    wait_until_idle(&agent).await;
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(
+        agent.task_status(&parent_id).await.unwrap(),
+        TaskStatus::Timeout
+    );
+    // Local deadlines are not retried, so the child would otherwise wait forever
+    assert_eq!(
+        agent.task_status(&child_id).await.unwrap(),
+        TaskStatus::Failed(format!("Dependency {} timed out", parent_id))
+    );
+}
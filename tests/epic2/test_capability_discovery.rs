@@ -10,6 +10,7 @@ async fn test_capability_discovery_via_announcement() {
         name: "agent-a-discovery".to_string(),
         capabilities: vec![], // No capabilities
         models: vec![],
+        ..Default::default()
     };
 
     let agent_a = DefaultAgent::new(config_a.clone()).await.unwrap();
@@ -22,6 +23,7 @@ async fn test_capability_discovery_via_announcement() {
         name: "agent-b-discovery".to_string(),
         capabilities: vec![TaskType::VectorComputation], // Unique capability
         models: vec![],
+        ..Default::default()
     };
 
     let agent_b = DefaultAgent::new(config_b.clone()).await.unwrap();
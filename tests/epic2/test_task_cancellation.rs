@@ -15,6 +15,7 @@ async fn create_test_agent(name: &str, port: u16, capabilities: Vec<TaskType>) -
         name: name.to_string(),
         capabilities,
         models: vec![],
+        ..Default::default()
     };
 
     let network_config = NetworkConfig {
@@ -106,6 +107,7 @@ async fn test_remote_task_cancellation() {
                 capabilities: PeerCapabilities {
                     supported_tasks: vec![TaskType::Custom("LongRunning".to_string())],
                     supported_models: vec![],
+                    ..Default::default()
                 },
                 status: ConnectionStatus::Connected,
             })
@@ -10,6 +10,7 @@ async fn test_task_dispatch_flow() {
         name: "agent-a".to_string(),
         capabilities: vec![], // No capabilities needed for requestor
         models: vec![],
+        ..Default::default()
     };
 
     let agent_a = DefaultAgent::new(config_a.clone()).await.unwrap();
@@ -22,6 +23,7 @@ async fn test_task_dispatch_flow() {
         name: "agent-b".to_string(),
         capabilities: vec![TaskType::TextProcessing], // Capable of TextProcessing
         models: vec![],
+        ..Default::default()
     };
 
     let agent_b = DefaultAgent::new(config_b.clone()).await.unwrap();
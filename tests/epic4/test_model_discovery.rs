@@ -10,6 +10,7 @@ async fn test_ai_model_discovery() {
         name: "agent-a-ai-discovery".to_string(),
         capabilities: vec![],
         models: vec![],
+        ..Default::default()
     };
 
     let agent_a = DefaultAgent::new(config_a.clone()).await.unwrap();
@@ -21,6 +22,7 @@ async fn test_ai_model_discovery() {
         name: "agent-b-ai-discovery".to_string(),
         capabilities: vec![TaskType::AiInference],
         models: vec![model_name.to_string()],
+        ..Default::default()
     };
 
     let agent_b = DefaultAgent::new(config_b.clone()).await.unwrap();
@@ -11,6 +11,7 @@ async fn test_remote_ai_execution() {
         name: "client-agent".to_string(),
         capabilities: vec![],
         models: vec![],
+        ..Default::default()
     };
     let client = DefaultAgent::new(config_client).await.unwrap();
     client.start().await.unwrap();
@@ -22,6 +23,7 @@ async fn test_remote_ai_execution() {
         name: "server-agent".to_string(),
         capabilities: vec![TaskType::TextProcessing], // Supports text processing (embedding)
         models: vec![model_name.to_string()],
+        ..Default::default()
     };
     let server = DefaultAgent::new(config_server).await.unwrap();
     server.start().await.unwrap();
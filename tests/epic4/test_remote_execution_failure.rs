@@ -11,6 +11,7 @@ async fn test_remote_execution_failure_handling() {
         name: "client-agent-failure-test".to_string(),
         capabilities: vec![],
         models: vec![],
+        ..Default::default()
     };
     let client = DefaultAgent::new(config_client).await.unwrap();
     client.start().await.unwrap();
@@ -25,6 +26,7 @@ async fn test_remote_execution_failure_handling() {
             TaskType::Custom("simulate_long_work".to_string()),
         ],
         models: vec![model_name.to_string()],
+        ..Default::default()
     };
     let server = DefaultAgent::new(config_server).await.unwrap();
     server.start().await.unwrap();
@@ -11,6 +11,7 @@ async fn test_remote_execution_retry_success() {
         name: "client-agent-retry-test".to_string(),
         capabilities: vec![],
         models: vec![],
+        ..Default::default()
     };
     let client = DefaultAgent::new(config_client).await.unwrap();
     client.start().await.unwrap();
@@ -20,6 +21,7 @@ async fn test_remote_execution_retry_success() {
         name: "server-agent-a".to_string(),
         capabilities: vec![TaskType::Custom("fail_task".to_string())],
         models: vec![],
+        ..Default::default()
     };
     let server_a = DefaultAgent::new(config_server_a).await.unwrap();
     server_a.start().await.unwrap();
@@ -29,6 +31,7 @@ async fn test_remote_execution_retry_success() {
         name: "server-agent-b".to_string(),
         capabilities: vec![TaskType::Custom("fail_task".to_string())], // Same capability
         models: vec![],
+        ..Default::default()
     };
     let server_b = DefaultAgent::new(config_server_b).await.unwrap();
     server_b.start().await.unwrap();
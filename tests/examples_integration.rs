@@ -94,6 +94,7 @@ async fn test_hello_agent_runs() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "test-hello-agent".to_string(),
         models: vec![],
+        ..Default::default()
     };
 
     let agent = DefaultAgent::new(config).await?;
@@ -117,6 +118,7 @@ async fn test_task_processing_workflow() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "test-task-agent".to_string(),
         models: vec![],
+        ..Default::default()
     };
 
     let agent = DefaultAgent::new(config).await?;
@@ -165,6 +167,7 @@ async fn test_batch_task_processing() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "test-batch-agent".to_string(),
         models: vec![],
+        ..Default::default()
     };
 
     let agent = DefaultAgent::new(config).await?;
@@ -211,6 +214,7 @@ async fn test_task_cancellation() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "test-cancel-agent".to_string(),
         models: vec![],
+        ..Default::default()
     };
 
     let agent = DefaultAgent::new(config).await?;
@@ -244,12 +248,14 @@ async fn test_network_peer_communication() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "net-test-1".to_string(),
         models: vec![],
+        ..Default::default()
     };
 
     let config2 = AgentConfig {
         capabilities: vec![],
         name: "net-test-2".to_string(),
         models: vec![],
+        ..Default::default()
     };
 
     let agent1 = DefaultAgent::new(config1).await?;
@@ -276,6 +282,7 @@ async fn test_complete_workflow_integration() -> Result<(), Box<dyn Error>> {
         capabilities: vec![],
         name: "integration-test-agent".to_string(),
         models: vec![],
+        ..Default::default()
     };
 
     let agent = DefaultAgent::new(config).await?;
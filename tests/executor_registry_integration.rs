@@ -0,0 +1,149 @@
+//! Integration tests for executing tasks through the executor registry.
+
+use p2p_ai_agents::agent::messaging::{Message, MessageType};
+use p2p_ai_agents::agent::task::{
+    Task, TaskExecutor, TaskPayload, TaskPriority, TaskStatus, TaskType,
+};
+use p2p_ai_agents::agent::{Agent, AgentConfig};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir, capabilities: Vec<TaskType>) -> Arc<Agent> {
+    TestAgent::new(dir)
+        .config(AgentConfig {
+            name: "registry-agent".to_string(),
+            capabilities,
+            ..Default::default()
+        })
+        .build_arc()
+        .await
+}
+
+fn task(task_type: TaskType, data: serde_json::Value) -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type,
+            data,
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+async fn wait_for_finish(agent: &Agent, task: &Task) -> TaskStatus {
+    for _ in 0..200 {
+        match agent.task_status(&task.id).await.unwrap() {
+            TaskStatus::Queued | TaskStatus::Running => sleep(Duration::from_millis(10)).await,
+            status => return status,
+        }
+    }
+    panic!("Task {} did not finish", task.id);
+}
+
+/// Reverses the payload's `text`.
+struct Reverse;
+
+#[async_trait::async_trait]
+impl TaskExecutor for Reverse {
+    async fn execute(&self, payload: &TaskPayload) -> anyhow::Result<serde_json::Value> {
+        let text = payload.data["text"].as_str().unwrap_or_default();
+        Ok(json!(text.chars().rev().collect::<String>()))
+    }
+}
+
+#[tokio::test]
+async fn test_runtime_registered_executor_runs_and_is_advertised() {
+    let dir = TempDir::new().unwrap();
+    let simulated = TaskType::Custom("simulated".to_string());
+    let agent = create_agent(&dir, vec![TaskType::TextProcessing, simulated.clone()]).await;
+    let reverse = TaskType::Custom("reverse".to_string());
+
+    assert!(agent.capabilities().contains(&TaskType::TextProcessing));
+    assert!(agent.capabilities().contains(&simulated));
+    assert!(!agent.capabilities().contains(&reverse));
+    // Built-in types run locally, but are only advertised if listed
+    assert!(agent
+        .executor_registry
+        .supports(&TaskType::VectorComputation));
+    assert!(!agent.capabilities().contains(&TaskType::VectorComputation));
+
+    agent.register_executor(reverse.clone(), Reverse).await;
+    assert!(agent.capabilities().contains(&reverse));
+
+    // The updated capabilities are announced
+    let messages = agent.network_manager.lock().await.get_messages().await;
+    let announced = messages.iter().rev().find_map(|msg| {
+        let message: Message = serde_json::from_slice(&msg.content).ok()?;
+        match message.content {
+            MessageType::CapabilityAnnouncement { capabilities, .. } => Some(capabilities),
+            _ => None,
+        }
+    });
+    assert!(announced.unwrap().contains(&reverse));
+
+    let reversed = task(reverse, json!({ "text": "registry" }));
+    agent.submit_task(reversed.clone()).await;
+    agent.process_next_task().await.unwrap();
+    assert_eq!(
+        wait_for_finish(&agent, &reversed).await,
+        TaskStatus::Completed(json!("yrtsiger"))
+    );
+}
+
+#[tokio::test]
+async fn test_task_without_executor_fails() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, vec![]).await;
+    assert!(agent
+        .executor_registry
+        .unregister(&TaskType::VectorComputation)
+        .is_some());
+    assert!(!agent.capabilities().contains(&TaskType::VectorComputation));
+
+    let vector = task(TaskType::VectorComputation, json!({}));
+    agent.submit_task(vector.clone()).await;
+    agent.process_next_task().await.unwrap();
+    assert_eq!(
+        wait_for_finish(&agent, &vector).await,
+        TaskStatus::Failed("No executor registered for VectorComputation".to_string())
+    );
+
+    // Custom types without an executor of their own are simulated
+    let custom = task(
+        TaskType::Custom("unlisted".to_string()),
+        json!({ "duration_ms": 20 }),
+    );
+    agent.submit_task(custom.clone()).await;
+    agent.process_next_task().await.unwrap();
+    assert_eq!(
+        wait_for_finish(&agent, &custom).await,
+        TaskStatus::Completed(json!({"status": "simulated_custom_execution"}))
+    );
+}
+
+#[tokio::test]
+async fn test_ai_task_types_are_served_without_the_ai_feature() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, vec![TaskType::AiInference]).await;
+    assert!(agent.capabilities().contains(&TaskType::AiInference));
+    assert!(agent.executor_registry.supports(&TaskType::AiModelDownload));
+
+    // Inference tasks are run by the text processing executor
+    let inference = task(
+        TaskType::AiInference,
+        json!({ "operation": "reverse", "text": "model" }),
+    );
+    agent.submit_task(inference.clone()).await;
+    agent.process_next_task().await.unwrap();
+    assert_eq!(
+        wait_for_finish(&agent, &inference).await,
+        TaskStatus::Completed(json!({ "reversed_text": "ledom" }))
+    );
+}
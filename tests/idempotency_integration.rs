@@ -0,0 +1,83 @@
+//! Integration tests for idempotent task submission.
+
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::Message;
+use p2p_ai_agents::agent::task::{Task, TaskStatus};
+use p2p_ai_agents::agent::Agent;
+use serde_json::json;
+use tempfile::TempDir;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Agent {
+    TestAgent::new(dir)
+        .name("idempotent-executor")
+        .build()
+        .await
+}
+
+/// Signs a task request as `requester` and hands it to `agent`.
+async fn deliver_request(agent: &Agent, requester: &AgentIdentity, task: Task) {
+    let mut message = Message::new_task_request("requester", agent.id(), task);
+    message.signature = Some(requester.sign_data(&message.to_signable_bytes()).unwrap());
+    message.public_key = Some(requester.public_key_bytes());
+    agent.handle_message(message).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_redelivered_request_is_not_run_again() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let requester = AgentIdentity::new(20, semaphore::Field::from(0))
+        .await
+        .unwrap();
+    agent
+        .identity
+        .trust_peer(&requester.public_key_bytes())
+        .unwrap();
+
+    let task = Task::new("count words");
+    let id = task.id;
+    deliver_request(&agent, &requester, task.clone()).await;
+    agent
+        .task_manager
+        .update_status(id, TaskStatus::Completed(json!({ "word_count": 2 })))
+        .await
+        .unwrap();
+
+    // Gossip re-delivery of the same request keeps the cached result
+    deliver_request(&agent, &requester, task).await;
+    assert_eq!(agent.task_manager.list_tasks().await.len(), 1);
+    assert_eq!(agent.task_manager.queued_count().await, 0);
+    assert_eq!(
+        agent.task_status(&id).await.unwrap(),
+        TaskStatus::Completed(json!({ "word_count": 2 }))
+    );
+}
+
+#[tokio::test]
+async fn test_retry_with_same_idempotency_key_is_deduplicated() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let requester = AgentIdentity::new(20, semaphore::Field::from(0))
+        .await
+        .unwrap();
+    agent
+        .identity
+        .trust_peer(&requester.public_key_bytes())
+        .unwrap();
+
+    let mut first = Task::new("embed document");
+    first.idempotency_key = Some("req-7".to_string());
+    deliver_request(&agent, &requester, first.clone()).await;
+
+    // The requester retries with a fresh task ID but the same key
+    let mut retry = Task::new("embed document");
+    retry.idempotency_key = Some("req-7".to_string());
+    deliver_request(&agent, &requester, retry.clone()).await;
+
+    assert_eq!(agent.task_manager.list_tasks().await.len(), 1);
+    assert!(agent.task_manager.get_task(retry.id).await.is_none());
+    assert_eq!(agent.submit_task(retry).await, first.id);
+}
@@ -0,0 +1,112 @@
+//! Integration tests for peer selection in `dispatch_task` and peer exclusion on retry.
+
+use p2p_ai_agents::agent::peer_selection::{PeerCandidate, PeerSelector};
+use p2p_ai_agents::agent::task::{Task, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::Agent;
+use p2p_ai_agents::network::{ConnectionStatus, Multiaddr, PeerCapabilities, PeerId, PeerInfo};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Agent {
+    TestAgent::new(dir).name("selector-requester").build().await
+}
+
+async fn add_peer(agent: &Agent, id: &str, reputation: i32) {
+    let nm = agent.network_manager.lock().await;
+    nm.peer_cache
+        .upsert_peer(PeerInfo {
+            peer_id: PeerId(id.to_string()),
+            addresses: vec![Multiaddr("/ip4/127.0.0.1/tcp/0".to_string())],
+            last_seen: chrono::Utc::now(),
+            reputation,
+            capabilities: PeerCapabilities::new(vec![TaskType::VectorComputation], vec![]),
+            status: ConnectionStatus::Connected,
+        })
+        .await;
+}
+
+fn vector_task() -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::VectorComputation,
+            data: json!({ "operation": "sum", "values": [1.0, 2.0] }),
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+async fn assigned_peer(agent: &Agent, id: p2p_ai_agents::agent::task::TaskId) -> Option<String> {
+    agent.task_manager.get_task(id).await.unwrap().assigned_to
+}
+
+#[tokio::test]
+async fn test_retry_never_returns_to_failed_peer() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    add_peer(&agent, "peer-a", 100).await;
+    add_peer(&agent, "peer-b", 100).await;
+
+    let id = agent.submit_task(vector_task()).await;
+    agent.dispatch_task(id).await.unwrap();
+    let first = assigned_peer(&agent, id).await.unwrap();
+
+    agent.retry_task(id).await.unwrap();
+    let second = assigned_peer(&agent, id).await.unwrap();
+    assert_ne!(first, second);
+
+    // Both peers have now failed the task, so the next retry has nowhere to go
+    agent.retry_task(id).await.unwrap();
+    let task = agent.task_manager.get_task(id).await.unwrap();
+    assert_eq!(task.excluded_peers.len(), 2);
+    assert!(matches!(task.status, TaskStatus::Failed(_)));
+}
+
+#[tokio::test]
+async fn test_least_loaded_is_default() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    add_peer(&agent, "peer-a", 100).await;
+    add_peer(&agent, "peer-b", 100).await;
+
+    let first = agent.submit_task(vector_task()).await;
+    agent.dispatch_task(first).await.unwrap();
+    let second = agent.submit_task(vector_task()).await;
+    agent.dispatch_task(second).await.unwrap();
+
+    assert_ne!(
+        assigned_peer(&agent, first).await,
+        assigned_peer(&agent, second).await
+    );
+}
+
+/// Always picks the candidate with the lexicographically largest ID.
+struct LastIdSelector;
+
+impl PeerSelector for LastIdSelector {
+    fn select(&self, _task: &Task, candidates: &[PeerCandidate]) -> Option<PeerId> {
+        candidates
+            .iter()
+            .map(|c| c.peer_id.clone())
+            .max_by(|a, b| a.0.cmp(&b.0))
+    }
+}
+
+#[tokio::test]
+async fn test_custom_selector() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir)
+        .await
+        .with_peer_selector(Arc::new(LastIdSelector));
+    add_peer(&agent, "peer-a", 900).await;
+    add_peer(&agent, "peer-z", 0).await;
+
+    let id = agent.submit_task(vector_task()).await;
+    agent.dispatch_task(id).await.unwrap();
+    assert_eq!(assigned_peer(&agent, id).await.as_deref(), Some("peer-z"));
+}
@@ -0,0 +1,148 @@
+//! Integration tests for recovering remote tasks after a restart.
+
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::Message;
+use p2p_ai_agents::agent::task::{Task, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::Agent;
+use p2p_ai_agents::network::{ConnectionStatus, Multiaddr, PeerCapabilities, PeerId, PeerInfo};
+use serde_json::json;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+mod common;
+use common::{signed, TestAgent};
+
+async fn create_agent(dir: &TempDir) -> Agent {
+    TestAgent::new(dir)
+        .name("recovering-requester")
+        .build()
+        .await
+}
+
+/// Adds a trusted text processing peer to the requester and returns its identity.
+async fn add_executor(agent: &Agent, name: &str) -> AgentIdentity {
+    let identity = AgentIdentity::new(20, semaphore::Field::from(0))
+        .await
+        .unwrap();
+    agent
+        .identity
+        .trust_peer(&identity.public_key_bytes())
+        .unwrap();
+
+    let nm = agent.network_manager.lock().await;
+    nm.peer_cache
+        .upsert_peer(PeerInfo {
+            peer_id: PeerId(name.to_string()),
+            addresses: vec![Multiaddr("/ip4/127.0.0.1/tcp/0".to_string())],
+            last_seen: chrono::Utc::now(),
+            reputation: 100,
+            capabilities: PeerCapabilities::new(vec![TaskType::TextProcessing], vec![]),
+            status: ConnectionStatus::Connected,
+        })
+        .await;
+    identity
+}
+
+#[tokio::test]
+async fn test_task_lost_by_peer_is_retried_elsewhere() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let exec_a = add_executor(&agent, "exec-a").await;
+    let exec_b = add_executor(&agent, "exec-b").await;
+
+    let id = agent
+        .submit_task(Task::with_payload(
+            TaskPriority::Normal,
+            TaskPayload {
+                task_type: TaskType::TextProcessing,
+                data: json!({ "operation": "word_count", "text": "one two" }),
+                parameters: HashMap::new(),
+            },
+        ))
+        .await;
+    agent.dispatch_task(id).await.unwrap();
+    let assigned = agent.task_manager.get_task(id).await.unwrap().assigned_to;
+    let (owner, owner_identity, other, other_identity) = if assigned.as_deref() == Some("exec-a") {
+        ("exec-a", &exec_a, "exec-b", &exec_b)
+    } else {
+        ("exec-b", &exec_b, "exec-a", &exec_a)
+    };
+
+    // After a restart the requester asks the owner, which no longer knows the task
+    agent.query_task_status(id).await;
+
+    // A peer that was never given the task cannot declare it lost
+    let message = Message::new_task_unknown(other, "broadcast", id);
+    agent
+        .handle_message(signed(other_identity, message))
+        .await
+        .unwrap();
+    assert_eq!(
+        agent.task_manager.get_task(id).await.unwrap().assigned_to,
+        Some(owner.to_string())
+    );
+
+    let message = Message::new_task_unknown(owner, "broadcast", id);
+    agent
+        .handle_message(signed(owner_identity, message))
+        .await
+        .unwrap();
+    let task = agent.task_manager.get_task(id).await.unwrap();
+    assert_eq!(task.retry_count, 1);
+    assert_eq!(task.assigned_to, Some(other.to_string()));
+    assert!(task.excluded_peers.contains(&owner.to_string()));
+    assert_eq!(task.status, TaskStatus::Queued);
+}
+
+#[tokio::test]
+async fn test_lost_task_fails_once_retries_are_exhausted() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let exec_a = add_executor(&agent, "exec-a").await;
+    add_executor(&agent, "exec-b").await;
+
+    let mut task = Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::TextProcessing,
+            data: json!({ "operation": "word_count", "text": "one two" }),
+            parameters: HashMap::new(),
+        },
+    );
+    task.max_retries = 0;
+    let id = agent.submit_task(task).await;
+    agent
+        .task_manager
+        .assign_task(id, "exec-a".to_string())
+        .await
+        .unwrap();
+
+    let message = Message::new_task_unknown("exec-a", "broadcast", id);
+    agent
+        .handle_message(signed(&exec_a, message))
+        .await
+        .unwrap();
+    let task = agent.task_manager.get_task(id).await.unwrap();
+    assert_eq!(task.retry_count, 0);
+    assert_eq!(
+        task.status,
+        TaskStatus::Failed("Exceeded max retries (0)".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_status_query_is_answered() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let requester = add_executor(&agent, "requester").await;
+
+    let id = agent.submit_task(Task::new("known")).await;
+    for task_id in [id, uuid::Uuid::new_v4()] {
+        let message = Message::new_task_status_query("requester", agent.id(), task_id);
+        agent
+            .handle_message(signed(&requester, message))
+            .await
+            .unwrap();
+    }
+    assert_eq!(agent.task_status(&id).await.unwrap(), TaskStatus::Queued);
+}
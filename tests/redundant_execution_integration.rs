@@ -0,0 +1,214 @@
+//! Integration tests for redundant (k-of-n) task execution with result voting.
+
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::Message;
+use p2p_ai_agents::agent::task::{Task, TaskId, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::voting::{Redundancy, ResultComparator};
+use p2p_ai_agents::agent::Agent;
+use p2p_ai_agents::network::reputation::STARTING_REPUTATION;
+use p2p_ai_agents::network::{ConnectionStatus, Multiaddr, PeerCapabilities, PeerId, PeerInfo};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tempfile::TempDir;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Agent {
+    TestAgent::new(dir).name("voting-requester").build().await
+}
+
+/// Adds trusted executor peers to the requester and returns their identities.
+async fn add_executors(agent: &Agent, names: &[&str]) -> Vec<(String, AgentIdentity)> {
+    let mut executors = vec![];
+    for name in names {
+        let identity = AgentIdentity::new(20, semaphore::Field::from(0))
+            .await
+            .unwrap();
+        agent
+            .identity
+            .trust_peer(&identity.public_key_bytes())
+            .unwrap();
+
+        let nm = agent.network_manager.lock().await;
+        nm.peer_cache
+            .upsert_peer(PeerInfo {
+                peer_id: PeerId(name.to_string()),
+                addresses: vec![Multiaddr("/ip4/127.0.0.1/tcp/0".to_string())],
+                last_seen: chrono::Utc::now(),
+                reputation: 100,
+                capabilities: PeerCapabilities::new(vec![TaskType::VectorComputation], vec![]),
+                status: ConnectionStatus::Connected,
+            })
+            .await;
+        executors.push((name.to_string(), identity));
+    }
+    executors
+}
+
+async fn respond(agent: &Agent, from: &(String, AgentIdentity), task_id: TaskId, result: Value) {
+    let (name, identity) = from;
+    let mut message =
+        Message::new_task_response(name, "broadcast", task_id, TaskStatus::Completed(result));
+    message.signature = Some(identity.sign_data(&message.to_signable_bytes()).unwrap());
+    message.public_key = Some(identity.public_key_bytes());
+    agent.handle_message(message).await.unwrap();
+}
+
+fn redundant_task(redundancy: Redundancy) -> Task {
+    let mut task = Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::VectorComputation,
+            data: json!({ "operation": "normalize", "values": [3.0, 4.0] }),
+            parameters: HashMap::new(),
+        },
+    );
+    task.redundancy = Some(redundancy);
+    task
+}
+
+async fn score(agent: &Agent, peer: &str) -> i32 {
+    agent
+        .reputation_manager
+        .read()
+        .await
+        .get_score(peer)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_two_of_three_vote_accepts_majority() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let executors = add_executors(&agent, &["exec-a", "exec-b", "exec-c"]).await;
+
+    let id = agent
+        .submit_task(redundant_task(Redundancy::new(2, 3)))
+        .await;
+    agent.dispatch_task(id).await.unwrap();
+    assert_eq!(
+        agent
+            .task_manager
+            .get_task(id)
+            .await
+            .unwrap()
+            .replicas
+            .len(),
+        3
+    );
+
+    respond(&agent, &executors[1], id, json!([0.0, 0.0])).await;
+    respond(&agent, &executors[0], id, json!([0.6, 0.8])).await;
+    assert_eq!(agent.task_status(&id).await.unwrap(), TaskStatus::Running);
+
+    respond(&agent, &executors[2], id, json!([0.6, 0.8])).await;
+    assert_eq!(
+        agent.task_status(&id).await.unwrap(),
+        TaskStatus::Completed(json!([0.6, 0.8]))
+    );
+    assert!(score(&agent, "exec-b").await < STARTING_REPUTATION);
+    assert!(score(&agent, "exec-a").await > STARTING_REPUTATION);
+    assert!(score(&agent, "exec-c").await > STARTING_REPUTATION);
+}
+
+#[tokio::test]
+async fn test_float_tolerance_and_late_dissent() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let executors = add_executors(&agent, &["exec-a", "exec-b", "exec-c"]).await;
+
+    let id = agent
+        .submit_task(redundant_task(Redundancy {
+            replicas: 3,
+            quorum: 2,
+            comparator: ResultComparator::FloatTolerance { epsilon: 1e-3 },
+        }))
+        .await;
+    agent.dispatch_task(id).await.unwrap();
+
+    respond(&agent, &executors[0], id, json!([0.6, 0.8])).await;
+    respond(&agent, &executors[1], id, json!([0.6001, 0.7999])).await;
+    assert!(matches!(
+        agent.task_status(&id).await.unwrap(),
+        TaskStatus::Completed(_)
+    ));
+
+    // A contradicting result after the decision is still reported
+    respond(&agent, &executors[2], id, json!([1.0, 0.0])).await;
+    assert!(score(&agent, "exec-c").await < STARTING_REPUTATION);
+    assert_eq!(
+        agent.task_status(&id).await.unwrap(),
+        TaskStatus::Completed(json!([0.6, 0.8]))
+    );
+}
+
+#[tokio::test]
+async fn test_not_enough_peers_for_quorum() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    add_executors(&agent, &["exec-a"]).await;
+
+    let id = agent
+        .submit_task(redundant_task(Redundancy::new(2, 3)))
+        .await;
+    assert!(agent.dispatch_task(id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_silent_replicas_time_out_as_failed_votes() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let executors = add_executors(&agent, &["exec-a", "exec-b", "exec-c"]).await;
+
+    let mut task = redundant_task(Redundancy::new(2, 3));
+    task.payload.as_mut().unwrap().data["timeout_ms"] = json!(100);
+    let id = agent.submit_task(task).await;
+    agent.dispatch_task(id).await.unwrap();
+    let dispatched = agent.task_manager.get_task(id).await.unwrap();
+    assert_eq!(dispatched.status, TaskStatus::Running);
+    assert!(dispatched.started_at.is_some());
+
+    // Only one replica answers, so the quorum is out of reach once the others
+    // time out
+    respond(&agent, &executors[0], id, json!([0.6, 0.8])).await;
+    agent.check_task_timeouts().await.unwrap();
+    assert_eq!(agent.task_status(&id).await.unwrap(), TaskStatus::Running);
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    agent.check_task_timeouts().await.unwrap();
+    assert_eq!(
+        agent.task_status(&id).await.unwrap(),
+        TaskStatus::Failed("No 2 of 3 peers agreed (1 distinct results, 2 failed)".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_votes_are_collected_again_after_restart() {
+    let dir = TempDir::new().unwrap();
+    let id = {
+        let agent = create_agent(&dir).await;
+        add_executors(&agent, &["exec-a", "exec-b", "exec-c"]).await;
+        let id = agent
+            .submit_task(redundant_task(Redundancy::new(2, 3)))
+            .await;
+        agent.dispatch_task(id).await.unwrap();
+        id
+    };
+
+    let agent = create_agent(&dir).await;
+    let executors = add_executors(&agent, &["exec-a", "exec-b", "exec-c"]).await;
+    let recovery = agent.recover().await.unwrap();
+    assert_eq!(recovery.remote, vec![id]);
+
+    // A single replica's answer is a vote, not the result
+    respond(&agent, &executors[0], id, json!([0.6, 0.8])).await;
+    assert_eq!(agent.task_status(&id).await.unwrap(), TaskStatus::Running);
+
+    respond(&agent, &executors[2], id, json!([0.6, 0.8])).await;
+    assert_eq!(
+        agent.task_status(&id).await.unwrap(),
+        TaskStatus::Completed(json!([0.6, 0.8]))
+    );
+}
@@ -0,0 +1,102 @@
+//! Integration tests for per-requester task quotas.
+
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::Message;
+use p2p_ai_agents::agent::quota::{QuotaConfig, TierQuota};
+use p2p_ai_agents::agent::task::{Task, TaskStatus};
+use p2p_ai_agents::agent::{Agent, AgentConfig};
+use serde_json::json;
+use tempfile::TempDir;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Agent {
+    TestAgent::new(dir).name("quota-executor").build().await
+}
+
+async fn trusted_requester(agent: &Agent) -> AgentIdentity {
+    let identity = AgentIdentity::new(20, semaphore::Field::from(0))
+        .await
+        .unwrap();
+    agent
+        .identity
+        .trust_peer(&identity.public_key_bytes())
+        .unwrap();
+    identity
+}
+
+/// Sends a new task from `requester` and returns whether it was accepted.
+async fn request(agent: &Agent, requester: &str, identity: &AgentIdentity) -> Option<Task> {
+    let task = Task::new("quota");
+    let mut message = Message::new_task_request(requester, agent.id(), task.clone());
+    message.signature = Some(identity.sign_data(&message.to_signable_bytes()).unwrap());
+    message.public_key = Some(identity.public_key_bytes());
+    agent.handle_message(message).await.unwrap();
+    agent.task_manager.get_task(task.id).await
+}
+
+#[tokio::test]
+async fn test_newcomer_is_held_to_concurrent_quota() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let identity = trusted_requester(&agent).await;
+    let limit = QuotaConfig::default().newcomer.concurrent;
+
+    let mut accepted = Vec::new();
+    for _ in 0..limit {
+        let task = request(&agent, "newcomer", &identity).await.unwrap();
+        assert_eq!(task.requested_by.as_deref(), Some("newcomer"));
+        accepted.push(task.id);
+    }
+    assert!(request(&agent, "newcomer", &identity).await.is_none());
+
+    // Finishing a task frees a slot
+    agent
+        .task_manager
+        .update_status(accepted[0], TaskStatus::Completed(json!(null)))
+        .await
+        .unwrap();
+    assert!(request(&agent, "newcomer", &identity).await.is_some());
+}
+
+#[tokio::test]
+async fn test_reputable_requester_gets_higher_quota() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let identity = trusted_requester(&agent).await;
+    {
+        let mut reputation = agent.reputation_manager.write().await;
+        reputation.register_agent("veteran".to_string());
+        reputation.increase_reputation("veteran", 900).unwrap();
+    }
+
+    for _ in 0..QuotaConfig::default().newcomer.concurrent + 1 {
+        assert!(request(&agent, "veteran", &identity).await.is_some());
+    }
+}
+
+#[tokio::test]
+async fn test_quotas_come_from_config() {
+    let dir = TempDir::new().unwrap();
+    let quotas = QuotaConfig {
+        newcomer: TierQuota {
+            concurrent: 5,
+            per_window: 1,
+        },
+        ..Default::default()
+    };
+    let agent = TestAgent::new(&dir)
+        .config(AgentConfig {
+            name: "quota-executor".to_string(),
+            quotas,
+            ..Default::default()
+        })
+        .build()
+        .await;
+    let identity = trusted_requester(&agent).await;
+
+    assert!(request(&agent, "newcomer", &identity).await.is_some());
+    // Over the configured window quota, though below the concurrent one
+    assert!(request(&agent, "newcomer", &identity).await.is_none());
+}
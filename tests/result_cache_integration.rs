@@ -0,0 +1,83 @@
+//! Integration tests for answering task requests from the result cache.
+
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::Message;
+use p2p_ai_agents::agent::result_cache::{ResultCache, ResultCacheConfig};
+use p2p_ai_agents::agent::task::{
+    Task, TaskManager, TaskPayload, TaskPriority, TaskStatus, TaskType,
+};
+use p2p_ai_agents::agent::Agent;
+use p2p_ai_agents::storage::local::LocalStorage;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Agent {
+    let cache = ResultCache::new(
+        Arc::new(LocalStorage::new(dir.path().join("cache")).unwrap()),
+        ResultCacheConfig::default(),
+    );
+    TestAgent::new(dir)
+        .name("caching-executor")
+        .task_manager(TaskManager::new(common::storage(dir)).with_result_cache(cache))
+        .build()
+        .await
+}
+
+fn word_count_task() -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::TextProcessing,
+            data: json!({ "operation": "word_count", "text": "one two three" }),
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_repeated_request_is_answered_from_cache() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let requester = AgentIdentity::new(20, semaphore::Field::from(0))
+        .await
+        .unwrap();
+    agent
+        .identity
+        .trust_peer(&requester.public_key_bytes())
+        .unwrap();
+
+    // Run the first request locally
+    let first = word_count_task();
+    let mut message = Message::new_task_request("requester", agent.id(), first.clone());
+    message.signature = Some(requester.sign_data(&message.to_signable_bytes()).unwrap());
+    message.public_key = Some(requester.public_key_bytes());
+    agent.handle_message(message).await.unwrap();
+    assert_eq!(agent.process_next_task().await.unwrap(), Some(first.id));
+    for _ in 0..50 {
+        if matches!(
+            agent.task_status(&first.id).await.unwrap(),
+            TaskStatus::Completed(_)
+        ) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let result = agent.task_status(&first.id).await.unwrap();
+    assert!(matches!(result, TaskStatus::Completed(_)));
+
+    // Another request for the same payload completes without being queued
+    let second = word_count_task();
+    let mut message = Message::new_task_request("requester", agent.id(), second.clone());
+    message.signature = Some(requester.sign_data(&message.to_signable_bytes()).unwrap());
+    message.public_key = Some(requester.public_key_bytes());
+    agent.handle_message(message).await.unwrap();
+
+    assert_eq!(agent.task_status(&second.id).await.unwrap(), result);
+    assert_eq!(agent.task_manager.queued_count().await, 0);
+    assert_eq!(agent.process_next_task().await.unwrap(), None);
+}
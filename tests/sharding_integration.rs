@@ -0,0 +1,199 @@
+//! Integration tests for map-reduce sharding of tasks across peers.
+
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::Message;
+use p2p_ai_agents::agent::sharding::Sharding;
+use p2p_ai_agents::agent::task::{Task, TaskId, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::Agent;
+use p2p_ai_agents::network::{ConnectionStatus, Multiaddr, PeerCapabilities, PeerId, PeerInfo};
+use serde_json::json;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Agent {
+    TestAgent::new(dir).name("sharding-requester").build().await
+}
+
+/// Adds trusted text processing peers to the requester and returns their identities.
+async fn add_executors(agent: &Agent, names: &[&str]) -> HashMap<String, AgentIdentity> {
+    let mut executors = HashMap::new();
+    for name in names {
+        let identity = AgentIdentity::new(20, semaphore::Field::from(0))
+            .await
+            .unwrap();
+        agent
+            .identity
+            .trust_peer(&identity.public_key_bytes())
+            .unwrap();
+
+        let nm = agent.network_manager.lock().await;
+        nm.peer_cache
+            .upsert_peer(PeerInfo {
+                peer_id: PeerId(name.to_string()),
+                addresses: vec![Multiaddr("/ip4/127.0.0.1/tcp/0".to_string())],
+                last_seen: chrono::Utc::now(),
+                reputation: 100,
+                capabilities: PeerCapabilities::new(vec![TaskType::TextProcessing], vec![]),
+                status: ConnectionStatus::Connected,
+            })
+            .await;
+        executors.insert(name.to_string(), identity);
+    }
+    executors
+}
+
+/// Sends the response of the peer a shard is assigned to.
+async fn respond(
+    agent: &Agent,
+    executors: &HashMap<String, AgentIdentity>,
+    shard_id: TaskId,
+    status: TaskStatus,
+) {
+    let shard = agent.task_manager.get_task(shard_id).await.unwrap();
+    let peer = shard.assigned_to.unwrap();
+    let identity = &executors[&peer];
+    let mut message = Message::new_task_response(&peer, "broadcast", shard_id, status);
+    message.signature = Some(identity.sign_data(&message.to_signable_bytes()).unwrap());
+    message.public_key = Some(identity.public_key_bytes());
+    agent.handle_message(message).await.unwrap();
+}
+
+fn word_count_task(corpus: Vec<&str>, max_shards: usize) -> Task {
+    let mut task = Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::TextProcessing,
+            data: json!({ "operation": "word_count", "corpus": corpus }),
+            parameters: HashMap::new(),
+        },
+    );
+    task.sharding = Some(Sharding::new(max_shards));
+    task
+}
+
+#[tokio::test]
+async fn test_shards_on_distinct_peers_are_reduced() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let executors = add_executors(&agent, &["exec-a", "exec-b", "exec-c"]).await;
+
+    let id = agent
+        .submit_task(word_count_task(
+            vec!["one two", "three", "four five six", "seven"],
+            8,
+        ))
+        .await;
+    agent.dispatch_task(id).await.unwrap();
+
+    // Shards are limited by the number of capable peers
+    let parent = agent.task_manager.get_task(id).await.unwrap();
+    assert_eq!(parent.status, TaskStatus::Running);
+    assert_eq!(parent.shards.len(), 3);
+
+    let mut peers = vec![];
+    for shard_id in &parent.shards {
+        let shard = agent.task_manager.get_task(*shard_id).await.unwrap();
+        assert_eq!(shard.shard_of, Some(id));
+        peers.push(shard.assigned_to.unwrap());
+    }
+    peers.sort();
+    peers.dedup();
+    assert_eq!(peers.len(), 3);
+
+    for (shard_id, count) in parent.shards.iter().zip([3, 3, 1]) {
+        respond(
+            &agent,
+            &executors,
+            *shard_id,
+            TaskStatus::Completed(json!({ "word_count": count })),
+        )
+        .await;
+    }
+    assert_eq!(
+        agent.task_status(&id).await.unwrap(),
+        TaskStatus::Completed(json!({ "word_count": 7 }))
+    );
+}
+
+#[tokio::test]
+async fn test_failed_shard_is_retried_alone() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let executors = add_executors(&agent, &["exec-a", "exec-b", "exec-c"]).await;
+
+    let id = agent
+        .submit_task(word_count_task(vec!["a b", "c d e"], 2))
+        .await;
+    agent.dispatch_task(id).await.unwrap();
+    let shards = agent.task_manager.get_task(id).await.unwrap().shards;
+
+    let first = agent.task_manager.get_task(shards[0]).await.unwrap();
+    let failed_peer = first.assigned_to.clone().unwrap();
+    respond(
+        &agent,
+        &executors,
+        shards[0],
+        TaskStatus::Failed("worker crashed".to_string()),
+    )
+    .await;
+
+    // Only the failed shard moved to another peer
+    let retried = agent.task_manager.get_task(shards[0]).await.unwrap();
+    assert_eq!(retried.status, TaskStatus::Queued);
+    assert_eq!(retried.retry_count, 1);
+    assert_ne!(retried.assigned_to, Some(failed_peer.clone()));
+    assert!(retried.excluded_peers.contains(&failed_peer));
+    assert_eq!(agent.task_status(&id).await.unwrap(), TaskStatus::Running);
+
+    respond(
+        &agent,
+        &executors,
+        shards[1],
+        TaskStatus::Completed(json!({ "word_count": 3 })),
+    )
+    .await;
+    respond(
+        &agent,
+        &executors,
+        shards[0],
+        TaskStatus::Completed(json!({ "word_count": 2 })),
+    )
+    .await;
+    assert_eq!(
+        agent.task_status(&id).await.unwrap(),
+        TaskStatus::Completed(json!({ "word_count": 5 }))
+    );
+}
+
+#[tokio::test]
+async fn test_exhausted_shard_fails_parent() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let executors = add_executors(&agent, &["exec-a", "exec-b"]).await;
+
+    let mut task = word_count_task(vec!["a", "b"], 2);
+    task.max_retries = 0;
+    let id = agent.submit_task(task).await;
+    agent.dispatch_task(id).await.unwrap();
+    let shards = agent.task_manager.get_task(id).await.unwrap().shards;
+
+    respond(
+        &agent,
+        &executors,
+        shards[0],
+        TaskStatus::Failed("bad input".to_string()),
+    )
+    .await;
+
+    assert!(matches!(
+        agent.task_status(&id).await.unwrap(),
+        TaskStatus::Failed(_)
+    ));
+    assert_eq!(
+        agent.task_status(&shards[1]).await.unwrap(),
+        TaskStatus::Cancelled
+    );
+}
@@ -0,0 +1,112 @@
+//! Integration tests for running allow-listed local commands as tasks.
+
+use p2p_ai_agents::agent::executors::subprocess::{CommandSpec, SubprocessConfig};
+use p2p_ai_agents::agent::task::{Task, TaskId, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::{Agent, AgentConfig};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Arc<Agent> {
+    TestAgent::new(dir)
+        .config(AgentConfig {
+            name: "subprocess-agent".to_string(),
+            subprocess: SubprocessConfig {
+                commands: BTreeMap::from([
+                    // Upper-cases a JSON string
+                    ("upper".to_string(), CommandSpec::new("tr", ["a-z", "A-Z"])),
+                    ("hang".to_string(), CommandSpec::new("sleep", ["30"])),
+                ]),
+                scratch_dir: dir.path().join("scratch"),
+                cgroup_parent: None,
+            },
+            ..Default::default()
+        })
+        .build_arc()
+        .await
+}
+
+fn command_task(name: &str, input: serde_json::Value) -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::Custom(name.to_string()),
+            data: json!({ "input": input }),
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+async fn wait_for_status(agent: &Agent, id: &TaskId, done: fn(&TaskStatus) -> bool) -> TaskStatus {
+    for _ in 0..500 {
+        let status = agent.task_status(id).await.unwrap();
+        if done(&status) {
+            return status;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Task {} did not reach the expected status", id);
+}
+
+#[tokio::test]
+async fn test_allow_listed_command_runs_as_task() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let capabilities = agent.capabilities();
+    assert!(capabilities.contains(&TaskType::Custom("upper".to_string())));
+    assert!(capabilities.contains(&TaskType::Custom("hang".to_string())));
+
+    let id = agent
+        .submit_task(command_task("upper", json!("hello tools")))
+        .await;
+    agent.process_next_task().await.unwrap().unwrap();
+    let status = wait_for_status(&agent, &id, |status| {
+        !matches!(status, TaskStatus::Queued | TaskStatus::Running)
+    })
+    .await;
+    assert_eq!(status, TaskStatus::Completed(json!("HELLO TOOLS")));
+}
+
+#[tokio::test]
+async fn test_cancelled_command_is_killed_and_cleaned_up() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+
+    let id = agent.submit_task(command_task("hang", json!(null))).await;
+    agent.process_next_task().await.unwrap().unwrap();
+    wait_for_status(&agent, &id, |status| *status == TaskStatus::Running).await;
+    // Give the command time to start in its scratch directory
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(
+        std::fs::read_dir(dir.path().join("scratch"))
+            .unwrap()
+            .count(),
+        1
+    );
+
+    agent.cancel_task(id).await.unwrap();
+    let status = wait_for_status(&agent, &id, |status| {
+        !matches!(status, TaskStatus::Queued | TaskStatus::Running)
+    })
+    .await;
+    assert_eq!(status, TaskStatus::Cancelled);
+
+    // The executor stops well within the cancellation grace period
+    for _ in 0..100 {
+        if std::fs::read_dir(dir.path().join("scratch"))
+            .unwrap()
+            .count()
+            == 0
+        {
+            return;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Scratch directory was not removed");
+}
@@ -0,0 +1,179 @@
+//! Integration tests for task checkpoints and resuming retried tasks from them.
+
+use p2p_ai_agents::agent::checkpoint::Checkpoint;
+use p2p_ai_agents::agent::messaging::{Message, MessageType};
+use p2p_ai_agents::agent::task::{Task, TaskId, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::{Agent, AgentConfig};
+use p2p_ai_agents::network::{ConnectionStatus, Multiaddr, PeerCapabilities, PeerId, PeerInfo};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+mod common;
+use common::{broadcasts, signed, trusted_peer, TestAgent};
+
+async fn create_agent(dir: &TempDir, config: AgentConfig) -> Arc<Agent> {
+    TestAgent::new(dir).config(config).build_arc().await
+}
+
+fn sleep_task(duration_ms: u64) -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::Custom("sleep".to_string()),
+            data: json!({ "duration_ms": duration_ms }),
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+fn checkpoint(sequence: u64, step: u64) -> Checkpoint {
+    Checkpoint {
+        sequence,
+        state: json!({ "step": step }),
+        created_at: SystemTime::now(),
+    }
+}
+
+async fn wait_for_completion(agent: &Agent, id: TaskId) {
+    for _ in 0..200 {
+        if let TaskStatus::Completed(_) = agent.task_status(&id).await.unwrap() {
+            return;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Task {} did not complete", id);
+}
+
+#[tokio::test]
+async fn test_requested_task_resumes_and_pushes_checkpoints() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, AgentConfig::default()).await;
+    let requester = trusted_peer(&agent).await;
+
+    // A previous peer got through the first step before going offline
+    let mut task = sleep_task(400);
+    task.checkpoint = Some(checkpoint(1, 1));
+    let request = Message::new_task_request("requester", agent.id(), task.clone());
+    agent
+        .handle_message(signed(&requester, request))
+        .await
+        .unwrap();
+
+    assert_eq!(agent.process_next_task().await.unwrap(), Some(task.id));
+    wait_for_completion(&agent, task.id).await;
+
+    let pushed: Vec<Checkpoint> = broadcasts(&agent)
+        .await
+        .into_iter()
+        .filter_map(|content| match content {
+            MessageType::TaskCheckpoint {
+                task_id,
+                checkpoint,
+            } if task_id == task.id => Some(checkpoint),
+            _ => None,
+        })
+        .collect();
+    let steps: Vec<(u64, serde_json::Value)> = pushed
+        .into_iter()
+        .map(|checkpoint| (checkpoint.sequence, checkpoint.state))
+        .collect();
+    assert_eq!(
+        steps,
+        vec![(2, json!({ "step": 2 })), (3, json!({ "step": 3 }))]
+    );
+
+    // Checkpoints are dropped once the task completes
+    let stored = agent.task_manager.get_task(task.id).await.unwrap();
+    assert_eq!(stored.checkpoint, None);
+}
+
+#[tokio::test]
+async fn test_local_checkpoints_are_kept_but_not_pushed() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, AgentConfig::default()).await;
+
+    let id = agent.submit_task(sleep_task(800)).await;
+    agent.process_next_task().await.unwrap();
+
+    sleep(Duration::from_millis(500)).await;
+    let task = agent.task_manager.get_task(id).await.unwrap();
+    assert_eq!(task.status, TaskStatus::Running);
+    assert!(task.checkpoint.unwrap().sequence >= 1);
+
+    wait_for_completion(&agent, id).await;
+    let pushed = broadcasts(&agent)
+        .await
+        .into_iter()
+        .any(|content| matches!(content, MessageType::TaskCheckpoint { .. }));
+    assert!(!pushed);
+}
+
+#[tokio::test]
+async fn test_retry_sends_checkpoint_from_assigned_peer() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, AgentConfig::default()).await;
+    let peer = trusted_peer(&agent).await;
+    for id in ["peer-a", "peer-b"] {
+        let nm = agent.network_manager.lock().await;
+        nm.peer_cache
+            .upsert_peer(PeerInfo {
+                peer_id: PeerId(id.to_string()),
+                addresses: vec![Multiaddr("/ip4/127.0.0.1/tcp/0".to_string())],
+                last_seen: chrono::Utc::now(),
+                reputation: 100,
+                capabilities: PeerCapabilities::new(
+                    vec![TaskType::Custom("sleep".to_string())],
+                    vec![],
+                ),
+                status: ConnectionStatus::Connected,
+            })
+            .await;
+    }
+
+    let id = agent.submit_task(sleep_task(400)).await;
+    agent.dispatch_task(id).await.unwrap();
+    let first = agent
+        .task_manager
+        .get_task(id)
+        .await
+        .unwrap()
+        .assigned_to
+        .unwrap();
+    let other = if first == "peer-a" {
+        "peer-b"
+    } else {
+        "peer-a"
+    };
+
+    // Only the peer running the task can checkpoint it
+    for (sender, checkpoint) in [
+        (first.as_str(), checkpoint(2, 2)),
+        (other, checkpoint(5, 3)),
+    ] {
+        let message = Message::new_task_checkpoint(sender, agent.id(), id, checkpoint);
+        agent.handle_message(signed(&peer, message)).await.unwrap();
+    }
+    // Stale checkpoints do not replace newer ones
+    let stale = Message::new_task_checkpoint(first.as_str(), agent.id(), id, checkpoint(1, 1));
+    agent.handle_message(signed(&peer, stale)).await.unwrap();
+    let task = agent.task_manager.get_task(id).await.unwrap();
+    assert_eq!(task.checkpoint.as_ref().unwrap().sequence, 2);
+
+    agent.retry_task(id).await.unwrap();
+    let resent = broadcasts(&agent)
+        .await
+        .into_iter()
+        .rev()
+        .find_map(|content| match content {
+            MessageType::TaskRequest(task) if task.id == id => Some(task),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(resent.checkpoint, task.checkpoint);
+    let retried = agent.task_manager.get_task(id).await.unwrap();
+    assert_eq!(retried.assigned_to.as_deref(), Some(other));
+}
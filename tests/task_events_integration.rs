@@ -0,0 +1,123 @@
+//! Integration tests for task lifecycle events published by the agent.
+
+use async_trait::async_trait;
+use p2p_ai_agents::agent::events::{TaskCompleted, TaskDispatched};
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::Message;
+use p2p_ai_agents::agent::task::{Task, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::Agent;
+use p2p_ai_agents::core::events::{EventHandler, EventResult};
+use p2p_ai_agents::network::{ConnectionStatus, Multiaddr, PeerCapabilities, PeerId, PeerInfo};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::sync::Mutex;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Agent {
+    TestAgent::new(dir).name("events-requester").build().await
+}
+
+/// Collects dispatch and completion events.
+#[derive(Clone, Default)]
+struct Subscriber {
+    dispatched: Arc<Mutex<Vec<TaskDispatched>>>,
+    completed: Arc<Mutex<Vec<TaskCompleted>>>,
+}
+
+#[async_trait]
+impl EventHandler<TaskDispatched> for Subscriber {
+    async fn handle(&self, event: &TaskDispatched) -> EventResult {
+        self.dispatched.lock().await.push(event.clone());
+        EventResult::Success
+    }
+
+    fn name(&self) -> &'static str {
+        "Subscriber"
+    }
+}
+
+#[async_trait]
+impl EventHandler<TaskCompleted> for Subscriber {
+    async fn handle(&self, event: &TaskCompleted) -> EventResult {
+        self.completed.lock().await.push(event.clone());
+        EventResult::Success
+    }
+
+    fn name(&self) -> &'static str {
+        "Subscriber"
+    }
+}
+
+#[tokio::test]
+async fn test_dispatch_and_remote_completion_are_published() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let subscriber = Subscriber::default();
+    agent
+        .event_bus()
+        .subscribe::<TaskDispatched, _>(subscriber.clone())
+        .await
+        .unwrap();
+    agent
+        .event_bus()
+        .subscribe::<TaskCompleted, _>(subscriber.clone())
+        .await
+        .unwrap();
+
+    let executor = AgentIdentity::new(20, semaphore::Field::from(0))
+        .await
+        .unwrap();
+    agent
+        .identity
+        .trust_peer(&executor.public_key_bytes())
+        .unwrap();
+    {
+        let nm = agent.network_manager.lock().await;
+        nm.peer_cache
+            .upsert_peer(PeerInfo {
+                peer_id: PeerId("exec-a".to_string()),
+                addresses: vec![Multiaddr("/ip4/127.0.0.1/tcp/0".to_string())],
+                last_seen: chrono::Utc::now(),
+                reputation: 100,
+                capabilities: PeerCapabilities::new(vec![TaskType::TextProcessing], vec![]),
+                status: ConnectionStatus::Connected,
+            })
+            .await;
+    }
+
+    let id = agent
+        .submit_task(Task::with_payload(
+            TaskPriority::Normal,
+            TaskPayload {
+                task_type: TaskType::TextProcessing,
+                data: json!({ "operation": "word_count", "text": "one two" }),
+                parameters: HashMap::new(),
+            },
+        ))
+        .await;
+    agent.dispatch_task(id).await.unwrap();
+
+    let dispatched = subscriber.dispatched.lock().await.clone();
+    assert_eq!(dispatched.len(), 1);
+    assert_eq!(dispatched[0].payload.task_id, id);
+    assert_eq!(dispatched[0].payload.peers, vec!["exec-a".to_string()]);
+
+    let mut response = Message::new_task_response(
+        "exec-a",
+        "broadcast",
+        id,
+        TaskStatus::Completed(json!({ "word_count": 2 })),
+    );
+    response.signature = Some(executor.sign_data(&response.to_signable_bytes()).unwrap());
+    response.public_key = Some(executor.public_key_bytes());
+    agent.handle_message(response).await.unwrap();
+
+    let completed = subscriber.completed.lock().await.clone();
+    assert_eq!(completed.len(), 1);
+    assert_eq!(completed[0].payload.task_id, id);
+    assert_eq!(completed[0].payload.result, json!({ "word_count": 2 }));
+}
@@ -0,0 +1,123 @@
+//! Integration tests for batch submission and task groups.
+
+use p2p_ai_agents::agent::groups::GroupStatus;
+use p2p_ai_agents::agent::task::{Task, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::Agent;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::time::{sleep, timeout};
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Arc<Agent> {
+    TestAgent::new(dir).name("batch-agent").build_arc().await
+}
+
+fn custom_task(duration_ms: u64) -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::Custom("simulated".to_string()),
+            data: json!({ "duration_ms": duration_ms }),
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_group_resolves_once_every_task_finished() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+
+    // A task submitted earlier under the same key does not join the group
+    let mut earlier = custom_task(10);
+    earlier.idempotency_key = Some("repeated".to_string());
+    agent.submit_task(earlier).await;
+    let mut repeated = custom_task(10);
+    repeated.idempotency_key = Some("repeated".to_string());
+
+    let group = agent
+        .submit_batch(vec![
+            custom_task(10),
+            custom_task(10),
+            custom_task(10),
+            repeated,
+        ])
+        .await;
+    let status = agent.group_status(group).await;
+    assert_eq!(status.total, 3);
+    assert_eq!(status.queued, 3);
+
+    let waiter = {
+        let agent = agent.clone();
+        tokio::spawn(async move { agent.wait_for_group(group).await })
+    };
+    sleep(Duration::from_millis(20)).await;
+    assert!(!waiter.is_finished());
+
+    // Run the earlier task and every member of the group
+    for _ in 0..4 {
+        agent.process_next_task().await.unwrap().unwrap();
+    }
+    let status = timeout(Duration::from_secs(5), waiter)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        status,
+        GroupStatus {
+            total: 3,
+            completed: 3,
+            ..Default::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_cancel_group_cancels_unfinished_tasks() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+
+    let mut finished = custom_task(10);
+    finished.priority = TaskPriority::High;
+    let finished_id = finished.id;
+    let group = agent
+        .submit_batch(vec![finished, custom_task(20_000), custom_task(20_000)])
+        .await;
+
+    assert_eq!(agent.process_next_task().await.unwrap(), Some(finished_id));
+    while !matches!(
+        agent.task_status(&finished_id).await.unwrap(),
+        TaskStatus::Completed(_)
+    ) {
+        sleep(Duration::from_millis(10)).await;
+    }
+    // One long task running, one still queued
+    agent.process_next_task().await.unwrap().unwrap();
+    sleep(Duration::from_millis(50)).await;
+    let status = agent.group_status(group).await;
+    assert_eq!((status.running, status.queued), (1, 1));
+
+    assert_eq!(agent.cancel_group(group).await.unwrap(), 2);
+    let status = timeout(Duration::from_secs(1), agent.wait_for_group(group))
+        .await
+        .unwrap();
+    assert_eq!(
+        status,
+        GroupStatus {
+            total: 3,
+            completed: 1,
+            cancelled: 2,
+            ..Default::default()
+        }
+    );
+    // Finished tasks keep their status
+    assert!(matches!(
+        agent.task_status(&finished_id).await.unwrap(),
+        TaskStatus::Completed(_)
+    ));
+}
@@ -0,0 +1,140 @@
+//! Integration tests for task progress reporting (local execution and remote requesters).
+
+use p2p_ai_agents::agent::identity::AgentIdentity;
+use p2p_ai_agents::agent::messaging::Message;
+use p2p_ai_agents::agent::task::{
+    ProgressUpdate, Task, TaskPayload, TaskPriority, TaskStatus, TaskType,
+};
+use p2p_ai_agents::agent::Agent;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+mod common;
+use common::{signed, trusted_peer, TestAgent};
+
+async fn create_agent(dir: &TempDir, name: &str) -> Arc<Agent> {
+    TestAgent::new(dir).name(name).build_arc().await
+}
+
+#[tokio::test]
+async fn test_local_execution_reports_progress() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir, "progress-local").await;
+
+    let task = Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::Custom("sleep".to_string()),
+            data: json!({ "duration_ms": 800 }),
+            parameters: HashMap::new(),
+        },
+    );
+    let id = agent.submit_task(task).await;
+    agent.process_next_task().await.unwrap();
+
+    // Halfway through, the simulated executor has reported intermediate progress
+    sleep(Duration::from_millis(500)).await;
+    let info = agent.task_manager.get_task_status(id).await.unwrap();
+    assert_eq!(info.status, TaskStatus::Running);
+    assert!(info.progress_percent.unwrap() >= 25);
+    assert!(info.estimated_completion_secs.is_some());
+}
+
+#[tokio::test]
+async fn test_remote_progress_updates_dispatched_task() {
+    let dir = TempDir::new().unwrap();
+    let requester = create_agent(&dir, "progress-requester").await;
+    let executor = AgentIdentity::new(20, semaphore::Field::from(0))
+        .await
+        .unwrap();
+    requester
+        .identity
+        .trust_peer(&executor.public_key_bytes())
+        .unwrap();
+
+    let id = requester.submit_task(Task::new("remote work")).await;
+    requester
+        .task_manager
+        .assign_task(id, "progress-executor".to_string())
+        .await
+        .unwrap();
+
+    let mut message = Message::new_task_progress(
+        "progress-executor",
+        "broadcast",
+        id,
+        ProgressUpdate {
+            percent: 50,
+            partial_output: Some(json!({"processed": 5})),
+            eta_secs: Some(30),
+        },
+    );
+    message.signature = Some(executor.sign_data(&message.to_signable_bytes()).unwrap());
+    message.public_key = Some(executor.public_key_bytes());
+    requester.handle_message(message).await.unwrap();
+
+    let info = requester.task_manager.get_task_status(id).await.unwrap();
+    assert_eq!(info.status, TaskStatus::Running);
+    assert_eq!(info.progress_percent, Some(50));
+    assert_eq!(info.partial_output, Some(json!({"processed": 5})));
+    assert!(info.estimated_completion_secs.unwrap() <= 30);
+}
+
+#[tokio::test]
+async fn test_progress_from_peers_not_running_the_task_is_ignored() {
+    let dir = TempDir::new().unwrap();
+    let requester = create_agent(&dir, "progress-requester").await;
+    let peer = trusted_peer(&requester).await;
+    let progress = |sender: &str, id, percent| {
+        let update = ProgressUpdate {
+            percent,
+            partial_output: None,
+            eta_secs: None,
+        };
+        signed(
+            &peer,
+            Message::new_task_progress(sender, "broadcast", id, update),
+        )
+    };
+
+    let id = requester.submit_task(Task::new("remote work")).await;
+    requester
+        .task_manager
+        .assign_task(id, "progress-executor".to_string())
+        .await
+        .unwrap();
+    requester
+        .handle_message(progress("stranger", id, 90))
+        .await
+        .unwrap();
+    let info = requester.task_manager.get_task_status(id).await.unwrap();
+    assert_eq!(info.status, TaskStatus::Queued);
+    assert_eq!(info.progress_percent, Some(0));
+
+    // Redundant tasks take progress from any of their replicas
+    let redundant = requester.submit_task(Task::new("voted work")).await;
+    requester
+        .task_manager
+        .set_replicas(
+            redundant,
+            vec!["replica-a".to_string(), "replica-b".to_string()],
+        )
+        .await
+        .unwrap();
+    for (sender, percent) in [("replica-b", 40), ("stranger", 90)] {
+        requester
+            .handle_message(progress(sender, redundant, percent))
+            .await
+            .unwrap();
+    }
+    let info = requester
+        .task_manager
+        .get_task_status(redundant)
+        .await
+        .unwrap();
+    assert_eq!(info.progress_percent, Some(40));
+}
@@ -0,0 +1,91 @@
+//! Integration tests for filtered and paginated task listings.
+
+use p2p_ai_agents::agent::query::{SortKey, SortOrder, StatusKind, TaskQuery};
+use p2p_ai_agents::agent::task::{Task, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::Agent;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir) -> Arc<Agent> {
+    TestAgent::new(dir).name("query-agent").build_arc().await
+}
+
+fn task(priority: TaskPriority, task_type: TaskType) -> Task {
+    let data = match task_type {
+        TaskType::TextProcessing => json!({ "operation": "word_count", "text": "a b" }),
+        _ => json!({ "operation": "sum", "values": [1.0] }),
+    };
+    Task::with_payload(
+        priority,
+        TaskPayload {
+            task_type,
+            data,
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_query_pages_through_matching_tasks() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+
+    let mut texts = Vec::new();
+    for _ in 0..5 {
+        texts.push(
+            agent
+                .submit_task(task(TaskPriority::Normal, TaskType::TextProcessing))
+                .await,
+        );
+    }
+    let vector = agent
+        .submit_task(task(TaskPriority::High, TaskType::VectorComputation))
+        .await;
+    agent.cancel_task(texts[0]).await.unwrap();
+
+    // Queued text tasks, two per page, newest first
+    let mut query = TaskQuery::new()
+        .with_status(StatusKind::Queued)
+        .with_task_type(TaskType::TextProcessing)
+        .sorted_by(SortKey::CreatedAt, SortOrder::Descending)
+        .limit(2);
+    let mut pages = 0;
+    let mut listed = Vec::new();
+    loop {
+        let page = agent.query_tasks(&query).await;
+        assert!(page.tasks.len() <= 2);
+        pages += 1;
+        listed.extend(page.tasks);
+        match page.next_cursor {
+            Some(cursor) => query = query.after(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(pages, 2);
+    assert!(listed
+        .windows(2)
+        .all(|pair| pair[0].created_at >= pair[1].created_at));
+    let mut ids: Vec<_> = listed.iter().map(|task| task.id).collect();
+    let mut expected = texts[1..].to_vec();
+    ids.sort();
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    let page = agent
+        .query_tasks(&TaskQuery::new().sorted_by(SortKey::Priority, SortOrder::Descending))
+        .await;
+    assert_eq!(page.tasks.len(), 6);
+    assert_eq!(page.tasks[0].id, vector);
+    assert!(page.next_cursor.is_none());
+
+    let page = agent
+        .query_tasks(&TaskQuery::new().with_status(StatusKind::Cancelled))
+        .await;
+    assert_eq!(page.tasks.len(), 1);
+    assert_eq!(page.tasks[0].status, TaskStatus::Cancelled);
+}
@@ -0,0 +1,255 @@
+//! Integration tests for validating task payloads against executor schemas.
+
+use p2p_ai_agents::agent::executors::schema::TaskSchema;
+use p2p_ai_agents::agent::messaging::{Message, MessageType};
+use p2p_ai_agents::agent::task::{
+    Task, TaskExecutor, TaskId, TaskPayload, TaskPriority, TaskStatus, TaskType,
+};
+use p2p_ai_agents::agent::{Agent, AgentConfig};
+use p2p_ai_agents::network::PeerId;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+mod common;
+use common::{broadcasts, signed, trusted_peer, TestAgent};
+
+async fn create_agent(dir: &TempDir) -> Arc<Agent> {
+    TestAgent::new(dir)
+        .config(AgentConfig {
+            name: "schema-agent".to_string(),
+            capabilities: vec![TaskType::VectorComputation],
+            ..Default::default()
+        })
+        .build_arc()
+        .await
+}
+
+fn similarity_task(data: serde_json::Value) -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::VectorComputation,
+            data,
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+/// Runs on payloads with a `prompt` string.
+struct Prompted;
+
+#[async_trait::async_trait]
+impl TaskExecutor for Prompted {
+    async fn execute(&self, payload: &TaskPayload) -> anyhow::Result<serde_json::Value> {
+        Ok(payload.data["prompt"].clone())
+    }
+
+    fn input_schema(&self) -> Option<serde_json::Value> {
+        Some(json!({
+            "type": "object",
+            "required": ["prompt"],
+            "properties": { "prompt": { "type": "string" } },
+        }))
+    }
+}
+
+#[tokio::test]
+async fn test_malformed_payload_fails_on_submission() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+
+    let malformed = similarity_task(json!({
+        "operation": "cosine_similarity",
+        "vector_a": [1.0, "two"],
+    }));
+    assert!(agent
+        .validate_payload(malformed.payload.as_ref().unwrap())
+        .is_err());
+    let id = agent.submit_task(malformed).await;
+    assert_eq!(
+        agent.task_status(&id).await.unwrap(),
+        TaskStatus::Failed(
+            "Invalid VectorComputation payload: \"vector_b\" is a required property; \
+             /vector_a/1: \"two\" is not of type \"number\""
+                .to_string()
+        )
+    );
+    // Nothing was queued
+    assert_eq!(agent.process_next_task().await.unwrap(), None);
+
+    let valid = similarity_task(json!({
+        "operation": "cosine_similarity",
+        "vector_a": [1.0, 0.0],
+        "vector_b": [1.0, 0.0],
+    }));
+    let id = agent.submit_task(valid).await;
+    assert_eq!(agent.process_next_task().await.unwrap(), Some(id));
+    for _ in 0..200 {
+        if let TaskStatus::Completed(result) = agent.task_status(&id).await.unwrap() {
+            assert_eq!(result, json!({ "similarity": 1.0 }));
+            return;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Valid task did not complete");
+}
+
+fn text_task(data: serde_json::Value) -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::TextProcessing,
+            data,
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+/// Runs queued tasks until the task has finished and returns its status.
+async fn run_until_finished(agent: &Agent, id: TaskId) -> TaskStatus {
+    for _ in 0..200 {
+        agent.process_next_task().await.unwrap();
+        match agent.task_status(&id).await.unwrap() {
+            TaskStatus::Queued | TaskStatus::Running => {}
+            status => return status,
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Task {} did not finish", id);
+}
+
+#[tokio::test]
+async fn test_dependent_payload_is_validated_after_resolution() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+
+    let parent = agent
+        .submit_task(text_task(
+            json!({ "operation": "reverse", "text": "hello" }),
+        ))
+        .await;
+    let counted = agent
+        .submit_task(text_task(
+            json!({ "operation": "word_count", "text": "one two" }),
+        ))
+        .await;
+
+    // The child's `text` is only a string once the parent's output is piped in
+    let mut piped = text_task(json!({
+        "operation": "reverse",
+        "text": { "$from_task": parent.to_string(), "$pointer": "/reversed_text" },
+    }));
+    piped.depends_on = vec![parent];
+    let piped = agent.submit_task(piped).await;
+    assert_eq!(agent.task_status(&piped).await.unwrap(), TaskStatus::Queued);
+
+    // A number piped into `text` fails the task once it is resolved
+    let mut miswired = text_task(json!({
+        "operation": "reverse",
+        "text": { "$from_task": counted.to_string(), "$pointer": "/word_count" },
+    }));
+    miswired.depends_on = vec![counted];
+    let miswired = agent.submit_task(miswired).await;
+    assert_eq!(
+        agent.task_status(&miswired).await.unwrap(),
+        TaskStatus::Queued
+    );
+
+    assert_eq!(
+        run_until_finished(&agent, piped).await,
+        TaskStatus::Completed(json!({ "reversed_text": "hello" }))
+    );
+    assert_eq!(
+        run_until_finished(&agent, miswired).await,
+        TaskStatus::Failed(
+            "Invalid TextProcessing payload: /text: 2 is not of type \"string\"".to_string()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_malformed_task_request_is_answered_with_failure() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let requester = trusted_peer(&agent).await;
+
+    let task = similarity_task(json!({ "vector_a": [1.0] }));
+    let request = Message::new_task_request("requester", agent.id(), task.clone());
+    agent
+        .handle_message(signed(&requester, request))
+        .await
+        .unwrap();
+
+    let reason = "Invalid VectorComputation payload: \"operation\" is a required property";
+    assert_eq!(
+        agent.task_status(&task.id).await.unwrap(),
+        TaskStatus::Failed(reason.to_string())
+    );
+    let answered = broadcasts(&agent).await.into_iter().any(|content| {
+        matches!(content, MessageType::TaskResponse { task_id, status }
+            if task_id == task.id && status == TaskStatus::Failed(reason.to_string()))
+    });
+    assert!(answered);
+    assert_eq!(agent.process_next_task().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_schemas_are_published_with_capabilities() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let prompted = TaskType::Custom("prompted".to_string());
+
+    agent.register_executor(prompted.clone(), Prompted).await;
+    let schemas = broadcasts(&agent)
+        .await
+        .into_iter()
+        .rev()
+        .find_map(|content| match content {
+            MessageType::CapabilityAnnouncement { schemas, .. } => Some(schemas),
+            _ => None,
+        })
+        .unwrap();
+    let published: Vec<&TaskType> = schemas.iter().map(|schema| &schema.task_type).collect();
+    assert!(published.contains(&&prompted));
+    assert!(published.contains(&&TaskType::VectorComputation));
+    // Types that are not advertised have their schemas kept private
+    assert!(!published.contains(&&TaskType::TextProcessing));
+    assert_eq!(
+        schemas
+            .iter()
+            .find(|schema| schema.task_type == prompted)
+            .unwrap()
+            .schema,
+        Prompted.input_schema().unwrap()
+    );
+
+    // Schemas announced by peers are kept with their capabilities
+    let peer = trusted_peer(&agent).await;
+    let announcement = Message::new_capability_announcement(
+        "peer",
+        vec![prompted.clone()],
+        vec![],
+        vec![TaskSchema {
+            task_type: prompted.clone(),
+            schema: Prompted.input_schema().unwrap(),
+        }],
+    );
+    agent
+        .handle_message(signed(&peer, announcement))
+        .await
+        .unwrap();
+    let info = agent
+        .network_manager
+        .lock()
+        .await
+        .peer_cache
+        .get_peer(&PeerId("peer".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(info.capabilities.schemas.len(), 1);
+    assert_eq!(info.capabilities.schemas[0].task_type, prompted);
+}
@@ -0,0 +1,163 @@
+//! Integration tests for running `Custom("wasm")` tasks in the WASM sandbox.
+#![cfg(feature = "wasm")]
+
+use base64::Engine as _;
+use p2p_ai_agents::agent::executors::wasm::{module_hash, WasmConfig, WASM_TASK_TYPE};
+use p2p_ai_agents::agent::task::{Task, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::{Agent, AgentConfig};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+mod common;
+use common::TestAgent;
+
+/// Returns the number of elements of the input array, as `{"count": n}`, by
+/// counting top-level commas.
+const COUNT: &str = r#"
+    (module
+      (memory (export "memory") 1)
+      (data (i32.const 0) "{\"count\":0}")
+      (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+      (func (export "run") (param $ptr i32) (param $len i32) (result i64)
+        (local $i i32) (local $count i32)
+        (if (i32.gt_u (local.get $len) (i32.const 2))
+          (then (local.set $count (i32.const 1))))
+        (block $done
+          (loop $next
+            (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+            (if (i32.eq (i32.load8_u (i32.add (local.get $ptr) (local.get $i))) (i32.const 44))
+              (then (local.set $count (i32.add (local.get $count) (i32.const 1)))))
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br $next)))
+        ;; Single digit counts only
+        (i32.store8 (i32.const 9) (i32.add (i32.const 48) (local.get $count)))
+        (i64.const 11)))
+"#;
+
+const SPIN: &str = r#"
+    (module
+      (memory (export "memory") 1)
+      (func (export "alloc") (param i32) (result i32) (i32.const 0))
+      (func (export "run") (param i32 i32) (result i64)
+        (loop $forever (br $forever))
+        (i64.const 0)))
+"#;
+
+async fn create_agent(dir: &TempDir) -> Arc<Agent> {
+    TestAgent::new(dir)
+        .config(AgentConfig {
+            name: "wasm-agent".to_string(),
+            capabilities: vec![TaskType::Custom(WASM_TASK_TYPE.to_string())],
+            wasm: WasmConfig {
+                module_dir: dir.path().join("wasm"),
+                fuel: 1_000_000,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .build_arc()
+        .await
+}
+
+fn wasm_task(data: serde_json::Value) -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::Custom(WASM_TASK_TYPE.to_string()),
+            data,
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+async fn run(agent: &Agent, task: Task) -> TaskStatus {
+    let id = agent.submit_task(task).await;
+    agent.process_next_task().await.unwrap().unwrap();
+    for _ in 0..500 {
+        match agent.task_status(&id).await.unwrap() {
+            TaskStatus::Queued | TaskStatus::Running => sleep(Duration::from_millis(10)).await,
+            status => return status,
+        }
+    }
+    panic!("Task {} did not finish", id);
+}
+
+#[tokio::test]
+async fn test_module_runs_and_is_cached() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    assert!(agent
+        .capabilities()
+        .contains(&TaskType::Custom(WASM_TASK_TYPE.to_string())));
+
+    let module = wat::parse_str(COUNT).unwrap();
+    let hash = module_hash(&module);
+
+    // Unknown modules must be sent along
+    let status = run(
+        &agent,
+        wasm_task(json!({ "module_hash": hash, "input": [] })),
+    )
+    .await;
+    assert_eq!(
+        status,
+        TaskStatus::Failed(format!("Module {} not found", hash))
+    );
+
+    let status = run(
+        &agent,
+        wasm_task(json!({
+            "module_hash": hash,
+            "module": base64::engine::general_purpose::STANDARD.encode(&module),
+            "input": [1, 2, 3],
+        })),
+    )
+    .await;
+    assert_eq!(status, TaskStatus::Completed(json!({"count": 3})));
+
+    // Later tasks reference the cached module by hash only
+    let status = run(
+        &agent,
+        wasm_task(json!({ "module_hash": hash, "input": ["a", "b"] })),
+    )
+    .await;
+    assert_eq!(status, TaskStatus::Completed(json!({"count": 2})));
+}
+
+#[tokio::test]
+async fn test_sandbox_rejects_mismatched_and_runaway_modules() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(&dir).await;
+    let encoded =
+        |wat: &str| base64::engine::general_purpose::STANDARD.encode(wat::parse_str(wat).unwrap());
+
+    // Module bytes that do not match the referenced hash are not cached or run
+    let status = run(
+        &agent,
+        wasm_task(json!({
+            "module_hash": module_hash(b"something else"),
+            "module": encoded(COUNT),
+        })),
+    )
+    .await;
+    assert!(
+        matches!(&status, TaskStatus::Failed(reason) if reason.starts_with("Module does not match hash")),
+        "{:?}",
+        status
+    );
+
+    let spin = wat::parse_str(SPIN).unwrap();
+    let status = run(
+        &agent,
+        wasm_task(json!({ "module_hash": module_hash(&spin), "module": encoded(SPIN) })),
+    )
+    .await;
+    assert_eq!(
+        status,
+        TaskStatus::Failed("Module ran out of fuel".to_string())
+    );
+}
@@ -0,0 +1,144 @@
+//! Integration tests for the agent worker pool (concurrent local execution).
+
+use p2p_ai_agents::agent::task::{Task, TaskPayload, TaskPriority, TaskStatus, TaskType};
+use p2p_ai_agents::agent::worker_pool::ConcurrencyLimit;
+use p2p_ai_agents::agent::{Agent, AgentConfig};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+mod common;
+use common::TestAgent;
+
+async fn create_agent(dir: &TempDir, config: AgentConfig) -> Arc<Agent> {
+    TestAgent::new(dir).config(config).build_arc().await
+}
+
+fn custom_task(kind: &str, duration_ms: u64) -> Task {
+    Task::with_payload(
+        TaskPriority::Normal,
+        TaskPayload {
+            task_type: TaskType::Custom(kind.to_string()),
+            data: json!({ "duration_ms": duration_ms }),
+            parameters: HashMap::new(),
+        },
+    )
+}
+
+async fn count_with_status(agent: &Agent, status: TaskStatus) -> usize {
+    agent
+        .task_manager
+        .list_tasks()
+        .await
+        .into_iter()
+        .filter(|t| t.status == status)
+        .count()
+}
+
+#[tokio::test]
+async fn test_global_concurrency_limit() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(
+        &dir,
+        AgentConfig {
+            name: "pool-global".to_string(),
+            max_concurrent_tasks: 2,
+            ..Default::default()
+        },
+    )
+    .await;
+
+    for _ in 0..3 {
+        agent.submit_task(custom_task("sleep", 500)).await;
+    }
+
+    assert!(agent.process_next_task().await.unwrap().is_some());
+    assert!(agent.process_next_task().await.unwrap().is_some());
+    // Pool is full: the third task stays queued
+    assert!(agent.process_next_task().await.unwrap().is_none());
+    assert_eq!(agent.worker_pool.active_workers(), 2);
+    assert_eq!(count_with_status(&agent, TaskStatus::Running).await, 2);
+    assert_eq!(count_with_status(&agent, TaskStatus::Queued).await, 1);
+
+    sleep(Duration::from_millis(800)).await;
+    assert_eq!(agent.worker_pool.active_workers(), 0);
+    assert!(agent.process_next_task().await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_per_type_limit_does_not_block_other_types() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(
+        &dir,
+        AgentConfig {
+            name: "pool-per-type".to_string(),
+            max_concurrent_tasks: 4,
+            task_type_limits: vec![ConcurrencyLimit {
+                task_type: TaskType::Custom("heavy".to_string()),
+                max_concurrent: 1,
+            }],
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let heavy_a = agent.submit_task(custom_task("heavy", 500)).await;
+    let heavy_b = agent.submit_task(custom_task("heavy", 500)).await;
+    let light = agent.submit_task(custom_task("light", 500)).await;
+
+    assert_eq!(agent.process_next_task().await.unwrap(), Some(heavy_a));
+    // heavy_b is next in line but its type is saturated, so light runs instead
+    assert_eq!(agent.process_next_task().await.unwrap(), Some(light));
+    assert!(agent.process_next_task().await.unwrap().is_none());
+    assert_eq!(
+        agent.task_status(&heavy_b).await.unwrap(),
+        TaskStatus::Queued
+    );
+}
+
+#[tokio::test]
+async fn test_submitted_tasks_run_concurrently_after_start() {
+    let dir = TempDir::new().unwrap();
+    let agent = create_agent(
+        &dir,
+        AgentConfig {
+            name: "pool-started".to_string(),
+            max_concurrent_tasks: 4,
+            ..Default::default()
+        },
+    )
+    .await;
+    agent.clone().start().await.unwrap();
+
+    let started = Instant::now();
+    let mut ids = vec![];
+    for _ in 0..4 {
+        ids.push(agent.submit_task(custom_task("sleep", 400)).await);
+    }
+
+    // Four 400ms tasks in parallel finish well before 4 x 400ms
+    loop {
+        let mut done = 0;
+        for id in &ids {
+            if matches!(
+                agent.task_status(id).await.unwrap(),
+                TaskStatus::Completed(_)
+            ) {
+                done += 1;
+            }
+        }
+        if done == ids.len() {
+            break;
+        }
+        assert!(
+            started.elapsed() < Duration::from_millis(1200),
+            "tasks did not run concurrently"
+        );
+        sleep(Duration::from_millis(20)).await;
+    }
+
+    agent.stop().await.unwrap();
+}